@@ -0,0 +1,154 @@
+//! Input distributions.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use sort_testing::Rng;
+
+/// The shape of a generated input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Distribution {
+    /// Uniformly random values within [0, n).
+    Random,
+    /// 0, 1, ..., n - 1.
+    Sorted,
+    /// n - 1, ..., 1, 0.
+    Reversed,
+    /// Ascending in the first half, descending in the second.
+    OrganPipe,
+    /// Random values out of 8 distinct ones.
+    FewUnique,
+    /// Ascending runs of √n elements.
+    Sawtooth,
+    /// Sorted, then `k` random pairs of elements are swapped.
+    NearlySorted,
+}
+
+impl Distribution {
+    /// All the distributions, in the order they are declared.
+    pub const ALL: [Distribution; 7] = [
+        Distribution::Random,
+        Distribution::Sorted,
+        Distribution::Reversed,
+        Distribution::OrganPipe,
+        Distribution::FewUnique,
+        Distribution::Sawtooth,
+        Distribution::NearlySorted,
+    ];
+
+    /// Returns the name of the distribution.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Distribution::Random => "random",
+            Distribution::Sorted => "sorted",
+            Distribution::Reversed => "reversed",
+            Distribution::OrganPipe => "organ-pipe",
+            Distribution::FewUnique => "few-unique",
+            Distribution::Sawtooth => "sawtooth",
+            Distribution::NearlySorted => "nearly-sorted",
+        }
+    }
+
+    /// Generates `n` values, `swaps` is the `k` of [`Distribution::NearlySorted`].
+    pub fn generate(&self, n: usize, swaps: usize, rng: &mut Rng) -> Vec<u32> {
+        match self {
+            Distribution::Random => (0..n).map(|_| rng.below(n) as u32).collect(),
+            Distribution::Sorted => (0..n as u32).collect(),
+            Distribution::Reversed => (0..n as u32).rev().collect(),
+            Distribution::OrganPipe => (0..n).map(|i| i.min(n - 1 - i) as u32).collect(),
+            Distribution::FewUnique => (0..n).map(|_| rng.below(8) as u32).collect(),
+            Distribution::Sawtooth => {
+                let tooth = ((n as f64).sqrt() as usize).max(1);
+                (0..n).map(|i| (i % tooth) as u32).collect()
+            }
+            Distribution::NearlySorted => {
+                let mut values: Vec<u32> = (0..n as u32).collect();
+                if n > 1 {
+                    for _ in 0..swaps {
+                        let (i, j) = (rng.below(n), rng.below(n));
+                        values.swap(i, j);
+                    }
+                }
+                values
+            }
+        }
+    }
+}
+
+impl fmt::Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Distribution {
+    type Err = ParseDistributionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Distribution::ALL
+            .iter()
+            .copied()
+            .find(|distribution| distribution.name() == s)
+            .ok_or_else(|| ParseDistributionError {
+                name: s.to_string(),
+            })
+    }
+}
+
+/// The error returned when parsing an unknown [`Distribution`] name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDistributionError {
+    name: String,
+}
+
+impl fmt::Display for ParseDistributionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown distribution `{}`", self.name)
+    }
+}
+
+impl Error for ParseDistributionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let mut rng = Rng::new(1);
+        assert_eq!(
+            Distribution::Reversed.generate(4, 0, &mut rng),
+            vec![3, 2, 1, 0]
+        );
+        assert_eq!(
+            Distribution::OrganPipe.generate(7, 0, &mut rng),
+            vec![0, 1, 2, 3, 2, 1, 0]
+        );
+        assert_eq!(
+            Distribution::Sawtooth.generate(9, 0, &mut rng),
+            vec![0, 1, 2, 0, 1, 2, 0, 1, 2]
+        );
+        assert!(Distribution::FewUnique
+            .generate(100, 0, &mut rng)
+            .iter()
+            .all(|&v| v < 8));
+        assert!(Distribution::Random
+            .generate(100, 0, &mut rng)
+            .iter()
+            .all(|&v| v < 100));
+        assert_eq!(Distribution::Random.generate(0, 0, &mut rng), vec![]);
+
+        let nearly = Distribution::NearlySorted.generate(100, 3, &mut rng);
+        let displaced = nearly.iter().enumerate().filter(|&(i, &v)| i as u32 != v);
+        assert!(displaced.count() <= 6);
+    }
+
+    #[test]
+    fn test_from_str() {
+        for distribution in Distribution::ALL.iter() {
+            assert_eq!(distribution.name().parse(), Ok(*distribution));
+        }
+        assert!("zigzag".parse::<Distribution>().is_err());
+    }
+}
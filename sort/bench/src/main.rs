@@ -0,0 +1,328 @@
+//! # Sort bench
+//!
+//! `sort-bench` sorts generated inputs of different distributions and
+//! sizes with every sort crate of the workspace and reports the wall time
+//! and the number of comparisons, swaps and moves of every sort.
+//!
+//! ```text
+//! sort-bench [--sizes 1000,10000,100000] [--distributions random,sorted,...]
+//!            [--quadratic-limit 10000] [--swaps 10] [--runs 1] [--seed 42]
+//!            [--format table|csv]
+//! ```
+//!
+//! `bubble` and `insertion` are skipped for inputs larger than the
+//! quadratic limit. The counts are taken from a separate observed sort.
+//!
+//! `quick` runs pattern-defeating quick sort, the introspective quick
+//! sort of the same crate is benchmarked next to it as `introsort`.
+
+mod distribution;
+mod report;
+
+use std::env;
+use std::io;
+use std::io::Write;
+use std::process;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use distribution::Distribution;
+use observe::SortStats;
+use report::{Format, Measurement};
+use sort::{Algorithm, Sorter};
+use sort_testing::Rng;
+
+const USAGE: &str = "usage: sort-bench [--sizes N,...] [--distributions NAME,...] \
+[--quadratic-limit N] [--swaps K] [--runs R] [--seed S] [--format table|csv]";
+
+/// The configuration of a benchmark.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Config {
+    sizes: Vec<usize>,
+    distributions: Vec<Distribution>,
+    /// Inputs larger than this are not sorted with the O(n^2) sorts.
+    quadratic_limit: usize,
+    /// The number of swapped pairs of [`Distribution::NearlySorted`].
+    swaps: usize,
+    /// The number of timed runs, the best time is reported.
+    runs: usize,
+    seed: u64,
+    format: Format,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            sizes: vec![1_000, 10_000, 100_000],
+            distributions: Distribution::ALL.to_vec(),
+            quadratic_limit: 10_000,
+            swaps: 10,
+            runs: 1,
+            seed: 42,
+            format: Format::Table,
+        }
+    }
+}
+
+/// Parses the value of the option.
+fn parse_value<T: FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{}` for {}", value, option))
+}
+
+/// Parses the comma separated list of values of the option.
+fn parse_list<T: FromStr>(option: &str, value: &str) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(|item| parse_value(option, item))
+        .collect()
+}
+
+/// Parses the command line arguments, without the program name.
+fn parse_args<I>(args: I) -> Result<Config, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut config = Config::default();
+    let mut args = args.into_iter();
+
+    while let Some(option) = args.next() {
+        if option == "--help" || option == "-h" {
+            return Err(USAGE.to_string());
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {}", option))?;
+
+        match option.as_str() {
+            "--sizes" => config.sizes = parse_list(&option, &value)?,
+            "--distributions" => config.distributions = parse_list(&option, &value)?,
+            "--quadratic-limit" => config.quadratic_limit = parse_value(&option, &value)?,
+            "--swaps" => config.swaps = parse_value(&option, &value)?,
+            "--runs" => config.runs = parse_value::<usize>(&option, &value)?.max(1),
+            "--seed" => config.seed = parse_value(&option, &value)?,
+            "--format" => {
+                config.format = match value.as_str() {
+                    "table" => Format::Table,
+                    "csv" => Format::Csv,
+                    _ => return Err(format!("invalid value `{}` for --format", value)),
+                }
+            }
+            _ => return Err(format!("unknown option `{}`\n{}", option, USAGE)),
+        }
+    }
+
+    Ok(config)
+}
+
+/// A sort which is benchmarked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Contender {
+    /// A comparison sort of the `sort` facade.
+    Comparison(Algorithm),
+    /// `quick::sort`, the introspective quick sort.
+    Introsort,
+    /// `radix::sort`.
+    Radix,
+    /// `counting::sort_by_key` over the range of the input.
+    Counting,
+}
+
+impl Contender {
+    /// All the benchmarked sorts.
+    fn all() -> Vec<Contender> {
+        let mut all: Vec<Contender> = Algorithm::ALL
+            .iter()
+            .map(|&algorithm| Contender::Comparison(algorithm))
+            .collect();
+        all.push(Contender::Introsort);
+        all.push(Contender::Radix);
+        all.push(Contender::Counting);
+        all
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Contender::Comparison(algorithm) => algorithm.name(),
+            Contender::Introsort => "introsort",
+            Contender::Radix => "radix",
+            Contender::Counting => "counting",
+        }
+    }
+
+    /// Returns whether the sort runs in O(n^2) time.
+    fn is_quadratic(&self) -> bool {
+        matches!(
+            self,
+            Contender::Comparison(Algorithm::Bubble) | Contender::Comparison(Algorithm::Insertion)
+        )
+    }
+
+    /// Sorts a copy of the input, returns the wall time and the number of comparisons.
+    fn run(&self, input: &[u32]) -> (Duration, Option<usize>) {
+        let mut values = input.to_vec();
+        let mut comparisons = 0;
+
+        let start = Instant::now();
+        match self {
+            Contender::Comparison(algorithm) => algorithm.sort_by(&mut values, |a, b| {
+                comparisons += 1;
+                a.cmp(b)
+            }),
+            Contender::Introsort => quick::sort_by(&mut values, |a, b| {
+                comparisons += 1;
+                a.cmp(b)
+            }),
+            Contender::Radix => radix::sort(&mut values),
+            Contender::Counting => {
+                let range = 0..input.iter().max().map_or(0, |&max| max as usize + 1);
+                counting::sort_by_key(&mut values, range, |&v| v as usize)
+                    .expect("the range covers every value");
+            }
+        }
+        let time = start.elapsed();
+
+        assert!(
+            values.windows(2).all(|w| w[0] <= w[1]),
+            "{} left the input unsorted",
+            self.name()
+        );
+
+        match self {
+            Contender::Comparison(_) | Contender::Introsort => (time, Some(comparisons)),
+            _ => (time, None),
+        }
+    }
+
+    /// Sorts a copy of the input with an observer.
+    fn observe(&self, input: &[u32]) -> Option<SortStats> {
+        match self {
+            Contender::Comparison(algorithm) => {
+                let mut stats = SortStats::default();
+                algorithm.sort_observed(&mut input.to_vec(), &mut stats);
+                Some(stats)
+            }
+            Contender::Introsort => {
+                let mut stats = SortStats::default();
+                quick::sort_observed(&mut input.to_vec(), &mut stats);
+                Some(stats)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Runs the benchmark, writing every measurement as soon as it is taken.
+fn bench<W: Write>(config: &Config, writer: &mut W) -> io::Result<()> {
+    let mut rng = Rng::new(config.seed);
+    config.format.write_header(writer)?;
+
+    for &distribution in &config.distributions {
+        for &size in &config.sizes {
+            let input = distribution.generate(size, config.swaps, &mut rng);
+
+            for contender in Contender::all() {
+                if contender.is_quadratic() && size > config.quadratic_limit {
+                    continue;
+                }
+
+                let (mut time, comparisons) = contender.run(&input);
+                for _ in 1..config.runs {
+                    time = time.min(contender.run(&input).0);
+                }
+                let stats = contender.observe(&input);
+
+                let measurement = Measurement {
+                    distribution,
+                    size,
+                    algorithm: contender.name(),
+                    time,
+                    comparisons,
+                    swaps: stats.map(|stats| stats.swaps),
+                    moves: stats.map(|stats| stats.moves),
+                };
+                config.format.write_row(&measurement, writer)?;
+            }
+        }
+    }
+
+    writer.flush()
+}
+
+fn main() {
+    let config = match parse_args(env::args().skip(1)) {
+        Ok(config) => config,
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(2);
+        }
+    };
+
+    let stdout = io::stdout();
+    if let Err(err) = bench(&config, &mut stdout.lock()) {
+        eprintln!("sort-bench: {}", err);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse_args(Vec::new()), Ok(Config::default()));
+
+        let config = parse_args(args(
+            "--sizes 10,20 --distributions sawtooth,sorted --quadratic-limit 5 --format csv",
+        ))
+        .unwrap();
+        assert_eq!(config.sizes, vec![10, 20]);
+        assert_eq!(
+            config.distributions,
+            vec![Distribution::Sawtooth, Distribution::Sorted]
+        );
+        assert_eq!(config.quadratic_limit, 5);
+        assert_eq!(config.format, Format::Csv);
+
+        assert!(parse_args(args("--sizes 10,x")).is_err());
+        assert!(parse_args(args("--runs")).is_err());
+        assert!(parse_args(args("--colour red")).is_err());
+        assert!(parse_args(args("--format xml")).is_err());
+    }
+
+    #[test]
+    fn test_bench_skips_quadratic_sorts() {
+        let config = parse_args(args(
+            "--sizes 4,64 --distributions few-unique --quadratic-limit 8 --format csv",
+        ))
+        .unwrap();
+        let mut out = Vec::new();
+        bench(&config, &mut out).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        let rows: Vec<&str> = out.lines().skip(1).collect();
+        assert_eq!(rows.len(), 8 + 6);
+        assert!(rows
+            .iter()
+            .any(|row| row.starts_with("few-unique,4,bubble,")));
+        assert!(!rows
+            .iter()
+            .any(|row| row.starts_with("few-unique,64,bubble,")));
+        assert!(!rows
+            .iter()
+            .any(|row| row.starts_with("few-unique,64,insertion,")));
+        assert!(rows
+            .iter()
+            .any(|row| row.starts_with("few-unique,64,counting,")));
+        assert!(rows
+            .iter()
+            .any(|row| row.starts_with("few-unique,64,introsort,")));
+    }
+}
@@ -0,0 +1,130 @@
+//! Printing the measurements.
+
+use std::io;
+use std::io::Write;
+use std::time::Duration;
+
+use super::distribution::Distribution;
+
+/// The result of sorting one input with one algorithm.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measurement {
+    pub distribution: Distribution,
+    pub size: usize,
+    pub algorithm: &'static str,
+    /// The best wall time out of the runs.
+    pub time: Duration,
+    /// `None` for sorts which don't compare elements.
+    pub comparisons: Option<usize>,
+    /// `None` for sorts which can't be observed.
+    pub swaps: Option<usize>,
+    /// `None` for sorts which can't be observed.
+    pub moves: Option<usize>,
+}
+
+/// The output format of the measurements.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// A table aligned for reading in a terminal.
+    #[default]
+    Table,
+    /// Comma separated values with a header, the time is in nanoseconds.
+    Csv,
+}
+
+/// Formats the count, or `missing` if there is none.
+fn count(count: Option<usize>, missing: &str) -> String {
+    count.map_or_else(|| missing.to_string(), |count| count.to_string())
+}
+
+impl Format {
+    /// Writes the header of the output.
+    pub fn write_header<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            Format::Table => writeln!(
+                writer,
+                "{:<13} {:>9} {:<9} {:>12} {:>13} {:>13} {:>13}",
+                "distribution", "n", "algorithm", "time (ms)", "comparisons", "swaps", "moves"
+            ),
+            Format::Csv => writeln!(
+                writer,
+                "distribution,n,algorithm,time_ns,comparisons,swaps,moves"
+            ),
+        }
+    }
+
+    /// Writes the measurement as a line of the output.
+    pub fn write_row<W: Write>(&self, measurement: &Measurement, writer: &mut W) -> io::Result<()> {
+        match self {
+            Format::Table => writeln!(
+                writer,
+                "{:<13} {:>9} {:<9} {:>12.3} {:>13} {:>13} {:>13}",
+                measurement.distribution.name(),
+                measurement.size,
+                measurement.algorithm,
+                measurement.time.as_secs_f64() * 1000.0,
+                count(measurement.comparisons, "-"),
+                count(measurement.swaps, "-"),
+                count(measurement.moves, "-"),
+            ),
+            Format::Csv => writeln!(
+                writer,
+                "{},{},{},{},{},{},{}",
+                measurement.distribution.name(),
+                measurement.size,
+                measurement.algorithm,
+                measurement.time.as_nanos(),
+                count(measurement.comparisons, ""),
+                count(measurement.swaps, ""),
+                count(measurement.moves, ""),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_csv() {
+        let measurement = Measurement {
+            distribution: Distribution::OrganPipe,
+            size: 100,
+            algorithm: "radix",
+            time: Duration::from_micros(15),
+            comparisons: None,
+            swaps: None,
+            moves: None,
+        };
+
+        let mut out = Vec::new();
+        Format::Csv.write_header(&mut out).unwrap();
+        Format::Csv.write_row(&measurement, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "distribution,n,algorithm,time_ns,comparisons,swaps,moves\n\
+             organ-pipe,100,radix,15000,,,\n"
+        );
+    }
+
+    #[test]
+    fn test_write_table() {
+        let measurement = Measurement {
+            distribution: Distribution::Sorted,
+            size: 10,
+            algorithm: "quick",
+            time: Duration::from_micros(1500),
+            comparisons: Some(45),
+            swaps: Some(9),
+            moves: Some(0),
+        };
+
+        let mut out = Vec::new();
+        Format::Table.write_row(&measurement, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "sorted               10 quick            1.500            45             9             0\n"
+        );
+    }
+}
@@ -2,6 +2,34 @@
 //!
 //! `bubble` provides sort capabilities using bubble sort
 //! using O(n^2) time complexity and O(1) space complexity.
+//!
+//! The operations of the sort can be observed, see [`sort_observed`].
+
+use std::cmp::Ordering;
+
+use observe::{NoopObserver, SortObserver};
+
+/// Sorts the slice using bubble sort,
+/// reporting the comparisons and swaps to the observer.
+fn internal_bubble_sort<T, F, O>(slice: &mut [T], compare: &mut F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let mut swapped = true;
+
+    while swapped {
+        swapped = false;
+        for i in 1..slice.len() {
+            observer.on_compare(i, i - 1);
+            if compare(&slice[i], &slice[i - 1]) == Ordering::Less {
+                swapped = true;
+                observer.on_swap(i, i - 1);
+                slice.swap(i, i - 1);
+            }
+        }
+    }
+}
 
 /// Sorts the slice using bubble sort.
 ///
@@ -18,15 +46,94 @@ pub fn sort<T>(slice: &mut [T])
 where
     T: Ord,
 {
-    let mut swapped = true;
+    sort_by(slice, T::cmp);
+}
 
-    while swapped {
-        swapped = false;
-        for i in 1..slice.len() {
-            if slice[i] < slice[i - 1] {
-                swapped = true;
-                slice.swap(i, i - 1);
-            }
-        }
-    }
+/// Sorts the slice using bubble sort with a comparator function.
+///
+/// The sort is stable, equal elements are never swapped.
+///
+/// # Examples
+///
+/// ```
+/// use bubble::sort_by;
+///
+/// let mut nmbrs = vec![1,3,2,2,4];
+/// sort_by(&mut nmbrs, |a, b| b.cmp(a));
+///
+/// assert_eq!(nmbrs, vec![4,3,2,2,1]);
+/// ```
+pub fn sort_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    internal_bubble_sort(slice, &mut compare, &mut NoopObserver);
+}
+
+/// Sorts the slice using bubble sort with a key extraction function.
+///
+/// # Examples
+///
+/// ```
+/// use bubble::sort_by_key;
+///
+/// let mut nmbrs = vec![-4,3,-2,1];
+/// sort_by_key(&mut nmbrs, |n: &i32| n.abs());
+///
+/// assert_eq!(nmbrs, vec![1,-2,3,-4]);
+/// ```
+pub fn sort_by_key<T, K, F>(slice: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    sort_by(slice, |a, b| f(a).cmp(&f(b)));
+}
+
+/// Sorts the slice using bubble sort,
+/// reporting every comparison and swap to the observer.
+///
+/// # Examples
+///
+/// ```
+/// use bubble::sort_observed;
+/// use observe::SortStats;
+///
+/// let mut nmbrs = vec![4,3,2,2,1];
+/// let mut stats = SortStats::default();
+/// sort_observed(&mut nmbrs, &mut stats);
+///
+/// assert_eq!(nmbrs, vec![1,2,2,3,4]);
+/// assert_eq!(stats.swaps, 9);
+/// ```
+pub fn sort_observed<T, O>(slice: &mut [T], observer: &mut O)
+where
+    T: Ord,
+    O: SortObserver,
+{
+    sort_by_observed(slice, T::cmp, observer);
+}
+
+/// Sorts the slice using bubble sort with a comparator function,
+/// reporting every comparison and swap to the observer.
+///
+/// # Examples
+///
+/// ```
+/// use bubble::sort_by_observed;
+/// use observe::SortStats;
+///
+/// let mut nmbrs = vec![1,2,3];
+/// let mut stats = SortStats::default();
+/// sort_by_observed(&mut nmbrs, |a, b| b.cmp(a), &mut stats);
+///
+/// assert_eq!(nmbrs, vec![3,2,1]);
+/// assert_eq!(stats.comparisons, 6);
+/// ```
+pub fn sort_by_observed<T, F, O>(slice: &mut [T], mut compare: F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    internal_bubble_sort(slice, &mut compare, observer);
 }
@@ -1,4 +1,5 @@
-use bubble::sort;
+use bubble::{sort, sort_by, sort_by_key, sort_by_observed, sort_observed};
+use observe::SortStats;
 
 #[test]
 fn insertion_sort_test() {
@@ -8,3 +9,56 @@ fn insertion_sort_test() {
 
     assert_eq!(nmbrs, vec![1, 2, 2, 3, 4, 5]);
 }
+
+#[test]
+fn bubble_sort_by_test() {
+    let mut nmbrs = vec![4, 3, 2, 2, 5, 1];
+
+    sort_by(&mut nmbrs, |a, b| b.cmp(a));
+
+    assert_eq!(nmbrs, vec![5, 4, 3, 2, 2, 1]);
+}
+
+#[test]
+fn bubble_sort_by_key_is_stable() {
+    let mut pairs = vec![(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd'), (1, 'e')];
+
+    sort_by_key(&mut pairs, |p| p.0);
+
+    assert_eq!(
+        pairs,
+        vec![(1, 'b'), (1, 'e'), (2, 'd'), (3, 'a'), (3, 'c')]
+    );
+}
+
+#[test]
+fn bubble_sort_observed_test() {
+    let mut nmbrs = vec![4, 3, 2, 2, 5, 1];
+    let mut stats = SortStats::default();
+
+    sort_observed(&mut nmbrs, &mut stats);
+
+    assert_eq!(nmbrs, vec![1, 2, 2, 3, 4, 5]);
+    assert_eq!(stats.swaps, 10);
+    assert_eq!(stats.moves, 0);
+    assert_eq!(stats.recursions, 0);
+}
+
+#[test]
+fn bubble_sort_by_observed_counts_comparisons_test() {
+    let mut nmbrs: Vec<i32> = (0..100).map(|i| (i * 37) % 100).collect();
+    let mut stats = SortStats::default();
+    let mut comparisons = 0;
+
+    sort_by_observed(
+        &mut nmbrs,
+        |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        },
+        &mut stats,
+    );
+
+    assert_eq!(nmbrs, (0..100).collect::<Vec<_>>());
+    assert_eq!(stats.comparisons, comparisons);
+}
@@ -0,0 +1,256 @@
+//! Sort keys, the parts of a line which are compared.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A position within a line, a 1-based field and a 1-based character of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Position {
+    field: usize,
+    /// The character within the field, 0 for the end of the field.
+    char: usize,
+}
+
+/// A sort key given with `-k POS1[,POS2]`.
+///
+/// A position is `F[.C][OPTS]`, the field `F` and the character `C` of it,
+/// both starting at 1. The key spans from the start of the character of
+/// `POS1`, the start of the field if `C` is omitted, to the end of the
+/// character of `POS2`, the end of the line if `POS2` is omitted and the
+/// end of the field if `C` is omitted or 0. `OPTS` are the flags `n` and
+/// `r`, which make only this key numeric or reversed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeySpec {
+    start: Position,
+    end: Option<Position>,
+    pub numeric: bool,
+    pub reverse: bool,
+}
+
+impl KeySpec {
+    /// Returns the part of the line covered by the key,
+    /// with the fields separated by the separator.
+    ///
+    /// Without a separator, fields are separated by runs of spaces.
+    pub fn extract<'a>(&self, line: &'a str, separator: Option<char>) -> &'a str {
+        let fields = fields(line, separator);
+        let offset = |field: &str| field.as_ptr() as usize - line.as_ptr() as usize;
+
+        let start = match fields.get(self.start.field - 1) {
+            Some(field) => offset(field) + char_offset(field, self.start.char - 1),
+            None => return "",
+        };
+        let end = match self.end {
+            None => line.len(),
+            Some(end) => match fields.get(end.field - 1) {
+                Some(field) if end.char == 0 => offset(field) + field.len(),
+                Some(field) => offset(field) + char_offset(field, end.char),
+                None => line.len(),
+            },
+        };
+
+        if start < end {
+            &line[start..end]
+        } else {
+            ""
+        }
+    }
+}
+
+/// Returns the byte offset of the character of the string,
+/// the length of the string if it's out of range.
+fn char_offset(s: &str, char: usize) -> usize {
+    s.char_indices()
+        .nth(char)
+        .map_or(s.len(), |(offset, _)| offset)
+}
+
+/// Splits the line into its fields with [`strings::strtok`].
+///
+/// Without a separator, fields are separated by runs of spaces.
+pub fn fields(line: &str, separator: Option<char>) -> Vec<&str> {
+    let delim = separator.unwrap_or(' ');
+    let mut rest = line;
+    let mut fields = Vec::new();
+
+    loop {
+        let last = !rest.contains(delim);
+        let field = strings::strtok(&mut rest, delim);
+        if separator.is_some() || !field.is_empty() {
+            fields.push(field);
+        }
+        if last {
+            return fields;
+        }
+    }
+}
+
+/// Parses a position of the key spec, returns it with its trailing options.
+///
+/// A start position without a character starts at the first one, an end
+/// position without a character ends at the end of the field.
+fn parse_position<'a>(
+    spec: &str,
+    pos: &'a str,
+    is_end: bool,
+) -> Result<(Position, &'a str), KeySpecError> {
+    let error = |reason| KeySpecError {
+        spec: spec.to_string(),
+        reason,
+    };
+    let number = |s: &str, reason| s.parse::<usize>().map_err(|_| error(reason));
+
+    let options_at = pos
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(pos.len());
+    let (position, options) = pos.split_at(options_at);
+
+    let mut parts = position.splitn(2, '.');
+    let field = number(parts.next().unwrap_or(""), "invalid field number")?;
+    let char = match parts.next() {
+        Some(char) => number(char, "invalid character number")?,
+        None if is_end => 0,
+        None => 1,
+    };
+
+    if field == 0 {
+        return Err(error("field numbers start at 1"));
+    }
+    if char == 0 && !is_end {
+        return Err(error("character numbers start at 1"));
+    }
+
+    Ok((Position { field, char }, options))
+}
+
+impl FromStr for KeySpec {
+    type Err = KeySpecError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let error = |reason| KeySpecError {
+            spec: spec.to_string(),
+            reason,
+        };
+
+        let mut positions = spec.splitn(2, ',');
+        let (start, start_options) = parse_position(spec, positions.next().unwrap_or(""), false)?;
+        let (end, end_options) = match positions.next() {
+            Some(end) => {
+                let (end, options) = parse_position(spec, end, true)?;
+                (Some(end), options)
+            }
+            None => (None, ""),
+        };
+
+        if end.is_some_and(|end| end.field < start.field) {
+            return Err(error("the key ends before it starts"));
+        }
+
+        let mut key = KeySpec {
+            start,
+            end,
+            numeric: false,
+            reverse: false,
+        };
+        for option in start_options.chars().chain(end_options.chars()) {
+            match option {
+                'n' => key.numeric = true,
+                'r' => key.reverse = true,
+                _ => return Err(error("unknown option, expected `n` or `r`")),
+            }
+        }
+
+        Ok(key)
+    }
+}
+
+/// The error returned when parsing an invalid [`KeySpec`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySpecError {
+    spec: String,
+    reason: &'static str,
+}
+
+impl fmt::Display for KeySpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid key spec `{}`: {}", self.spec, self.reason)
+    }
+}
+
+impl Error for KeySpecError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(spec: &str) -> KeySpec {
+        spec.parse().unwrap()
+    }
+
+    #[test]
+    fn test_fields() {
+        assert_eq!(fields("a  b c", None), vec!["a", "b", "c"]);
+        assert_eq!(fields(" a b ", None), vec!["a", "b"]);
+        assert_eq!(fields("a::b", Some(':')), vec!["a", "", "b"]);
+        assert_eq!(fields("", Some(':')), vec![""]);
+    }
+
+    #[test]
+    fn test_parse_key_spec() {
+        assert_eq!(
+            key("2"),
+            KeySpec {
+                start: Position { field: 2, char: 1 },
+                end: None,
+                numeric: false,
+                reverse: false,
+            }
+        );
+        assert_eq!(
+            key("2.3,4n"),
+            KeySpec {
+                start: Position { field: 2, char: 3 },
+                end: Some(Position { field: 4, char: 0 }),
+                numeric: true,
+                reverse: false,
+            }
+        );
+        assert!(key("1nr,1").reverse);
+
+        for (spec, reason) in &[
+            ("", "invalid field number"),
+            ("x", "invalid field number"),
+            ("0", "field numbers start at 1"),
+            ("1.0", "character numbers start at 1"),
+            ("1.x", "invalid character number"),
+            ("3,2", "the key ends before it starts"),
+            ("1b", "unknown option, expected `n` or `r`"),
+            ("1,", "invalid field number"),
+        ] {
+            assert_eq!(
+                spec.parse::<KeySpec>(),
+                Err(KeySpecError {
+                    spec: spec.to_string(),
+                    reason,
+                }),
+                "{}",
+                spec
+            );
+        }
+    }
+
+    #[test]
+    fn test_extract() {
+        let line = "root:x:0:0:Charlie Root:/root";
+        assert_eq!(key("5").extract(line, Some(':')), "Charlie Root:/root");
+        assert_eq!(key("5,5").extract(line, Some(':')), "Charlie Root");
+        assert_eq!(key("5.9,5").extract(line, Some(':')), "Root");
+        assert_eq!(key("1.2,1.3").extract(line, Some(':')), "oo");
+        assert_eq!(key("3,4").extract(line, Some(':')), "0:0");
+        assert_eq!(key("9").extract(line, Some(':')), "");
+
+        assert_eq!(key("2,2").extract("a   bb  c", None), "bb");
+        assert_eq!(key("1.9,1").extract("abc def", None), "");
+    }
+}
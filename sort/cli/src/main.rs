@@ -0,0 +1,384 @@
+//! # Sort CLI
+//!
+//! `sort-cli` sorts the lines of the files, or of the standard input,
+//! like the Unix `sort`, with the chosen sort crate of the workspace.
+//!
+//! ```text
+//! sort-cli [--algo bubble|heap|insertion|merge|quick] [-n] [-r] [-u] [-s]
+//!          [-t SEP] [-k POS1[,POS2]]... [FILE]...
+//! ```
+//!
+//! * `--algo` picks the sort, `merge` by default. `quick` runs the
+//!   pattern-defeating quick sort, the default unstable sort.
+//! * `-n` compares by the leading number, lines without one count as 0.
+//! * `-r` reverses the order.
+//! * `-u` outputs only the first of the lines with equal keys.
+//! * `-s` keeps lines with equal keys in their input order, with any
+//!   algorithm. Otherwise they are ordered by the whole line.
+//! * `-t` separates the fields by the character instead of runs of spaces.
+//! * `-k` compares by the key, see [`key::KeySpec`]. Keys with their own
+//!   `n` or `r` options ignore `-n` and `-r`.
+//!
+//! Invalid options and key specs exit with 2, unreadable files with 1.
+
+mod key;
+
+use std::cmp::Ordering;
+use std::env;
+use std::fs;
+use std::io;
+use std::io::{Read, Write};
+use std::process;
+
+use key::KeySpec;
+use sort::{Algorithm, Sorter};
+
+const USAGE: &str =
+    "usage: sort-cli [--algo bubble|heap|insertion|merge|quick] [-n] [-r] [-u] [-s] \
+[-t SEP] [-k POS1[,POS2]]... [FILE]...";
+
+/// The configuration of a sort.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Config {
+    algorithm: Algorithm,
+    numeric: bool,
+    reverse: bool,
+    unique: bool,
+    stable: bool,
+    /// The field separator, fields are separated by runs of spaces if `None`.
+    separator: Option<char>,
+    keys: Vec<KeySpec>,
+    /// The files to sort, the standard input if empty or `-`.
+    files: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            algorithm: Algorithm::Merge,
+            numeric: false,
+            reverse: false,
+            unique: false,
+            stable: false,
+            separator: None,
+            keys: Vec::new(),
+            files: Vec::new(),
+        }
+    }
+}
+
+/// Parses the command line arguments, without the program name.
+///
+/// Single letter flags can be grouped, as in `-nr`, and the values of
+/// `-k` and `-t` can follow them directly, as in `-k2` or `-t:`.
+fn parse_args<I>(args: I) -> Result<Config, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut config = Config::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--help" | "-h" => return Err(USAGE.to_string()),
+            "--algo" => {
+                let value = args.next().ok_or("missing value for --algo")?;
+                config.algorithm = value.parse().map_err(|err| format!("{}", err))?;
+            }
+            "--" => {
+                config.files.extend(args);
+                break;
+            }
+            "-" => config.files.push(arg),
+            _ if arg.starts_with("--") => {
+                return Err(format!("unknown option `{}`\n{}", arg, USAGE));
+            }
+            _ if arg.starts_with('-') => {
+                for (at, flag) in arg[1..].char_indices() {
+                    match flag {
+                        'n' => config.numeric = true,
+                        'r' => config.reverse = true,
+                        'u' => config.unique = true,
+                        's' => config.stable = true,
+                        'k' | 't' => {
+                            let value = match &arg[1 + at + 1..] {
+                                "" => args
+                                    .next()
+                                    .ok_or_else(|| format!("missing value for -{}", flag))?,
+                                rest => rest.to_string(),
+                            };
+                            if flag == 'k' {
+                                config
+                                    .keys
+                                    .push(value.parse().map_err(|err| format!("{}", err))?);
+                            } else {
+                                config.separator = Some(parse_separator(&value)?);
+                            }
+                            break;
+                        }
+                        _ => return Err(format!("unknown option `-{}`\n{}", flag, USAGE)),
+                    }
+                }
+            }
+            _ => config.files.push(arg),
+        }
+    }
+
+    Ok(config)
+}
+
+/// Parses the value of `-t`, a single character or `\t` for a tab.
+fn parse_separator(value: &str) -> Result<char, String> {
+    if value == "\\t" {
+        return Ok('\t');
+    }
+
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(separator), None) => Ok(separator),
+        _ => Err(format!(
+            "invalid separator `{}`: expected a single character",
+            value
+        )),
+    }
+}
+
+/// Returns the leading number of the string, 0 if there is none.
+///
+/// The number may be preceded by spaces, start with a `-`
+/// and have a fractional part.
+fn numeric_value(s: &str) -> f64 {
+    let s = s.trim_start();
+    let digits = |from: usize| {
+        s[from..]
+            .find(|c: char| !c.is_ascii_digit())
+            .map_or(s.len(), |len| from + len)
+    };
+
+    let mut end = digits(if s.starts_with('-') { 1 } else { 0 });
+    if s[end..].starts_with('.') {
+        end = digits(end + 1);
+    }
+
+    s[..end].parse().unwrap_or(0.0)
+}
+
+/// Compares the strings as text or by their leading numbers.
+fn compare_values(a: &str, b: &str, numeric: bool, reverse: bool) -> Ordering {
+    let order = if numeric {
+        numeric_value(a)
+            .partial_cmp(&numeric_value(b))
+            .unwrap_or(Ordering::Equal)
+    } else {
+        a.cmp(b)
+    };
+
+    if reverse {
+        order.reverse()
+    } else {
+        order
+    }
+}
+
+/// Compares the lines by their keys, the whole lines if there are none.
+fn compare_keys(config: &Config, a: &str, b: &str) -> Ordering {
+    if config.keys.is_empty() {
+        return compare_values(a, b, config.numeric, config.reverse);
+    }
+
+    for key in &config.keys {
+        let (numeric, reverse) = if key.numeric || key.reverse {
+            (key.numeric, key.reverse)
+        } else {
+            (config.numeric, config.reverse)
+        };
+        let a = key.extract(a, config.separator);
+        let b = key.extract(b, config.separator);
+
+        match compare_values(a, b, numeric, reverse) {
+            Ordering::Equal => continue,
+            order => return order,
+        }
+    }
+
+    Ordering::Equal
+}
+
+/// Sorts the lines of the inputs and writes them to the writer.
+fn run<W: Write>(config: &Config, inputs: &[String], writer: &mut W) -> io::Result<()> {
+    let mut lines: Vec<(usize, &str)> = inputs
+        .iter()
+        .flat_map(|input| input.lines())
+        .enumerate()
+        .collect();
+
+    config.algorithm.sort_by(&mut lines, |a, b| {
+        let order = compare_keys(config, a.1, b.1);
+        if config.stable || config.unique {
+            // The input order also keeps the unstable algorithms stable.
+            order.then(a.0.cmp(&b.0))
+        } else {
+            order.then_with(|| compare_values(a.1, b.1, false, config.reverse))
+        }
+    });
+
+    let len = if config.unique {
+        merge::dedup_sorted_by(&mut lines, |a, b| compare_keys(config, a.1, b.1))
+    } else {
+        lines.len()
+    };
+
+    for (_, line) in &lines[..len] {
+        writeln!(writer, "{}", line)?;
+    }
+    writer.flush()
+}
+
+/// Reads the files, the standard input for `-`.
+fn read_inputs(files: &[String]) -> Result<Vec<String>, String> {
+    let stdin = ["-".to_string()];
+    let files = if files.is_empty() { &stdin[..] } else { files };
+
+    files
+        .iter()
+        .map(|file| {
+            let mut input = String::new();
+            let read = if file == "-" {
+                io::stdin().read_to_string(&mut input).map(|_| input)
+            } else {
+                fs::read_to_string(file)
+            };
+            read.map_err(|err| format!("sort-cli: {}: {}", file, err))
+        })
+        .collect()
+}
+
+fn main() {
+    let config = match parse_args(env::args().skip(1)) {
+        Ok(config) => config,
+        Err(message) => {
+            eprintln!("sort-cli: {}", message);
+            process::exit(2);
+        }
+    };
+
+    let inputs = match read_inputs(&config.files) {
+        Ok(inputs) => inputs,
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(1);
+        }
+    };
+
+    let stdout = io::stdout();
+    if let Err(err) = run(&config, &inputs, &mut stdout.lock()) {
+        eprintln!("sort-cli: {}", err);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn sort_lines(line: &str, input: &str) -> String {
+        let config = parse_args(args(line)).unwrap();
+        let mut out = Vec::new();
+        run(&config, &[input.to_string()], &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse_args(Vec::new()), Ok(Config::default()));
+
+        let config = parse_args(args("--algo quick -nr -t: -k2,2 -k 1 a.txt - b.txt")).unwrap();
+        assert_eq!(config.algorithm, Algorithm::Quick);
+        assert!(config.numeric && config.reverse && !config.unique && !config.stable);
+        assert_eq!(config.separator, Some(':'));
+        assert_eq!(
+            config.keys,
+            vec!["2,2".parse().unwrap(), "1".parse().unwrap()]
+        );
+        assert_eq!(config.files, args("a.txt - b.txt"));
+
+        let config = parse_args(args("-us -t \\t -- -n")).unwrap();
+        assert!(config.unique && config.stable && !config.numeric);
+        assert_eq!(config.separator, Some('\t'));
+        assert_eq!(config.files, args("-n"));
+
+        assert_eq!(
+            parse_args(args("-k 0")),
+            Err("invalid key spec `0`: field numbers start at 1".to_string())
+        );
+        assert_eq!(
+            parse_args(args("-k2,1")),
+            Err("invalid key spec `2,1`: the key ends before it starts".to_string())
+        );
+        assert_eq!(
+            parse_args(args("--algo shell")),
+            Err("unknown sort algorithm `shell`".to_string())
+        );
+        assert!(parse_args(args("-t ab")).is_err());
+        assert!(parse_args(args("-k")).is_err());
+        assert!(parse_args(args("-x")).is_err());
+        assert!(parse_args(args("--reverse")).is_err());
+    }
+
+    #[test]
+    fn test_numeric_value() {
+        assert_eq!(numeric_value("  42 apples"), 42.0);
+        assert_eq!(numeric_value("-3.5x"), -3.5);
+        assert_eq!(numeric_value("1."), 1.0);
+        assert_eq!(numeric_value("apples"), 0.0);
+        assert_eq!(numeric_value("-"), 0.0);
+    }
+
+    #[test]
+    fn test_sort_every_algorithm() {
+        for algorithm in Algorithm::ALL.iter() {
+            let line = format!("--algo {}", algorithm);
+            assert_eq!(sort_lines(&line, "b\nc\na\n"), "a\nb\nc\n");
+            assert_eq!(sort_lines(&format!("{} -r", line), "b\nc\na"), "c\nb\na\n");
+            assert_eq!(
+                sort_lines(&format!("{} -n", line), "10\n9\n-1\nx\n"),
+                "-1\nx\n9\n10\n"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sort_by_keys() {
+        let passwd = "root:x:0\ndaemon:x:1\nbin:x:2\nsys:x:10\n";
+        assert_eq!(
+            sort_lines("-t: -k3n", passwd),
+            "root:x:0\ndaemon:x:1\nbin:x:2\nsys:x:10\n"
+        );
+        assert_eq!(
+            sort_lines("-t: -k3,3", passwd),
+            "root:x:0\ndaemon:x:1\nsys:x:10\nbin:x:2\n"
+        );
+        assert_eq!(
+            sort_lines("-k2,2 -k1,1r", "a 2\nb 1\nc 2\n"),
+            "b 1\nc 2\na 2\n"
+        );
+    }
+
+    #[test]
+    fn test_stable_and_unique() {
+        let input = "b 1\na 2\nc 1\na 1\n";
+        assert_eq!(sort_lines("-k2,2", input), "a 1\nb 1\nc 1\na 2\n");
+        for algorithm in Algorithm::ALL.iter() {
+            assert_eq!(
+                sort_lines(&format!("--algo {} -s -k2,2", algorithm), input),
+                "b 1\nc 1\na 1\na 2\n"
+            );
+        }
+        assert_eq!(sort_lines("-u -k2,2", input), "b 1\na 2\n");
+        assert_eq!(sort_lines("-u", "b\na\nb\na\n"), "a\nb\n");
+    }
+}
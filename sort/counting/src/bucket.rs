@@ -0,0 +1,126 @@
+//! Bucket sort.
+//!
+//! The range of the keys is split into buckets of equal width, the
+//! elements are distributed into them with counting sort and every bucket
+//! is sorted with insertion sort. With uniformly distributed keys every
+//! bucket holds a constant number of elements on average.
+
+use std::cmp::Ordering;
+use std::ops::RangeInclusive;
+
+use super::{counting_order, SortError};
+
+/// Sorts the slice of floats within the range using bucket sort.
+///
+/// See [`bucket_sort_by_key`].
+///
+/// # Examples
+///
+/// ```
+/// let mut floats = vec![0.42, 0.07, 0.99, 0.5, 0.0];
+/// counting::bucket_sort(&mut floats, 0.0..=1.0, 4).unwrap();
+///
+/// assert_eq!(floats, vec![0.0, 0.07, 0.42, 0.5, 0.99]);
+/// ```
+pub fn bucket_sort<T>(
+    slice: &mut [T],
+    range: RangeInclusive<f64>,
+    buckets: usize,
+) -> Result<(), SortError>
+where
+    T: Copy + Into<f64>,
+{
+    bucket_sort_by_key(slice, range, buckets, |&key| key.into())
+}
+
+/// Sorts the slice using bucket sort with a key extraction function.
+///
+/// The range is split into `buckets` buckets of equal width, at least one.
+/// Every key must be within the range, otherwise a
+/// [`SortError::FloatOutOfRange`] is returned for the first element with
+/// a NaN or out of range key and the slice is left untouched.
+///
+/// Runs in O(n) expected time for keys uniformly distributed over the
+/// range, with about as many buckets as elements. If most of the keys end
+/// up in the same bucket, the insertion sort makes it O(n^2).
+///
+/// The sort is stable, elements with equal keys keep their relative order.
+///
+/// # Examples
+///
+/// ```
+/// use counting::SortError;
+///
+/// let mut readings = vec![("b", 20.5), ("a", -3.0), ("c", 20.5)];
+/// counting::bucket_sort_by_key(&mut readings, -50.0..=50.0, 8, |r| r.1).unwrap();
+///
+/// assert_eq!(readings, vec![("a", -3.0), ("b", 20.5), ("c", 20.5)]);
+///
+/// readings.push(("d", f64::NAN));
+/// let err = counting::bucket_sort_by_key(&mut readings, -50.0..=50.0, 8, |r| r.1);
+/// assert!(matches!(err, Err(SortError::FloatOutOfRange { index: 3, .. })));
+/// ```
+pub fn bucket_sort_by_key<T, F>(
+    slice: &mut [T],
+    range: RangeInclusive<f64>,
+    buckets: usize,
+    f: F,
+) -> Result<(), SortError>
+where
+    F: Fn(&T) -> f64,
+{
+    let buckets = buckets.max(1);
+    let (lo, hi) = (*range.start(), *range.end());
+    let width = hi - lo;
+
+    let mut keys = Vec::with_capacity(slice.len());
+    for (index, elem) in slice.iter().enumerate() {
+        let key = f(elem);
+        if !range.contains(&key) {
+            return Err(SortError::FloatOutOfRange { index, key });
+        }
+
+        // The float to int cast saturates, NaN from a zero width range becomes 0.
+        let bucket = ((key - lo) / width * buckets as f64) as usize;
+        keys.push(bucket.min(buckets - 1));
+    }
+
+    let mut order = counting_order(&keys, buckets);
+    sort::apply_permutation(slice, &mut order);
+
+    let mut counts = vec![0; buckets];
+    for &bucket in &keys {
+        counts[bucket] += 1;
+    }
+
+    let mut start = 0;
+    for count in counts {
+        let bucket = &mut slice[start..start + count];
+        insertion::sort_by(bucket, |a, b| {
+            f(a).partial_cmp(&f(b)).unwrap_or(Ordering::Equal)
+        });
+        start += count;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_sort_with_zero_width_range() {
+        let mut floats = vec![1.0, 1.0];
+        assert_eq!(bucket_sort(&mut floats, 1.0..=1.0, 4), Ok(()));
+        assert_eq!(
+            bucket_sort(&mut floats, 1.0..=1.0, 0),
+            Ok(()),
+            "the bucket count is at least one"
+        );
+        assert_eq!(
+            bucket_sort(&mut [0.5, 2.0], 1.0..=1.0, 4),
+            Err(SortError::FloatOutOfRange { index: 0, key: 0.5 })
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! # Counting
+//!
+//! `counting` provides sort capabilities for elements with bounded keys.
+//!
+//! Elements with integer keys within a declared range are sorted using
+//! counting sort, see [`sort_by_key`], in O(n + k) time complexity and
+//! O(n + k) space complexity, where `k` is the size of the key range.
+//! Uniformly distributed floats are sorted using bucket sort, see
+//! [`bucket_sort`], in O(n) expected time complexity.
+//!
+//! Keys outside of the declared range are reported as a [`SortError`],
+//! the slice is left untouched in that case.
+
+pub use bucket::{bucket_sort, bucket_sort_by_key};
+
+mod bucket;
+
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+/// The error returned when a key is outside of the declared range.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortError {
+    /// The key of the element at `index` is outside of the key range.
+    KeyOutOfRange { index: usize, key: usize },
+    /// The key of the element at `index` is NaN or outside of the range.
+    FloatOutOfRange { index: usize, key: f64 },
+}
+
+impl fmt::Display for SortError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortError::KeyOutOfRange { index, key } => {
+                write!(f, "key {} of element {} is out of range", key, index)
+            }
+            SortError::FloatOutOfRange { index, key } => {
+                write!(f, "key {} of element {} is out of range", key, index)
+            }
+        }
+    }
+}
+
+impl Error for SortError {}
+
+/// Returns the order in which the elements have to be placed to be
+/// sorted by their keys, `order[i]` is the index of the element which
+/// belongs at index `i`. `keys` must be offsets into `buckets` buckets.
+///
+/// Elements with equal keys keep their relative order.
+fn counting_order(keys: &[usize], buckets: usize) -> Vec<usize> {
+    let mut offsets = vec![0; buckets];
+    for &key in keys {
+        offsets[key] += 1;
+    }
+
+    let mut sum = 0;
+    for offset in offsets.iter_mut() {
+        let next = sum + *offset;
+        *offset = sum;
+        sum = next;
+    }
+
+    let mut order = vec![0; keys.len()];
+    for (i, &key) in keys.iter().enumerate() {
+        order[offsets[key]] = i;
+        offsets[key] += 1;
+    }
+
+    order
+}
+
+/// Sorts the slice of keys within the range using counting sort.
+///
+/// # Examples
+///
+/// ```
+/// let mut codes: Vec<u16> = vec![404, 200, 500, 200, 301];
+/// counting::sort(&mut codes, 100..600).unwrap();
+///
+/// assert_eq!(codes, vec![200, 200, 301, 404, 500]);
+/// ```
+pub fn sort<T>(slice: &mut [T], range: Range<usize>) -> Result<(), SortError>
+where
+    T: Copy + Into<usize>,
+{
+    sort_by_key(slice, range, |&key| key.into())
+}
+
+/// Sorts the slice using counting sort with a key extraction function.
+///
+/// The key is extracted once per element and must be within the range,
+/// otherwise a [`SortError::KeyOutOfRange`] is returned for the first
+/// element with an out of range key and the slice is left untouched.
+/// The elements are only moved once, after the sorted order has been computed.
+///
+/// The sort is stable, elements with equal keys keep their relative order.
+///
+/// # Examples
+///
+/// ```
+/// use counting::SortError;
+///
+/// let mut people = vec![("bob", 42), ("alice", 7), ("eve", 42), ("dan", 19)];
+/// counting::sort_by_key(&mut people, 0..128, |p| p.1).unwrap();
+///
+/// assert_eq!(people, vec![("alice", 7), ("dan", 19), ("bob", 42), ("eve", 42)]);
+///
+/// let err = counting::sort_by_key(&mut people, 0..42, |p| p.1).unwrap_err();
+/// assert_eq!(err, SortError::KeyOutOfRange { index: 2, key: 42 });
+/// ```
+pub fn sort_by_key<T, F>(slice: &mut [T], range: Range<usize>, f: F) -> Result<(), SortError>
+where
+    F: Fn(&T) -> usize,
+{
+    let mut keys = Vec::with_capacity(slice.len());
+    for (index, elem) in slice.iter().enumerate() {
+        let key = f(elem);
+        if !range.contains(&key) {
+            return Err(SortError::KeyOutOfRange { index, key });
+        }
+        keys.push(key - range.start);
+    }
+
+    let mut order = counting_order(&keys, range.len());
+    sort::apply_permutation(slice, &mut order);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counting_order() {
+        assert_eq!(counting_order(&[2, 0, 2, 1, 0], 3), vec![1, 4, 3, 0, 2]);
+        assert_eq!(counting_order(&[], 0), vec![]);
+    }
+}
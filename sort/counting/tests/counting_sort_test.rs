@@ -0,0 +1,92 @@
+use counting::{bucket_sort, bucket_sort_by_key, sort, sort_by_key, SortError};
+
+#[test]
+fn counting_sort_works() {
+    let mut things: Vec<u8> = (0..1000).map(|i| ((i * 7919) % 251) as u8).collect();
+    let mut want = things.clone();
+    want.sort();
+
+    sort(&mut things, 0..256).unwrap();
+    assert_eq!(things, want);
+
+    let mut things: Vec<usize> = vec![];
+    sort(&mut things, 0..0).unwrap();
+    assert_eq!(things, vec![]);
+}
+
+#[test]
+fn counting_sort_by_key_is_stable() {
+    let mut pairs: Vec<(usize, usize)> = (0..500).map(|i| (10 + i % 7, i)).collect();
+
+    sort_by_key(&mut pairs, 10..17, |p| p.0).unwrap();
+
+    let mut want: Vec<(usize, usize)> = (0..500).map(|i| (10 + i % 7, i)).collect();
+    want.sort_by_key(|p| p.0);
+    assert_eq!(pairs, want);
+}
+
+#[test]
+fn counting_sort_rejects_out_of_range_keys() {
+    let mut things: Vec<usize> = vec![3, 1, 9, 2];
+
+    assert_eq!(
+        sort(&mut things, 0..5),
+        Err(SortError::KeyOutOfRange { index: 2, key: 9 })
+    );
+    assert_eq!(things, vec![3, 1, 9, 2]);
+
+    assert_eq!(
+        sort_by_key(&mut things, 2..10, |&n| n),
+        Err(SortError::KeyOutOfRange { index: 1, key: 1 })
+    );
+    assert_eq!(
+        SortError::KeyOutOfRange { index: 1, key: 1 }.to_string(),
+        "key 1 of element 1 is out of range"
+    );
+}
+
+#[test]
+fn bucket_sort_works() {
+    let mut floats: Vec<f64> = (0..1000)
+        .map(|i| ((i * 7919) % 1000) as f64 / 1000.0)
+        .collect();
+    let mut want = floats.clone();
+    want.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    bucket_sort(&mut floats, 0.0..=1.0, 100).unwrap();
+    assert_eq!(floats, want);
+
+    let mut floats: Vec<f32> = vec![0.5, -0.25, 1.0, -1.0];
+    bucket_sort(&mut floats, -1.0..=1.0, 1).unwrap();
+    assert_eq!(floats, vec![-1.0, -0.25, 0.5, 1.0]);
+}
+
+#[test]
+fn bucket_sort_by_key_is_stable() {
+    let mut pairs: Vec<(f64, usize)> = (0..300).map(|i| ((i % 5) as f64 / 4.0, i)).collect();
+
+    bucket_sort_by_key(&mut pairs, 0.0..=1.0, 3, |p| p.0).unwrap();
+
+    let mut want: Vec<(f64, usize)> = (0..300).map(|i| ((i % 5) as f64 / 4.0, i)).collect();
+    want.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    assert_eq!(pairs, want);
+}
+
+#[test]
+fn bucket_sort_rejects_out_of_range_keys() {
+    let mut floats = vec![0.5, 1.5, 0.2];
+    assert_eq!(
+        bucket_sort(&mut floats, 0.0..=1.0, 10),
+        Err(SortError::FloatOutOfRange { index: 1, key: 1.5 })
+    );
+    assert_eq!(floats, vec![0.5, 1.5, 0.2]);
+
+    let mut floats = vec![0.5, f64::NAN];
+    assert!(matches!(
+        bucket_sort(&mut floats, 0.0..=1.0, 10),
+        Err(SortError::FloatOutOfRange { index: 1, .. })
+    ));
+
+    let mut floats = vec![f64::INFINITY];
+    assert!(bucket_sort(&mut floats, 0.0..=1.0, 10).is_err());
+}
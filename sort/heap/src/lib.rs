@@ -0,0 +1,285 @@
+//! # Heap
+//!
+//! `heap` provides sort capabilities using heap sort
+//! using O(n log(n)) time complexity and O(1) space complexity.
+//!
+//! The binary max-heap primitives the sort is built on, [`heapify`],
+//! [`sift_down`] and [`sift_up`], are public so that they can be reused,
+//! e.g. for a priority queue. The heap is stored in the slice itself,
+//! the children of the element at index `i` are at `2i + 1` and `2i + 2`.
+//!
+//! The operations of the sort can be observed, see [`sort_observed`].
+
+use std::cmp::Ordering;
+
+use observe::{NoopObserver, SortObserver};
+
+/// Rearranges the slice into a max-heap.
+///
+/// # Examples
+///
+/// ```
+/// use heap::heapify;
+///
+/// let mut nmbrs = vec![1,2,3,4,5];
+/// heapify(&mut nmbrs);
+///
+/// assert_eq!(nmbrs[0], 5);
+/// ```
+pub fn heapify<T>(slice: &mut [T])
+where
+    T: Ord,
+{
+    heapify_by(slice, T::cmp);
+}
+
+/// Rearranges the slice into a max-heap with respect to the comparator function.
+///
+/// # Examples
+///
+/// ```
+/// use heap::heapify_by;
+///
+/// let mut nmbrs = vec![5,4,3,2,1];
+/// heapify_by(&mut nmbrs, |a, b| b.cmp(a));
+///
+/// assert_eq!(nmbrs[0], 1);
+/// ```
+pub fn heapify_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for i in (0..slice.len() / 2).rev() {
+        sift_down_by(slice, i, &mut compare);
+    }
+}
+
+/// Moves the element at index `i` down the max-heap until
+/// none of its children is greater than it.
+///
+/// The children of `i` must already be roots of max-heaps.
+///
+/// # Examples
+///
+/// ```
+/// use heap::sift_down;
+///
+/// let mut nmbrs = vec![1,5,4,3,2];
+/// sift_down(&mut nmbrs, 0);
+///
+/// assert_eq!(nmbrs, vec![5,3,4,1,2]);
+/// ```
+pub fn sift_down<T>(slice: &mut [T], i: usize)
+where
+    T: Ord,
+{
+    sift_down_by(slice, i, T::cmp);
+}
+
+/// Moves the element at index `i` down the max-heap with respect
+/// to the comparator function.
+///
+/// See [`sift_down`].
+pub fn sift_down_by<T, F>(slice: &mut [T], i: usize, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    observed_sift_down(slice, i, &mut compare, &mut NoopObserver);
+}
+
+/// Moves the element at index `i` down the max-heap,
+/// reporting the comparisons and swaps to the observer.
+fn observed_sift_down<T, F, O>(slice: &mut [T], mut i: usize, compare: &mut F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    loop {
+        let mut child = 2 * i + 1;
+        if child >= slice.len() {
+            return;
+        }
+
+        if child + 1 < slice.len() {
+            observer.on_compare(child, child + 1);
+            if compare(&slice[child], &slice[child + 1]) == Ordering::Less {
+                child += 1;
+            }
+        }
+
+        observer.on_compare(i, child);
+        if compare(&slice[i], &slice[child]) != Ordering::Less {
+            return;
+        }
+
+        observer.on_swap(i, child);
+        slice.swap(i, child);
+        i = child;
+    }
+}
+
+/// Moves the element at index `i` up the max-heap until
+/// its parent is not less than it.
+///
+/// The slice without the element at `i` must already be a max-heap.
+///
+/// # Examples
+///
+/// ```
+/// use heap::sift_up;
+///
+/// let mut nmbrs = vec![5,3,4,1,2,6];
+/// sift_up(&mut nmbrs, 5);
+///
+/// assert_eq!(nmbrs, vec![6,3,5,1,2,4]);
+/// ```
+pub fn sift_up<T>(slice: &mut [T], i: usize)
+where
+    T: Ord,
+{
+    sift_up_by(slice, i, T::cmp);
+}
+
+/// Moves the element at index `i` up the max-heap with respect
+/// to the comparator function.
+///
+/// See [`sift_up`].
+pub fn sift_up_by<T, F>(slice: &mut [T], mut i: usize, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    while i > 0 {
+        let parent = (i - 1) / 2;
+        if compare(&slice[parent], &slice[i]) != Ordering::Less {
+            return;
+        }
+
+        slice.swap(parent, i);
+        i = parent;
+    }
+}
+
+/// Sorts the slice using heap sort,
+/// reporting the comparisons and swaps to the observer.
+fn internal_heapsort<T, F, O>(slice: &mut [T], compare: &mut F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    for i in (0..slice.len() / 2).rev() {
+        observed_sift_down(slice, i, compare, observer);
+    }
+
+    for end in (1..slice.len()).rev() {
+        observer.on_swap(0, end);
+        slice.swap(0, end);
+        observed_sift_down(&mut slice[..end], 0, compare, observer);
+    }
+}
+
+/// Sorts the slice using heap sort.
+///
+/// # Examples
+///
+/// ```
+/// use heap::sort;
+///
+/// let mut nmbrs = vec![4,3,2,5,1];
+/// sort(&mut nmbrs);
+///
+/// assert_eq!(nmbrs, vec![1,2,3,4,5]);
+/// ```
+pub fn sort<T>(slice: &mut [T])
+where
+    T: Ord,
+{
+    sort_by(slice, T::cmp);
+}
+
+/// Sorts the slice using heap sort with a comparator function.
+///
+/// The sort is not stable, equal elements may be reordered.
+///
+/// # Examples
+///
+/// ```
+/// use heap::sort_by;
+///
+/// let mut nmbrs = vec![1,3,2,5,4];
+/// sort_by(&mut nmbrs, |a, b| b.cmp(a));
+///
+/// assert_eq!(nmbrs, vec![5,4,3,2,1]);
+/// ```
+pub fn sort_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    internal_heapsort(slice, &mut compare, &mut NoopObserver);
+}
+
+/// Sorts the slice using heap sort with a key extraction function.
+///
+/// # Examples
+///
+/// ```
+/// use heap::sort_by_key;
+///
+/// let mut nmbrs = vec![-4,3,-2,5,1];
+/// sort_by_key(&mut nmbrs, |n: &i32| n.abs());
+///
+/// assert_eq!(nmbrs, vec![1,-2,3,-4,5]);
+/// ```
+pub fn sort_by_key<T, K, F>(slice: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    sort_by(slice, |a, b| f(a).cmp(&f(b)));
+}
+
+/// Sorts the slice using heap sort,
+/// reporting every comparison and swap to the observer.
+///
+/// # Examples
+///
+/// ```
+/// use heap::sort_observed;
+/// use observe::SortStats;
+///
+/// let mut nmbrs = vec![4,3,2,5,1];
+/// let mut stats = SortStats::default();
+/// sort_observed(&mut nmbrs, &mut stats);
+///
+/// assert_eq!(nmbrs, vec![1,2,3,4,5]);
+/// assert!(stats.swaps >= 4);
+/// ```
+pub fn sort_observed<T, O>(slice: &mut [T], observer: &mut O)
+where
+    T: Ord,
+    O: SortObserver,
+{
+    sort_by_observed(slice, T::cmp, observer);
+}
+
+/// Sorts the slice using heap sort with a comparator function,
+/// reporting every comparison and swap to the observer.
+///
+/// # Examples
+///
+/// ```
+/// use heap::sort_by_observed;
+/// use observe::SortStats;
+///
+/// let mut nmbrs = vec![1];
+/// let mut stats = SortStats::default();
+/// sort_by_observed(&mut nmbrs, |a, b| b.cmp(a), &mut stats);
+///
+/// assert_eq!(stats, SortStats::default());
+/// ```
+pub fn sort_by_observed<T, F, O>(slice: &mut [T], mut compare: F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    internal_heapsort(slice, &mut compare, observer);
+}
@@ -0,0 +1,43 @@
+use heap::{heapify, sift_up, sort, sort_by};
+
+fn is_max_heap(slice: &[i32]) -> bool {
+    (1..slice.len()).all(|i| slice[(i - 1) / 2] >= slice[i])
+}
+
+#[test]
+fn heap_sort_works() {
+    let mut things = vec![1, 4, 4, 4, 3, 3];
+    sort(&mut things);
+    assert_eq!(things, vec![1, 3, 3, 4, 4, 4]);
+
+    let mut things = vec![
+        1, 5, 3, 2, 66, 7, 5234, 56, 4534, 65453, 45, 3543, 4325, 3432,
+    ];
+    sort(&mut things);
+    assert_eq!(
+        things,
+        vec![1, 2, 3, 5, 7, 45, 56, 66, 3432, 3543, 4325, 4534, 5234, 65453]
+    );
+
+    let mut things: Vec<i32> = vec![];
+    sort_by(&mut things, |a, b| b.cmp(a));
+    assert_eq!(things, vec![]);
+}
+
+#[test]
+fn heap_primitives_keep_heap_property() {
+    let mut heap = vec![
+        1, 5, 3, 2, 66, 7, 5234, 56, 4534, 65453, 45, 3543, 4325, 3432,
+    ];
+    heapify(&mut heap);
+    assert!(is_max_heap(&heap));
+    assert_eq!(heap[0], 65453);
+
+    for &n in &[0, 100_000, 50] {
+        heap.push(n);
+        let last = heap.len() - 1;
+        sift_up(&mut heap, last);
+        assert!(is_max_heap(&heap));
+    }
+    assert_eq!(heap[0], 100_000);
+}
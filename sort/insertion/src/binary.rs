@@ -0,0 +1,145 @@
+//! Binary insertion sort.
+//!
+//! The position of every element is found by a binary search over the
+//! already sorted prefix, so only O(n log(n)) comparisons are made.
+//! The elements are still shifted one by one, in O(n^2) time, but with
+//! a single `ptr::copy` per insertion.
+
+use std::cmp::Ordering;
+use std::ptr;
+
+/// Returns the index of the first element of the sorted `prefix`
+/// which is greater than `key`.
+fn upper_bound<T, F>(prefix: &[T], key: &T, compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut lo = 0;
+    let mut hi = prefix.len();
+
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if compare(key, &prefix[mid]) == Ordering::Less {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+
+    lo
+}
+
+/// Sorts the slice using binary insertion sort, `sorted` is the length
+/// of the prefix which is already sorted.
+///
+/// The comparator is only called before an element is moved, so a
+/// panicking comparator leaves the slice as a permutation of its elements.
+fn internal_binary_sort<T, F>(slice: &mut [T], sorted: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for i in sorted.max(1)..slice.len() {
+        let (prefix, rest) = slice.split_at(i);
+        let pos = upper_bound(prefix, &rest[0], compare);
+        if pos == i {
+            continue;
+        }
+
+        // Safety: the element at `i` is read out, the elements in
+        // [pos, i) are shifted one to the right over it and it is
+        // written into the gap at `pos`. Nothing can panic in between.
+        unsafe {
+            let p = slice.as_mut_ptr();
+            let tmp = ptr::read(p.add(i));
+            ptr::copy(p.add(pos), p.add(pos + 1), i - pos);
+            ptr::write(p.add(pos), tmp);
+        }
+    }
+}
+
+/// Sorts the slice using binary insertion sort.
+///
+/// # Examples
+///
+/// ```
+/// use insertion::binary_sort;
+///
+/// let mut nmbrs = vec![4,3,2,2,1];
+/// binary_sort(&mut nmbrs);
+///
+/// assert_eq!(nmbrs, vec![1,2,2,3,4]);
+/// ```
+pub fn binary_sort<T>(slice: &mut [T])
+where
+    T: Ord,
+{
+    binary_sort_by(slice, T::cmp);
+}
+
+/// Sorts the slice using binary insertion sort with a comparator function.
+///
+/// Makes O(n log(n)) comparisons, which pays off over [`sort_by`](crate::sort_by)
+/// when comparisons are expensive. The sort is stable, equal elements
+/// keep their relative order.
+///
+/// # Examples
+///
+/// ```
+/// use insertion::binary_sort_by;
+///
+/// let mut words = vec!["pear", "fig", "apple"];
+/// binary_sort_by(&mut words, |a, b| a.len().cmp(&b.len()));
+///
+/// assert_eq!(words, vec!["fig", "pear", "apple"]);
+/// ```
+pub fn binary_sort_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    internal_binary_sort(slice, 0, &mut compare);
+}
+
+/// Sorts the slice using binary insertion sort with a key extraction function.
+///
+/// # Examples
+///
+/// ```
+/// use insertion::binary_sort_by_key;
+///
+/// let mut nmbrs = vec![-4,3,-2,1];
+/// binary_sort_by_key(&mut nmbrs, |n: &i32| n.abs());
+///
+/// assert_eq!(nmbrs, vec![1,-2,3,-4]);
+/// ```
+pub fn binary_sort_by_key<T, K, F>(slice: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    binary_sort_by(slice, |a, b| f(a).cmp(&f(b)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upper_bound() {
+        let prefix = vec![1, 2, 2, 4];
+        assert_eq!(upper_bound(&prefix, &0, &mut i32::cmp), 0);
+        assert_eq!(upper_bound(&prefix, &2, &mut i32::cmp), 3);
+        assert_eq!(upper_bound(&prefix, &5, &mut i32::cmp), 4);
+    }
+
+    #[test]
+    fn test_internal_binary_sort_with_sorted_prefix() {
+        let mut nmbrs = vec![1, 3, 5, 4, 2];
+        let mut comparisons = 0;
+        internal_binary_sort(&mut nmbrs, 3, &mut |a: &i32, b: &i32| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        assert_eq!(nmbrs, vec![1, 2, 3, 4, 5]);
+        assert_eq!(comparisons, 5);
+    }
+}
@@ -2,6 +2,61 @@
 //!
 //! `insertion` provides sort capabilities using insertion sort
 //! using O(n^2) time complexity and O(1) space complexity.
+//!
+//! The operations of the sort can be observed, see [`sort_observed`].
+//!
+//! When comparisons are expensive [`binary_sort`] finds the position of
+//! every element with a binary search instead. [`shell_sort`] sorts in
+//! O(n^(4/3)) time or better, depending on the gap sequence.
+//! Both are suited as the base case of other sorts for small slices.
+//!
+//! Arrays of up to 16 elements can be sorted with optimal sorting
+//! networks, which make the same comparisons whatever the data,
+//! see [`sort_network`] and [`sort_small_by`]. The latter can be observed,
+//! see [`sort_small_by_observed`].
+//!
+//! If the comparator panics the slice is left as a permutation of its
+//! elements, [`binary_sort`] only moves an element once its position is found.
+
+pub use binary::{binary_sort, binary_sort_by, binary_sort_by_key};
+pub use network::{
+    sort_network, sort_network_by, sort_network_by_key, sort_small_by, sort_small_by_observed,
+    MAX_NETWORK_LEN,
+};
+pub use shell::{shell_sort, shell_sort_by, shell_sort_by_key, Gaps};
+
+mod binary;
+mod network;
+mod shell;
+
+use std::cmp::Ordering;
+
+use observe::{NoopObserver, SortObserver};
+
+/// Sorts the slice using insertion sort,
+/// reporting the comparisons and swaps to the observer.
+fn internal_insertion_sort<T, F, O>(slice: &mut [T], compare: &mut F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let mut curr = 1;
+
+    while curr < slice.len() {
+        let mut tmp = curr;
+        while tmp > 0 {
+            observer.on_compare(tmp, tmp - 1);
+            if compare(&slice[tmp], &slice[tmp - 1]) != Ordering::Less {
+                break;
+            }
+            observer.on_swap(tmp, tmp - 1);
+            slice.swap(tmp, tmp - 1);
+            tmp -= 1;
+        }
+
+        curr += 1;
+    }
+}
 
 /// Sorts the slice using insertion sort.
 ///
@@ -18,15 +73,95 @@ pub fn sort<T>(slice: &mut [T])
 where
     T: Ord,
 {
-    let mut curr = 1;
+    sort_by(slice, T::cmp);
+}
 
-    while curr < slice.len() {
-        let mut tmp = curr;
-        while tmp > 0 && slice[tmp] < slice[tmp - 1] {
-            slice.swap(tmp, tmp - 1);
-            tmp -= 1;
-        }
+/// Sorts the slice using insertion sort with a comparator function.
+///
+/// The sort is stable, equal elements keep their relative order.
+///
+/// # Examples
+///
+/// ```
+/// use insertion::sort_by;
+///
+/// let mut nmbrs = vec![1,3,2,2,4];
+/// sort_by(&mut nmbrs, |a, b| b.cmp(a));
+///
+/// assert_eq!(nmbrs, vec![4,3,2,2,1]);
+/// ```
+pub fn sort_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    internal_insertion_sort(slice, &mut compare, &mut NoopObserver);
+}
 
-        curr += 1;
-    }
+/// Sorts the slice using insertion sort with a key extraction function.
+///
+/// # Examples
+///
+/// ```
+/// use insertion::sort_by_key;
+///
+/// let mut nmbrs = vec![-4,3,-2,1];
+/// sort_by_key(&mut nmbrs, |n: &i32| n.abs());
+///
+/// assert_eq!(nmbrs, vec![1,-2,3,-4]);
+/// ```
+pub fn sort_by_key<T, K, F>(slice: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    sort_by(slice, |a, b| f(a).cmp(&f(b)));
+}
+
+/// Sorts the slice using insertion sort,
+/// reporting every comparison and swap to the observer.
+///
+/// # Examples
+///
+/// ```
+/// use insertion::sort_observed;
+/// use observe::SortStats;
+///
+/// let mut nmbrs = vec![4,3,2,2,1];
+/// let mut stats = SortStats::default();
+/// sort_observed(&mut nmbrs, &mut stats);
+///
+/// assert_eq!(nmbrs, vec![1,2,2,3,4]);
+/// assert_eq!(stats.swaps, 9);
+/// ```
+pub fn sort_observed<T, O>(slice: &mut [T], observer: &mut O)
+where
+    T: Ord,
+    O: SortObserver,
+{
+    sort_by_observed(slice, T::cmp, observer);
+}
+
+/// Sorts the slice using insertion sort with a comparator function,
+/// reporting every comparison and swap to the observer.
+///
+/// # Examples
+///
+/// ```
+/// use insertion::sort_by_observed;
+/// use observe::SortStats;
+///
+/// let mut nmbrs = vec![3,2,1];
+/// let mut stats = SortStats::default();
+/// sort_by_observed(&mut nmbrs, |a, b| b.cmp(a), &mut stats);
+///
+/// assert_eq!(nmbrs, vec![3,2,1]);
+/// assert_eq!(stats.comparisons, 2);
+/// assert_eq!(stats.swaps, 0);
+/// ```
+pub fn sort_by_observed<T, F, O>(slice: &mut [T], mut compare: F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    internal_insertion_sort(slice, &mut compare, observer);
 }
@@ -0,0 +1,431 @@
+//! Sorting networks.
+//!
+//! A sorting network is a fixed sequence of compare-exchanges of two
+//! positions. Which positions are compared doesn't depend on the data,
+//! so small arrays are sorted without the unpredictable branches of
+//! insertion sort.
+//!
+//! The networks have the fewest comparators known for their size,
+//! see Bert Dobbelaere's "List of sorting networks". The network for
+//! 16 elements is Green's, the one for 15 elements is Green's without
+//! its last channel.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::ptr;
+
+use observe::{NoopObserver, SortObserver};
+
+/// The longest arrays sorting networks are provided for.
+pub const MAX_NETWORK_LEN: usize = 16;
+
+// The comparators of every network, a layer of comparators
+// of distinct positions per line.
+
+const NETWORK_2: &[(u8, u8)] = &[(0, 1)];
+
+#[rustfmt::skip]
+const NETWORK_3: &[(u8, u8)] = &[
+    (0, 2),
+    (0, 1),
+    (1, 2),
+];
+
+#[rustfmt::skip]
+const NETWORK_4: &[(u8, u8)] = &[
+    (0, 2), (1, 3),
+    (0, 1), (2, 3),
+    (1, 2),
+];
+
+#[rustfmt::skip]
+const NETWORK_5: &[(u8, u8)] = &[
+    (0, 3), (1, 4),
+    (0, 2), (1, 3),
+    (0, 1), (2, 4),
+    (1, 2), (3, 4),
+    (2, 3),
+];
+
+#[rustfmt::skip]
+const NETWORK_6: &[(u8, u8)] = &[
+    (0, 5), (1, 3), (2, 4),
+    (1, 2), (3, 4),
+    (0, 3), (2, 5),
+    (0, 1), (2, 3), (4, 5),
+    (1, 2), (3, 4),
+];
+
+#[rustfmt::skip]
+const NETWORK_7: &[(u8, u8)] = &[
+    (0, 6), (2, 3), (4, 5),
+    (0, 2), (1, 4), (3, 6),
+    (0, 1), (2, 5), (3, 4),
+    (1, 2), (4, 6),
+    (2, 3), (4, 5),
+    (1, 2), (3, 4), (5, 6),
+];
+
+#[rustfmt::skip]
+const NETWORK_8: &[(u8, u8)] = &[
+    (0, 2), (1, 3), (4, 6), (5, 7),
+    (0, 4), (1, 5), (2, 6), (3, 7),
+    (0, 1), (2, 3), (4, 5), (6, 7),
+    (2, 4), (3, 5),
+    (1, 4), (3, 6),
+    (1, 2), (3, 4), (5, 6),
+];
+
+#[rustfmt::skip]
+const NETWORK_9: &[(u8, u8)] = &[
+    (0, 3), (1, 7), (2, 5), (4, 8),
+    (0, 7), (2, 4), (3, 8), (5, 6),
+    (0, 2), (1, 3), (4, 5), (7, 8),
+    (1, 4), (3, 6), (5, 7),
+    (0, 1), (2, 4), (3, 5), (6, 8),
+    (2, 3), (4, 5), (6, 7),
+    (1, 2), (3, 4), (5, 6),
+];
+
+#[rustfmt::skip]
+const NETWORK_10: &[(u8, u8)] = &[
+    (0, 8), (1, 9), (2, 7), (3, 5), (4, 6),
+    (0, 2), (1, 4), (5, 8), (7, 9),
+    (0, 3), (2, 4), (5, 7), (6, 9),
+    (0, 1), (3, 6), (8, 9),
+    (1, 5), (2, 3), (4, 8), (6, 7),
+    (1, 2), (3, 5), (4, 6), (7, 8),
+    (2, 3), (4, 5), (6, 7),
+    (3, 4), (5, 6),
+];
+
+#[rustfmt::skip]
+const NETWORK_11: &[(u8, u8)] = &[
+    (0, 9), (1, 6), (2, 4), (3, 7), (5, 8),
+    (0, 1), (3, 5), (4, 10), (6, 9), (7, 8),
+    (1, 3), (2, 5), (4, 7), (8, 10),
+    (0, 4), (1, 2), (3, 7), (5, 9), (6, 8),
+    (0, 1), (2, 6), (4, 5), (7, 8), (9, 10),
+    (2, 4), (3, 6), (5, 7), (8, 9),
+    (1, 2), (3, 4), (5, 6), (7, 8),
+    (2, 3), (4, 5), (6, 7),
+];
+
+#[rustfmt::skip]
+const NETWORK_12: &[(u8, u8)] = &[
+    (0, 8), (1, 7), (2, 6), (3, 11), (4, 10), (5, 9),
+    (0, 1), (2, 5), (3, 4), (6, 9), (7, 8), (10, 11),
+    (0, 2), (1, 6), (5, 10), (9, 11),
+    (0, 3), (1, 2), (4, 6), (5, 7), (8, 11), (9, 10),
+    (1, 4), (3, 5), (6, 8), (7, 10),
+    (1, 3), (2, 5), (6, 9), (8, 10),
+    (2, 3), (4, 5), (6, 7), (8, 9),
+    (4, 6), (5, 7),
+    (3, 4), (5, 6), (7, 8),
+];
+
+#[rustfmt::skip]
+const NETWORK_13: &[(u8, u8)] = &[
+    (0, 12), (1, 10), (2, 9), (3, 7), (5, 11), (6, 8),
+    (1, 6), (2, 3), (4, 11), (7, 9), (8, 10),
+    (0, 4), (1, 2), (3, 6), (7, 8), (9, 10), (11, 12),
+    (4, 6), (5, 9), (8, 11), (10, 12),
+    (0, 5), (3, 8), (4, 7), (6, 11), (9, 10),
+    (0, 1), (2, 5), (6, 9), (7, 8), (10, 11),
+    (1, 3), (2, 4), (5, 6), (9, 10),
+    (1, 2), (3, 4), (5, 7), (6, 8),
+    (2, 3), (4, 5), (6, 7), (8, 9),
+    (3, 4), (5, 6),
+];
+
+#[rustfmt::skip]
+const NETWORK_14: &[(u8, u8)] = &[
+    (0, 1), (2, 3), (4, 5), (6, 7), (8, 9), (10, 11), (12, 13),
+    (0, 2), (1, 3), (4, 8), (5, 9), (10, 12), (11, 13),
+    (0, 4), (1, 2), (3, 7), (5, 8), (6, 10), (9, 13), (11, 12),
+    (0, 6), (1, 5), (3, 9), (4, 10), (7, 13), (8, 12),
+    (2, 10), (3, 11), (4, 6), (7, 9),
+    (1, 3), (2, 8), (5, 11), (6, 7), (10, 12),
+    (1, 4), (2, 6), (3, 5), (7, 11), (8, 10), (9, 12),
+    (2, 4), (3, 6), (5, 8), (7, 10), (9, 11),
+    (3, 4), (5, 6), (7, 8), (9, 10),
+    (6, 7),
+];
+
+#[rustfmt::skip]
+const NETWORK_15: &[(u8, u8)] = &[
+    (0, 13), (1, 12), (3, 14), (4, 8), (5, 6), (7, 11), (9, 10),
+    (0, 5), (1, 7), (2, 9), (3, 4), (6, 13), (8, 14), (11, 12),
+    (0, 1), (2, 3), (4, 5), (6, 8), (7, 9), (10, 11), (12, 13),
+    (0, 2), (1, 3), (4, 10), (5, 11), (6, 7), (8, 9), (12, 14),
+    (1, 2), (3, 12), (4, 6), (5, 7), (8, 10), (9, 11), (13, 14),
+    (1, 4), (2, 6), (5, 8), (7, 10), (9, 13), (11, 14),
+    (2, 4), (3, 6), (9, 12), (11, 13),
+    (3, 5), (6, 8), (7, 9), (10, 12),
+    (3, 4), (5, 6), (7, 8), (9, 10), (11, 12),
+    (6, 7), (8, 9),
+];
+
+#[rustfmt::skip]
+const NETWORK_16: &[(u8, u8)] = &[
+    (0, 13), (1, 12), (2, 15), (3, 14), (4, 8), (5, 6), (7, 11), (9, 10),
+    (0, 5), (1, 7), (2, 9), (3, 4), (6, 13), (8, 14), (10, 15), (11, 12),
+    (0, 1), (2, 3), (4, 5), (6, 8), (7, 9), (10, 11), (12, 13), (14, 15),
+    (0, 2), (1, 3), (4, 10), (5, 11), (6, 7), (8, 9), (12, 14), (13, 15),
+    (1, 2), (3, 12), (4, 6), (5, 7), (8, 10), (9, 11), (13, 14),
+    (1, 4), (2, 6), (5, 8), (7, 10), (9, 13), (11, 14),
+    (2, 4), (3, 6), (9, 12), (11, 13),
+    (3, 5), (6, 8), (7, 9), (10, 12),
+    (3, 4), (5, 6), (7, 8), (9, 10), (11, 12),
+    (6, 7), (8, 9),
+];
+
+/// Returns the comparators of the network sorting `n` elements.
+const fn network(n: usize) -> &'static [(u8, u8)] {
+    match n {
+        0 | 1 => &[],
+        2 => NETWORK_2,
+        3 => NETWORK_3,
+        4 => NETWORK_4,
+        5 => NETWORK_5,
+        6 => NETWORK_6,
+        7 => NETWORK_7,
+        8 => NETWORK_8,
+        9 => NETWORK_9,
+        10 => NETWORK_10,
+        11 => NETWORK_11,
+        12 => NETWORK_12,
+        13 => NETWORK_13,
+        14 => NETWORK_14,
+        15 => NETWORK_15,
+        16 => NETWORK_16,
+        _ => panic!("sorting networks are provided for at most 16 elements"),
+    }
+}
+
+/// The sorting network for `N` elements.
+struct Network<const N: usize>;
+
+impl<const N: usize> Network<N> {
+    /// Evaluated at compile time, so sorting longer arrays doesn't compile.
+    const COMPARATORS: &'static [(u8, u8)] = network(N);
+}
+
+/// Sorts the array using the optimal sorting network for its length.
+///
+/// See [`sort_network_by`].
+///
+/// # Examples
+///
+/// ```
+/// use insertion::sort_network;
+///
+/// let mut nmbrs = [4,3,2,5,1];
+/// sort_network(&mut nmbrs);
+///
+/// assert_eq!(nmbrs, [1,2,3,4,5]);
+/// ```
+pub fn sort_network<T, const N: usize>(array: &mut [T; N])
+where
+    T: Ord,
+{
+    sort_network_by(array, T::cmp);
+}
+
+/// Sorts the array using the optimal sorting network for its length
+/// with a comparator function.
+///
+/// Arrays of up to [`MAX_NETWORK_LEN`] elements are supported, sorting
+/// longer ones fails to compile. The network for `N` elements makes the
+/// same comparisons whatever the data, from 1 for 2 elements to 60 for 16.
+///
+/// The sort is not stable, equal elements may be reordered.
+///
+/// # Examples
+///
+/// ```
+/// use insertion::sort_network_by;
+///
+/// let mut nmbrs = [1,3,2,5,4];
+/// sort_network_by(&mut nmbrs, |a, b| b.cmp(a));
+///
+/// assert_eq!(nmbrs, [5,4,3,2,1]);
+/// ```
+///
+/// ```compile_fail
+/// let mut nmbrs = [0; 17];
+/// insertion::sort_network_by(&mut nmbrs, |a, b| a.cmp(b));
+/// ```
+pub fn sort_network_by<T, F, const N: usize>(array: &mut [T; N], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    observed_sort_network_by(array, &mut compare, &mut NoopObserver);
+}
+
+/// Sorts the array using the sorting network for its length,
+/// reporting the comparisons and swaps to the observer.
+fn observed_sort_network_by<T, F, O, const N: usize>(
+    array: &mut [T; N],
+    compare: &mut F,
+    observer: &mut O,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    for &(i, j) in Network::<N>::COMPARATORS {
+        compare_exchange(array, i as usize, j as usize, compare, observer);
+    }
+}
+
+/// Orders the elements at indices `i < j` of the array.
+///
+/// The smaller element is selected rather than branched on,
+/// so the compiler can emit conditional moves.
+fn compare_exchange<T, F, O>(array: &mut [T], i: usize, j: usize, compare: &mut F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    debug_assert!(i < j && j < array.len());
+    observer.on_compare(j, i);
+    let swap = compare(&array[j], &array[i]) == Ordering::Less;
+    if swap {
+        observer.on_swap(i, j);
+    }
+
+    // SAFETY: `i` and `j` are indices of the array, the comparison
+    // above checked their bounds, and the networks only hold distinct
+    // pairs. Both elements are read out before either is written back,
+    // and nothing can panic in between, so every element ends up in
+    // the array once.
+    unsafe {
+        let a = array.as_mut_ptr().add(i);
+        let b = array.as_mut_ptr().add(j);
+        let (lo, hi) = if swap { (b, a) } else { (a, b) };
+        let (lo, hi) = (ptr::read(lo), ptr::read(hi));
+        ptr::write(a, lo);
+        ptr::write(b, hi);
+    }
+}
+
+/// Sorts the array using the optimal sorting network for its length
+/// with a key extraction function.
+///
+/// # Examples
+///
+/// ```
+/// use insertion::sort_network_by_key;
+///
+/// let mut nmbrs = [-4,3,-2,5,1];
+/// sort_network_by_key(&mut nmbrs, |n: &i32| n.abs());
+///
+/// assert_eq!(nmbrs, [1,-2,3,-4,5]);
+/// ```
+pub fn sort_network_by_key<T, K, F, const N: usize>(array: &mut [T; N], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    sort_network_by(array, |a, b| f(a).cmp(&f(b)));
+}
+
+/// Sorts the slice of `N` elements with the sorting network for `N`.
+fn sort_slice_network_by<T, F, O, const N: usize>(
+    slice: &mut [T],
+    compare: &mut F,
+    observer: &mut O,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    if let Ok(array) = <&mut [T; N]>::try_from(slice) {
+        observed_sort_network_by(array, compare, observer);
+    }
+}
+
+/// Sorts the slice using the sorting network for its length
+/// with a comparator function.
+///
+/// Slices longer than [`MAX_NETWORK_LEN`] are sorted with insertion sort.
+/// Suited as the base case of unstable sorts.
+///
+/// The sort is not stable, equal elements may be reordered.
+///
+/// # Examples
+///
+/// ```
+/// use insertion::sort_small_by;
+///
+/// let mut nmbrs = vec![6,1,5,2,4,3];
+/// sort_small_by(&mut nmbrs[1..], |a, b| a.cmp(b));
+///
+/// assert_eq!(nmbrs, vec![6,1,2,3,4,5]);
+/// ```
+pub fn sort_small_by<T, F>(slice: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    sort_small_by_observed(slice, compare, &mut NoopObserver);
+}
+
+/// Sorts the slice using the sorting network for its length
+/// with a comparator function, reporting the comparisons and swaps
+/// to the observer.
+///
+/// See [`sort_small_by`].
+///
+/// # Examples
+///
+/// ```
+/// use insertion::sort_small_by_observed;
+/// use observe::SortStats;
+///
+/// let mut nmbrs = vec![4,3,2,1];
+/// let mut stats = SortStats::default();
+/// sort_small_by_observed(&mut nmbrs, |a, b| a.cmp(b), &mut stats);
+///
+/// assert_eq!(nmbrs, vec![1,2,3,4]);
+/// assert_eq!(stats.comparisons, 5);
+/// ```
+pub fn sort_small_by_observed<T, F, O>(slice: &mut [T], mut compare: F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let compare = &mut compare;
+    match slice.len() {
+        0 | 1 => {}
+        2 => sort_slice_network_by::<T, F, O, 2>(slice, compare, observer),
+        3 => sort_slice_network_by::<T, F, O, 3>(slice, compare, observer),
+        4 => sort_slice_network_by::<T, F, O, 4>(slice, compare, observer),
+        5 => sort_slice_network_by::<T, F, O, 5>(slice, compare, observer),
+        6 => sort_slice_network_by::<T, F, O, 6>(slice, compare, observer),
+        7 => sort_slice_network_by::<T, F, O, 7>(slice, compare, observer),
+        8 => sort_slice_network_by::<T, F, O, 8>(slice, compare, observer),
+        9 => sort_slice_network_by::<T, F, O, 9>(slice, compare, observer),
+        10 => sort_slice_network_by::<T, F, O, 10>(slice, compare, observer),
+        11 => sort_slice_network_by::<T, F, O, 11>(slice, compare, observer),
+        12 => sort_slice_network_by::<T, F, O, 12>(slice, compare, observer),
+        13 => sort_slice_network_by::<T, F, O, 13>(slice, compare, observer),
+        14 => sort_slice_network_by::<T, F, O, 14>(slice, compare, observer),
+        15 => sort_slice_network_by::<T, F, O, 15>(slice, compare, observer),
+        16 => sort_slice_network_by::<T, F, O, 16>(slice, compare, observer),
+        _ => super::sort_by_observed(slice, compare, observer),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_networks_have_best_known_sizes() {
+        let sizes = [0, 0, 1, 3, 5, 9, 12, 16, 19, 25, 29, 35, 39, 45, 51, 56, 60];
+        for (n, &size) in sizes.iter().enumerate() {
+            let comparators = network(n);
+            assert_eq!(comparators.len(), size, "{}", n);
+            assert!(comparators.iter().all(|&(i, j)| i < j && (j as usize) < n));
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! Shell sort.
+//!
+//! Insertion sort over elements which are a gap apart, for a decreasing
+//! sequence of gaps ending with 1. Elements travel far with the large
+//! gaps, so the final pass is an insertion sort of a nearly sorted slice.
+
+use std::cmp::Ordering;
+
+/// The gap sequence of [`shell_sort`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Gaps {
+    /// 1, 4, 10, 23, 57, 132, 301, 701, 1750, extended by a factor
+    /// of 2.25. The empirically best known sequence.
+    #[default]
+    Ciura,
+    /// 1, 4, 9, 20, 46, 103, ..., ⌈(9^k - 4^k) / (5 * 4^(k-1))⌉.
+    Tokuda,
+    /// 1, 8, 23, 77, 281, ..., 4^k + 3 * 2^(k-1) + 1,
+    /// O(n^(4/3)) time at worst.
+    Sedgewick,
+}
+
+/// The gaps found by Ciura, larger ones are extrapolated.
+const CIURA: [usize; 9] = [1, 4, 10, 23, 57, 132, 301, 701, 1750];
+
+impl Gaps {
+    /// Returns the gaps smaller than `len` in increasing order.
+    fn below(self, len: usize) -> Vec<usize> {
+        let mut gaps = vec![1];
+        loop {
+            let k = gaps.len();
+            let next = match self {
+                Gaps::Ciura if k < CIURA.len() => CIURA[k],
+                Gaps::Ciura => (gaps[k - 1] as f64 * 2.25) as usize,
+                Gaps::Tokuda => {
+                    let k = k as i32 + 1;
+                    ((9f64.powi(k) - 4f64.powi(k)) / (5.0 * 4f64.powi(k - 1))).ceil() as usize
+                }
+                Gaps::Sedgewick => (1 << (2 * k)) + 3 * (1 << (k - 1)) + 1,
+            };
+
+            if next >= len {
+                return gaps;
+            }
+            gaps.push(next);
+        }
+    }
+}
+
+/// Sorts the slice using shell sort with the given gap sequence.
+///
+/// # Examples
+///
+/// ```
+/// use insertion::{shell_sort, Gaps};
+///
+/// let mut nmbrs = vec![4,3,2,2,1];
+/// shell_sort(&mut nmbrs, Gaps::Ciura);
+///
+/// assert_eq!(nmbrs, vec![1,2,2,3,4]);
+/// ```
+pub fn shell_sort<T>(slice: &mut [T], gaps: Gaps)
+where
+    T: Ord,
+{
+    shell_sort_by(slice, gaps, T::cmp);
+}
+
+/// Sorts the slice using shell sort with the given gap sequence
+/// and a comparator function.
+///
+/// The sort is done in place with O(1) space complexity.
+/// It is not stable, equal elements may be reordered.
+///
+/// # Examples
+///
+/// ```
+/// use insertion::{shell_sort_by, Gaps};
+///
+/// let mut nmbrs: Vec<i32> = (0..100).collect();
+/// shell_sort_by(&mut nmbrs, Gaps::Tokuda, |a, b| b.cmp(a));
+///
+/// assert_eq!(nmbrs, (0..100).rev().collect::<Vec<_>>());
+/// ```
+pub fn shell_sort_by<T, F>(slice: &mut [T], gaps: Gaps, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for gap in gaps.below(slice.len()).into_iter().rev() {
+        for curr in gap..slice.len() {
+            let mut tmp = curr;
+            while tmp >= gap && compare(&slice[tmp], &slice[tmp - gap]) == Ordering::Less {
+                slice.swap(tmp, tmp - gap);
+                tmp -= gap;
+            }
+        }
+    }
+}
+
+/// Sorts the slice using shell sort with the given gap sequence
+/// and a key extraction function.
+///
+/// # Examples
+///
+/// ```
+/// use insertion::{shell_sort_by_key, Gaps};
+///
+/// let mut nmbrs = vec![-4,3,-2,1];
+/// shell_sort_by_key(&mut nmbrs, Gaps::Sedgewick, |n: &i32| n.abs());
+///
+/// assert_eq!(nmbrs, vec![1,-2,3,-4]);
+/// ```
+pub fn shell_sort_by_key<T, K, F>(slice: &mut [T], gaps: Gaps, mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    shell_sort_by(slice, gaps, |a, b| f(a).cmp(&f(b)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gaps() {
+        assert_eq!(Gaps::Ciura.below(0), vec![1]);
+        assert_eq!(Gaps::Ciura.below(2000), CIURA.to_vec());
+        assert_eq!(
+            Gaps::Ciura.below(5000),
+            vec![1, 4, 10, 23, 57, 132, 301, 701, 1750, 3937]
+        );
+        assert_eq!(Gaps::Tokuda.below(300), vec![1, 4, 9, 20, 46, 103, 233]);
+        assert_eq!(Gaps::Sedgewick.below(1100), vec![1, 8, 23, 77, 281, 1073]);
+    }
+}
@@ -1,4 +1,9 @@
-use insertion::sort;
+use insertion::{
+    binary_sort, binary_sort_by, binary_sort_by_key, shell_sort, shell_sort_by, shell_sort_by_key,
+    sort, sort_by, sort_by_key, sort_by_observed, sort_network, sort_network_by_key, sort_observed,
+    sort_small_by, sort_small_by_observed, Gaps,
+};
+use observe::SortStats;
 
 #[test]
 fn insertion_sort_test() {
@@ -8,3 +13,192 @@ fn insertion_sort_test() {
 
     assert_eq!(nmbrs, vec![1, 2, 2, 3, 4, 5]);
 }
+
+#[test]
+fn insertion_sort_by_test() {
+    let mut nmbrs = vec![4, 3, 2, 2, 5, 1];
+
+    sort_by(&mut nmbrs, |a, b| b.cmp(a));
+
+    assert_eq!(nmbrs, vec![5, 4, 3, 2, 2, 1]);
+}
+
+#[test]
+fn insertion_sort_by_key_is_stable() {
+    let mut pairs = vec![(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd'), (1, 'e')];
+
+    sort_by_key(&mut pairs, |p| p.0);
+
+    assert_eq!(
+        pairs,
+        vec![(1, 'b'), (1, 'e'), (2, 'd'), (3, 'a'), (3, 'c')]
+    );
+}
+
+#[test]
+fn insertion_sort_observed_test() {
+    let mut nmbrs = vec![4, 3, 2, 2, 5, 1];
+    let mut stats = SortStats::default();
+
+    sort_observed(&mut nmbrs, &mut stats);
+
+    assert_eq!(nmbrs, vec![1, 2, 2, 3, 4, 5]);
+    assert_eq!(stats.swaps, 10);
+    assert_eq!(stats.moves, 0);
+    assert_eq!(stats.recursions, 0);
+}
+
+#[test]
+fn insertion_sort_by_observed_counts_comparisons_test() {
+    let mut nmbrs: Vec<i32> = (0..100).map(|i| (i * 37) % 100).collect();
+    let mut stats = SortStats::default();
+    let mut comparisons = 0;
+
+    sort_by_observed(
+        &mut nmbrs,
+        |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        },
+        &mut stats,
+    );
+
+    assert_eq!(nmbrs, (0..100).collect::<Vec<_>>());
+    assert_eq!(stats.comparisons, comparisons);
+}
+
+#[test]
+fn binary_sort_works() {
+    let mut nmbrs: Vec<i32> = (0..200).map(|i| (i * 7919) % 53).collect();
+    let mut want = nmbrs.clone();
+    want.sort();
+
+    binary_sort(&mut nmbrs);
+
+    assert_eq!(nmbrs, want);
+}
+
+#[test]
+fn binary_sort_makes_few_comparisons() {
+    let mut words: Vec<String> = (0..256).rev().map(|i| format!("{:03}", i)).collect();
+    let mut comparisons = 0;
+
+    binary_sort_by(&mut words, |a, b| {
+        comparisons += 1;
+        a.cmp(b)
+    });
+
+    assert!(words.windows(2).all(|w| w[0] <= w[1]));
+    // At most ⌈log2(i + 1)⌉ comparisons for the i-th element.
+    assert!(comparisons <= 256 * 8);
+}
+
+#[test]
+fn binary_sort_by_key_is_stable() {
+    let mut pairs = vec![(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd'), (1, 'e')];
+
+    binary_sort_by_key(&mut pairs, |p| p.0);
+
+    assert_eq!(
+        pairs,
+        vec![(1, 'b'), (1, 'e'), (2, 'd'), (3, 'a'), (3, 'c')]
+    );
+}
+
+#[test]
+fn shell_sort_works_with_every_gap_sequence() {
+    for gaps in [Gaps::Ciura, Gaps::Tokuda, Gaps::Sedgewick].iter() {
+        let mut nmbrs: Vec<i32> = (0..5000).map(|i| (i * 7919) % 1009).collect();
+        let mut want = nmbrs.clone();
+        want.sort();
+
+        shell_sort(&mut nmbrs, *gaps);
+        assert_eq!(nmbrs, want);
+
+        shell_sort_by(&mut nmbrs, *gaps, |a, b| b.cmp(a));
+        want.reverse();
+        assert_eq!(nmbrs, want);
+    }
+
+    let mut empty: Vec<i32> = vec![];
+    shell_sort_by_key(&mut empty, Gaps::default(), |n| *n);
+    assert_eq!(empty, vec![]);
+}
+
+/// Sorts every array of zeros and ones with the network for `N` elements.
+///
+/// By the 0-1 principle a network sorting all of them sorts any input.
+fn assert_network_sorts_zero_one_inputs<const N: usize>() {
+    for bits in 0u32..1 << N {
+        let mut array = [0u8; N];
+        for (i, x) in array.iter_mut().enumerate() {
+            *x = (bits >> i & 1) as u8;
+        }
+
+        sort_network(&mut array);
+
+        let zeros = N - bits.count_ones() as usize;
+        assert!(
+            array[..zeros].iter().all(|&x| x == 0) && array[zeros..].iter().all(|&x| x == 1),
+            "the network for {} elements doesn't sort {:0width$b}",
+            N,
+            bits,
+            width = N
+        );
+    }
+}
+
+#[test]
+fn sort_network_satisfies_zero_one_principle() {
+    assert_network_sorts_zero_one_inputs::<0>();
+    assert_network_sorts_zero_one_inputs::<1>();
+    assert_network_sorts_zero_one_inputs::<2>();
+    assert_network_sorts_zero_one_inputs::<3>();
+    assert_network_sorts_zero_one_inputs::<4>();
+    assert_network_sorts_zero_one_inputs::<5>();
+    assert_network_sorts_zero_one_inputs::<6>();
+    assert_network_sorts_zero_one_inputs::<7>();
+    assert_network_sorts_zero_one_inputs::<8>();
+    assert_network_sorts_zero_one_inputs::<9>();
+    assert_network_sorts_zero_one_inputs::<10>();
+    assert_network_sorts_zero_one_inputs::<11>();
+    assert_network_sorts_zero_one_inputs::<12>();
+    assert_network_sorts_zero_one_inputs::<13>();
+    assert_network_sorts_zero_one_inputs::<14>();
+    assert_network_sorts_zero_one_inputs::<15>();
+    assert_network_sorts_zero_one_inputs::<16>();
+}
+
+#[test]
+fn sort_network_by_key_test() {
+    let mut words = ["ccc", "a", "dddd", "bb"];
+
+    sort_network_by_key(&mut words, |w| w.len());
+
+    assert_eq!(words, ["a", "bb", "ccc", "dddd"]);
+}
+
+#[test]
+fn sort_small_by_test() {
+    for len in 0..=20 {
+        let mut nmbrs: Vec<u32> = (0..len).map(|i| i * 7 % 11).collect();
+        let mut expected = nmbrs.clone();
+        expected.sort_unstable();
+
+        sort_small_by(&mut nmbrs, |a, b| a.cmp(b));
+
+        assert_eq!(nmbrs, expected, "{}", len);
+    }
+}
+
+#[test]
+fn sort_small_by_observed_counts_network_comparisons() {
+    let mut nmbrs: Vec<u32> = (0..16).rev().collect();
+    let mut stats = SortStats::default();
+    sort_small_by_observed(&mut nmbrs, |a, b| a.cmp(b), &mut stats);
+
+    assert_eq!(nmbrs, (0..16).collect::<Vec<_>>());
+    assert_eq!(stats.comparisons, 60);
+    assert!(stats.swaps > 0);
+    assert_eq!(stats.moves, 0);
+}
@@ -0,0 +1,528 @@
+//! In-place stable block merge, in the style of WikiSort.
+//!
+//! To merge the sorted runs A and B without a buffer, unique values are
+//! pulled out of A to the start of the slice to form two internal buffers.
+//! A is then split into blocks of about sqrt(|A|) elements. The first
+//! buffer tags the A blocks so that their order can be recovered, the A
+//! blocks are rolled through B and dropped where they belong, and every
+//! A block is merged with the B values following it using the second
+//! buffer as swap space. Finally the buffers are sorted and merged back.
+//!
+//! Everything is done with swaps and rotations, the merge allocates nothing.
+//! If A doesn't have enough unique values for both buffers, the ones it has
+//! tag larger blocks, and every A block is merged with the B values following
+//! it by rotations instead. There are at most as many rotations as there are
+//! unique values in the block, so the merge still takes O(n) moves.
+//! If one of the runs is short, the runs are merged by rotations as well.
+
+use std::cmp::Ordering;
+
+use observe::{Shifted, SortObserver};
+
+use super::{lower_bound, rotate_left, upper_bound};
+
+/// Runs with at most this many elements are merged by rotations.
+const ROTATION_THRESHOLD: usize = 16;
+
+/// A runs with at most this many unique values are merged by rotations.
+const ROTATION_UNIQUE: usize = 8;
+
+/// Swaps the `len` elements starting at `a` with the `len` elements starting at `b`.
+fn block_swap<T, O>(slice: &mut [T], a: usize, b: usize, len: usize, observer: &mut O)
+where
+    O: SortObserver,
+{
+    for i in 0..len {
+        observer.on_swap(a + i, b + i);
+        slice.swap(a + i, b + i);
+    }
+}
+
+/// Merges the sorted runs [0, mid) and [mid, len) of the slice by rotations.
+///
+/// Every rotation moves the B values which are smaller than the next A value
+/// in front of it, so there are at most as many rotations as there are
+/// unique values in A, or values in B.
+fn rotation_merge<T, F, O>(slice: &mut [T], mut mid: usize, compare: &mut F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let len = slice.len();
+    let mut lo = 0;
+
+    while lo < mid && mid < len {
+        lo = upper_bound(slice, (lo, mid), mid, compare, observer);
+        if lo == mid {
+            return;
+        }
+
+        let count = lower_bound(slice, (mid, len), lo, compare, observer) - mid;
+        rotate_left(slice, (lo, mid + count), mid - lo, observer);
+
+        lo += count + 1;
+        mid += count;
+    }
+}
+
+/// Pulls up to `want` unique values out of the sorted slice to its start.
+///
+/// The first occurrences of the smallest values are pulled, and the
+/// values which are left behind keep their relative order.
+/// Returns the number of values pulled, which is less than `want`
+/// if there aren't enough unique values. If there are less than `min`
+/// nothing is pulled and the slice is left as is.
+fn pull_unique<T, F, O>(
+    slice: &mut [T],
+    (min, want): (usize, usize),
+    compare: &mut F,
+    observer: &mut O,
+) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    if slice.is_empty() || want == 0 {
+        return 0;
+    }
+
+    // Find the first occurrence of the last unique value to pull.
+    let (mut last, mut count) = (0, 1);
+    while count < want {
+        let next = upper_bound(slice, (last, slice.len()), last, compare, observer);
+        if next == slice.len() {
+            break;
+        }
+        last = next;
+        count += 1;
+    }
+    if count < min {
+        return 0;
+    }
+
+    // Grow the block of unique values from the last one to the left, moving
+    // the duplicates in between behind it, until it reaches the start.
+    let (mut start, mut end) = (last, last + 1);
+    while start > 0 {
+        let first = lower_bound(slice, (0, start), start - 1, compare, observer);
+        let gap = start - first - 1;
+        rotate_left(slice, (first + 1, end), gap, observer);
+        start = first;
+        end -= gap;
+    }
+
+    count
+}
+
+/// Merges the sorted run A = [0, mid) back into the sorted run [mid, len),
+/// where A holds unique values which were pulled out of the slice.
+///
+/// Every value of A goes before the values equal to it, which is where it
+/// was pulled out from.
+fn redistribute<T, F, O>(slice: &mut [T], mid: usize, compare: &mut F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let len = slice.len();
+    let (mut start, mut end) = (0, mid);
+
+    while start < end {
+        let amount = lower_bound(slice, (end, len), start, compare, observer) - end;
+        rotate_left(slice, (start, end + amount), end - start, observer);
+
+        start += amount + 1;
+        end += amount;
+    }
+}
+
+/// Merges the A values, which have been swapped into the buffer starting at
+/// `buf`, with the B values [a_end, b_end) into [a_start, b_end).
+///
+/// The values are moved by swapping them with whatever is at their
+/// destination, so the buffer gets its values back, in a different order.
+fn merge_internal<T, F, O>(
+    slice: &mut [T],
+    (a_start, a_end, b_end): (usize, usize, usize),
+    buf: usize,
+    compare: &mut F,
+    observer: &mut O,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let a_len = a_end - a_start;
+    let b_len = b_end - a_end;
+    let (mut a_count, mut b_count, mut insert) = (0, 0, a_start);
+
+    if a_len > 0 && b_len > 0 {
+        loop {
+            observer.on_compare(a_end + b_count, buf + a_count);
+            if compare(&slice[a_end + b_count], &slice[buf + a_count]) != Ordering::Less {
+                observer.on_swap(insert, buf + a_count);
+                slice.swap(insert, buf + a_count);
+                a_count += 1;
+                insert += 1;
+                if a_count == a_len {
+                    break;
+                }
+            } else {
+                observer.on_swap(insert, a_end + b_count);
+                slice.swap(insert, a_end + b_count);
+                b_count += 1;
+                insert += 1;
+                if b_count == b_len {
+                    break;
+                }
+            }
+        }
+    }
+
+    block_swap(slice, buf + a_count, insert, a_len - a_count, observer);
+}
+
+/// Merges the sorted runs [a_start, b_start) and [b_start, b_end) using the
+/// tag buffer starting at `tags` and the swap buffer starting at `buf`,
+/// which holds at least `block_size` values.
+///
+/// The tag buffer needs one value for every full A block, and its values
+/// must be unique and sorted. Without a swap buffer the A blocks are
+/// merged by rotations.
+fn block_merge<T, F, O>(
+    slice: &mut [T],
+    (a_start, b_start, b_end): (usize, usize, usize),
+    block_size: usize,
+    (tags, buf): (usize, Option<usize>),
+    compare: &mut F,
+    observer: &mut O,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let first_a_len = (b_start - a_start) % block_size;
+
+    // Tag every full A block by swapping its first value with a tag.
+    let mut tag = tags;
+    let mut block = a_start + first_a_len;
+    while block < b_start {
+        observer.on_swap(tag, block);
+        slice.swap(tag, block);
+        tag += 1;
+        block += block_size;
+    }
+
+    let (mut last_a_start, mut last_a_end) = (a_start, a_start + first_a_len);
+    let (mut last_b_start, mut last_b_end) = (0, 0);
+    let (mut block_a_start, mut block_a_end) = (last_a_end, b_start);
+    let (mut block_b_start, mut block_b_end) = (b_start, b_end.min(b_start + block_size));
+    let mut next_tag = tags;
+
+    if let Some(buf) = buf {
+        block_swap(slice, last_a_start, buf, first_a_len, observer);
+    }
+
+    while block_a_start < block_a_end {
+        let last_b_len = last_b_end - last_b_start;
+        let drop_a = block_b_start == block_b_end
+            || (last_b_len > 0 && {
+                observer.on_compare(last_b_end - 1, next_tag);
+                compare(&slice[last_b_end - 1], &slice[next_tag]) != Ordering::Less
+            });
+
+        if drop_a {
+            // Split the previous B block where the smallest A block goes.
+            let b_split = lower_bound(
+                slice,
+                (last_b_start, last_b_end),
+                next_tag,
+                compare,
+                observer,
+            );
+            let b_remaining = last_b_end - b_split;
+
+            // The smallest tag belongs to the A block that was first in A.
+            let mut min_a = block_a_start;
+            let mut find_a = min_a + block_size;
+            while find_a < block_a_end {
+                observer.on_compare(find_a, min_a);
+                if compare(&slice[find_a], &slice[min_a]) == Ordering::Less {
+                    min_a = find_a;
+                }
+                find_a += block_size;
+            }
+            block_swap(slice, block_a_start, min_a, block_size, observer);
+
+            // Put the first value of the A block back in place of its tag.
+            observer.on_swap(block_a_start, next_tag);
+            slice.swap(block_a_start, next_tag);
+            next_tag += 1;
+
+            merge_last_a(
+                slice,
+                (last_a_start, last_a_end, b_split),
+                buf,
+                compare,
+                observer,
+            );
+
+            if let Some(buf) = buf {
+                // Move the A block to the buffer, the buffer values it leaves
+                // behind can be overwritten by the rest of the B block.
+                block_swap(slice, block_a_start, buf, block_size, observer);
+                block_swap(
+                    slice,
+                    b_split,
+                    block_a_start + block_size - b_remaining,
+                    b_remaining,
+                    observer,
+                );
+            } else {
+                // Move the A block in front of the rest of the B block.
+                rotate_left(
+                    slice,
+                    (b_split, block_a_start + block_size),
+                    b_remaining,
+                    observer,
+                );
+            }
+
+            last_a_start = block_a_start - b_remaining;
+            last_a_end = last_a_start + block_size;
+            last_b_start = last_a_end;
+            last_b_end = last_a_end + b_remaining;
+
+            block_a_start += block_size;
+        } else if block_b_end - block_b_start < block_size {
+            // Move the last, shorter, B block in front of the A blocks.
+            let b_len = block_b_end - block_b_start;
+            rotate_left(
+                slice,
+                (block_a_start, block_b_end),
+                block_b_start - block_a_start,
+                observer,
+            );
+
+            last_b_start = block_a_start;
+            last_b_end = block_a_start + b_len;
+            block_a_start += b_len;
+            block_a_end += b_len;
+            block_b_end = block_b_start;
+        } else {
+            // Roll the leftmost A block to the end by swapping it with the next B block.
+            block_swap(slice, block_a_start, block_b_start, block_size, observer);
+
+            last_b_start = block_a_start;
+            last_b_end = block_a_start + block_size;
+            block_a_start += block_size;
+            block_a_end += block_size;
+            block_b_start += block_size;
+            block_b_end = b_end.min(block_b_end + block_size);
+        }
+    }
+
+    merge_last_a(
+        slice,
+        (last_a_start, last_a_end, b_end),
+        buf,
+        compare,
+        observer,
+    );
+}
+
+/// Merges the previous A block with the B values [a_end, b_end) following it,
+/// through the swap buffer if there is one, where the A values are then,
+/// otherwise by rotations.
+fn merge_last_a<T, F, O>(
+    slice: &mut [T],
+    (a_start, a_end, b_end): (usize, usize, usize),
+    buf: Option<usize>,
+    compare: &mut F,
+    observer: &mut O,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    match buf {
+        Some(buf) => merge_internal(slice, (a_start, a_end, b_end), buf, compare, observer),
+        None => rotation_merge(
+            &mut slice[a_start..b_end],
+            a_end - a_start,
+            compare,
+            &mut Shifted::new(&mut *observer, a_start),
+        ),
+    }
+}
+
+/// Merges the sorted runs [0, mid) and [mid, len) of the slice in place.
+///
+/// The merge is stable and uses O(1) space.
+pub(crate) fn merge<T, F, O>(slice: &mut [T], mid: usize, compare: &mut F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let len = slice.len();
+    if mid == 0 || mid == len {
+        return;
+    }
+
+    observer.on_compare(mid, mid - 1);
+    if compare(&slice[mid], &slice[mid - 1]) != Ordering::Less {
+        return;
+    }
+
+    observer.on_compare(len - 1, 0);
+    if compare(&slice[len - 1], &slice[0]) == Ordering::Less {
+        rotate_left(slice, (0, len), mid, observer);
+        return;
+    }
+
+    if mid <= ROTATION_THRESHOLD || len - mid <= ROTATION_THRESHOLD {
+        rotation_merge(slice, mid, compare, observer);
+        return;
+    }
+
+    let mut block_size = (mid as f64).sqrt() as usize;
+    let mut buffer_size = mid / block_size + 1;
+
+    // Without enough unique values for the swap buffer, all of them tag
+    // blocks large enough to need no more tags than that. With only a few
+    // the runs are merged by rotations, as many as there are unique values.
+    let pulled = pull_unique(
+        &mut slice[..mid],
+        (ROTATION_UNIQUE + 1, 2 * buffer_size),
+        compare,
+        observer,
+    );
+    let buf = if pulled == 2 * buffer_size {
+        Some(buffer_size)
+    } else if pulled == 0 {
+        rotation_merge(slice, mid, compare, observer);
+        return;
+    } else {
+        buffer_size = pulled;
+        block_size = (mid - pulled) / pulled + 1;
+        None
+    };
+
+    let a_start = pulled;
+    observer.on_compare(len - 1, a_start);
+    if compare(&slice[len - 1], &slice[a_start]) == Ordering::Less {
+        rotate_left(slice, (a_start, len), mid - a_start, observer);
+    } else {
+        observer.on_compare(mid, mid - 1);
+        if compare(&slice[mid], &slice[mid - 1]) == Ordering::Less {
+            block_merge(
+                slice,
+                (a_start, mid, len),
+                block_size,
+                (0, buf),
+                compare,
+                observer,
+            );
+        }
+    }
+
+    // The tag buffer got its values back in order, the swap buffer not.
+    if buf.is_some() {
+        super::tim::observed_binary_insertion_sort(
+            &mut slice[buffer_size..pulled],
+            1,
+            compare,
+            &mut Shifted::new(&mut *observer, buffer_size),
+        );
+    }
+    redistribute(slice, pulled, compare, observer);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use observe::{NoopObserver, Trace};
+
+    #[test]
+    fn test_rotation_merge() {
+        let mut nmbrs = vec![1, 3, 3, 5, 7, 2, 3, 4, 8];
+        rotation_merge(&mut nmbrs, 5, &mut i32::cmp, &mut NoopObserver);
+        assert_eq!(nmbrs, vec![1, 2, 3, 3, 3, 4, 5, 7, 8]);
+    }
+
+    #[test]
+    fn test_pull_unique() {
+        let mut pairs = vec![(1, 'a'), (1, 'b'), (2, 'c'), (3, 'd'), (3, 'e'), (4, 'f')];
+        assert_eq!(
+            pull_unique(
+                &mut pairs,
+                (1, 3),
+                &mut |a, b| a.0.cmp(&b.0),
+                &mut NoopObserver
+            ),
+            3
+        );
+        assert_eq!(
+            pairs,
+            vec![(1, 'a'), (2, 'c'), (3, 'd'), (1, 'b'), (3, 'e'), (4, 'f')]
+        );
+
+        redistribute(&mut pairs, 3, &mut |a, b| a.0.cmp(&b.0), &mut NoopObserver);
+        assert_eq!(
+            pairs,
+            vec![(1, 'a'), (1, 'b'), (2, 'c'), (3, 'd'), (3, 'e'), (4, 'f')]
+        );
+    }
+
+    #[test]
+    fn test_pull_unique_without_enough_values() {
+        let mut nmbrs = vec![1, 1, 2, 2];
+        assert_eq!(
+            pull_unique(&mut nmbrs, (1, 3), &mut i32::cmp, &mut NoopObserver),
+            2
+        );
+        assert_eq!(nmbrs, vec![1, 2, 1, 2]);
+
+        let mut nmbrs = vec![1, 1, 2, 2];
+        assert_eq!(
+            pull_unique(&mut nmbrs, (3, 4), &mut i32::cmp, &mut NoopObserver),
+            0
+        );
+        assert_eq!(nmbrs, vec![1, 1, 2, 2]);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut nmbrs: Vec<usize> = (0..100)
+            .map(|i| 2 * i)
+            .chain((0..60).map(|i| 3 * i))
+            .collect();
+        merge(&mut nmbrs, 100, &mut usize::cmp, &mut NoopObserver);
+
+        let mut want: Vec<usize> = (0..100)
+            .map(|i| 2 * i)
+            .chain((0..60).map(|i| 3 * i))
+            .collect();
+        want.sort();
+        assert_eq!(nmbrs, want);
+    }
+
+    #[test]
+    fn test_merge_with_few_unique_values() {
+        // 20 unique values in A are not enough for the swap buffer, but tag the blocks.
+        let input: Vec<(usize, usize)> = (0..400)
+            .map(|i| (i / 20, i))
+            .chain((0..300).map(|i| (i % 30, 400 + i)))
+            .collect();
+        let mut pairs = input.clone();
+        pairs[400..].sort();
+        let sorted = pairs.clone();
+        let mut trace = Trace::new();
+        merge(&mut pairs, 400, &mut |a, b| a.0.cmp(&b.0), &mut trace);
+
+        let mut want = sorted.clone();
+        want.sort_by_key(|p| p.0);
+        assert_eq!(pairs, want);
+        assert_eq!(
+            observe::replay_by(&sorted, trace.events(), |a, b| a.0.cmp(&b.0)),
+            Ok(pairs)
+        );
+    }
+}
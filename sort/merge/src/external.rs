@@ -0,0 +1,434 @@
+//! External merge sort.
+//!
+//! The records are read in chunks which fit into the memory budget,
+//! every chunk is sorted with [`sort_by`](crate::sort_by) and spilled
+//! into a temporary file. The sorted runs are then merged by keeping the
+//! smallest unmerged record of every run in a binary heap.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::env;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic;
+use std::sync::atomic::AtomicUsize;
+
+use super::sort_by;
+
+/// Default memory budget of [`ExternalSortOptions`], 64 MiB.
+const DEFAULT_MEMORY_BUDGET: usize = 64 << 20;
+
+/// Maximum number of runs merged at once. With more runs
+/// they are first merged in groups into longer runs.
+const MAX_FAN_IN: usize = 64;
+
+/// Number of temporary files created by this process,
+/// makes their names unique.
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// The format in which records are read, spilled and written.
+pub trait Encoding {
+    /// The decoded record.
+    type Record;
+
+    /// Reads the next record, returns `None` at the end of the input.
+    fn read<R: BufRead>(&self, reader: &mut R) -> io::Result<Option<Self::Record>>;
+
+    /// Writes the record such that [`read`](Encoding::read) reads it back.
+    fn write<W: Write>(&self, writer: &mut W, record: &Self::Record) -> io::Result<()>;
+
+    /// Returns the approximate number of bytes the record occupies in memory.
+    fn size_of(&self, record: &Self::Record) -> usize;
+}
+
+/// Newline delimited UTF-8 text, every line is a record.
+///
+/// The line terminator, `\n` or `\r\n`, is not part of the record,
+/// every record is written with a `\n`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Lines;
+
+impl Encoding for Lines {
+    type Record = String;
+
+    fn read<R: BufRead>(&self, reader: &mut R) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+        Ok(Some(line))
+    }
+
+    fn write<W: Write>(&self, writer: &mut W, record: &String) -> io::Result<()> {
+        writer.write_all(record.as_bytes())?;
+        writer.write_all(b"\n")
+    }
+
+    fn size_of(&self, record: &String) -> usize {
+        mem::size_of::<String>() + record.len()
+    }
+}
+
+/// Binary records, each prefixed with its length as a little endian `u32`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LengthPrefixed;
+
+impl Encoding for LengthPrefixed {
+    type Record = Vec<u8>;
+
+    fn read<R: BufRead>(&self, reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+        if reader.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+
+        let mut len = [0; 4];
+        reader.read_exact(&mut len)?;
+        let mut record = vec![0; u32::from_le_bytes(len) as usize];
+        reader.read_exact(&mut record)?;
+        Ok(Some(record))
+    }
+
+    fn write<W: Write>(&self, writer: &mut W, record: &Vec<u8>) -> io::Result<()> {
+        let len = u32::try_from(record.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "record is too long for a u32 length prefix",
+            )
+        })?;
+        writer.write_all(&len.to_le_bytes())?;
+        writer.write_all(record)
+    }
+
+    fn size_of(&self, record: &Vec<u8>) -> usize {
+        mem::size_of::<Vec<u8>>() + record.len()
+    }
+}
+
+/// Options of [`external_sort`].
+///
+/// # Examples
+///
+/// ```
+/// use merge::ExternalSortOptions;
+///
+/// let options = ExternalSortOptions::default()
+///     .memory_budget(1 << 20)
+///     .temp_dir("/tmp");
+///
+/// assert_eq!(options.memory_budget, 1 << 20);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalSortOptions {
+    /// Approximate number of bytes the records of a chunk may occupy
+    /// in memory before it is sorted and spilled, defaults to 64 MiB.
+    pub memory_budget: usize,
+    /// The directory of the temporary files,
+    /// defaults to [`env::temp_dir`].
+    pub temp_dir: PathBuf,
+}
+
+impl Default for ExternalSortOptions {
+    fn default() -> Self {
+        ExternalSortOptions {
+            memory_budget: DEFAULT_MEMORY_BUDGET,
+            temp_dir: env::temp_dir(),
+        }
+    }
+}
+
+impl ExternalSortOptions {
+    /// Sets the memory budget in bytes.
+    pub fn memory_budget(mut self, bytes: usize) -> Self {
+        self.memory_budget = bytes;
+        self
+    }
+
+    /// Sets the directory of the temporary files.
+    pub fn temp_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.temp_dir = dir.into();
+        self
+    }
+}
+
+/// A temporary file holding a sorted run, removed when dropped.
+struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    /// Creates a new empty temporary file within the directory.
+    fn create(dir: &Path) -> io::Result<(TempFile, File)> {
+        let name = format!(
+            "merge-external-{}-{}.run",
+            process::id(),
+            TEMP_FILES.fetch_add(1, atomic::Ordering::Relaxed)
+        );
+        let path = dir.join(name);
+        let file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok((TempFile { path }, file))
+    }
+
+    /// Opens the file for reading.
+    fn open(&self) -> io::Result<BufReader<File>> {
+        File::open(&self.path).map(BufReader::new)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Writes the records of the sorted chunk into a new temporary file.
+fn spill<E>(chunk: &[E::Record], encoding: &E, dir: &Path) -> io::Result<TempFile>
+where
+    E: Encoding,
+{
+    let (run, file) = TempFile::create(dir)?;
+    let mut writer = BufWriter::new(file);
+    for record in chunk {
+        encoding.write(&mut writer, record)?;
+    }
+    writer.flush()?;
+
+    Ok(run)
+}
+
+/// Merges the sorted runs into the writer.
+///
+/// Ties are won by the record of the earlier run,
+/// so the merge is stable if the runs are in input order.
+fn merge_runs<E, W, F>(
+    runs: &[TempFile],
+    encoding: &E,
+    writer: &mut W,
+    compare: &mut F,
+) -> io::Result<()>
+where
+    E: Encoding,
+    W: Write,
+    F: FnMut(&E::Record, &E::Record) -> Ordering,
+{
+    let mut readers = runs
+        .iter()
+        .map(TempFile::open)
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let mut heap = Vec::with_capacity(readers.len());
+    for (run, reader) in readers.iter_mut().enumerate() {
+        if let Some(record) = encoding.read(reader)? {
+            heap.push((record, run));
+        }
+    }
+
+    // The heap is a max-heap, reversing the comparison
+    // puts the smallest record of the earliest run at the root.
+    let mut reversed =
+        |a: &(E::Record, usize), b: &(E::Record, usize)| compare(&b.0, &a.0).then(b.1.cmp(&a.1));
+    heap::heapify_by(&mut heap, &mut reversed);
+
+    while !heap.is_empty() {
+        let run = heap[0].1;
+        encoding.write(writer, &heap[0].0)?;
+
+        match encoding.read(&mut readers[run])? {
+            Some(record) => heap[0].0 = record,
+            None => {
+                heap.swap_remove(0);
+            }
+        }
+        heap::sift_down_by(&mut heap, 0, &mut reversed);
+    }
+
+    Ok(())
+}
+
+/// Sorts the records of the reader into the writer.
+///
+/// See [`external_sort_by`].
+///
+/// # Examples
+///
+/// ```
+/// use merge::{external_sort, ExternalSortOptions, Lines};
+///
+/// let input = "pear\napple\nfig\n";
+/// let mut output = Vec::new();
+/// external_sort(input.as_bytes(), &mut output, Lines, &ExternalSortOptions::default()).unwrap();
+///
+/// assert_eq!(output, b"apple\nfig\npear\n");
+/// ```
+pub fn external_sort<E, R, W>(
+    reader: R,
+    writer: W,
+    encoding: E,
+    options: &ExternalSortOptions,
+) -> io::Result<()>
+where
+    E: Encoding,
+    E::Record: Ord,
+    R: BufRead,
+    W: Write,
+{
+    external_sort_by(reader, writer, encoding, options, |a, b| a.cmp(b))
+}
+
+/// Sorts the records of the reader into the writer with a comparator function.
+///
+/// Chunks of records within the memory budget are sorted in memory
+/// and spilled into temporary files, which are merged into the writer.
+/// If all the records fit into the budget no temporary file is created.
+///
+/// The temporary files are removed once the sort returns,
+/// also when it fails on an I/O error.
+///
+/// The sort is stable, equal records keep their relative order.
+///
+/// # Examples
+///
+/// ```
+/// use merge::{external_sort_by, ExternalSortOptions, LengthPrefixed};
+///
+/// let mut input = Vec::new();
+/// for record in [&b"bb"[..], b"a", b"ccc"].iter() {
+///     input.extend_from_slice(&(record.len() as u32).to_le_bytes());
+///     input.extend_from_slice(record);
+/// }
+///
+/// let options = ExternalSortOptions::default().memory_budget(0);
+/// let mut output = Vec::new();
+/// external_sort_by(&input[..], &mut output, LengthPrefixed, &options, |a, b| b.cmp(a)).unwrap();
+///
+/// assert_eq!(output, b"\x03\0\0\0ccc\x02\0\0\0bb\x01\0\0\0a");
+/// ```
+pub fn external_sort_by<E, R, W, F>(
+    mut reader: R,
+    mut writer: W,
+    encoding: E,
+    options: &ExternalSortOptions,
+    mut compare: F,
+) -> io::Result<()>
+where
+    E: Encoding,
+    R: BufRead,
+    W: Write,
+    F: FnMut(&E::Record, &E::Record) -> Ordering,
+{
+    let mut runs = Vec::new();
+    let mut chunk = Vec::new();
+    let mut used = 0;
+
+    while let Some(record) = encoding.read(&mut reader)? {
+        used += encoding.size_of(&record);
+        chunk.push(record);
+
+        if used >= options.memory_budget {
+            sort_by(&mut chunk, &mut compare);
+            runs.push(spill(&chunk, &encoding, &options.temp_dir)?);
+            chunk.clear();
+            used = 0;
+        }
+    }
+
+    sort_by(&mut chunk, &mut compare);
+    if runs.is_empty() {
+        for record in &chunk {
+            encoding.write(&mut writer, record)?;
+        }
+        return writer.flush();
+    }
+
+    if !chunk.is_empty() {
+        runs.push(spill(&chunk, &encoding, &options.temp_dir)?);
+    }
+    drop(chunk);
+
+    while runs.len() > MAX_FAN_IN {
+        let mut merged = Vec::with_capacity(runs.len().div_ceil(MAX_FAN_IN));
+        for group in runs.chunks(MAX_FAN_IN) {
+            let (run, file) = TempFile::create(&options.temp_dir)?;
+            let mut run_writer = BufWriter::new(file);
+            merge_runs(group, &encoding, &mut run_writer, &mut compare)?;
+            run_writer.flush()?;
+            merged.push(run);
+        }
+        runs = merged;
+    }
+
+    merge_runs(&runs, &encoding, &mut writer, &mut compare)?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        let mut input = "a\r\nb\n\nc".as_bytes();
+        let mut lines = Vec::new();
+        while let Some(line) = Lines.read(&mut input).unwrap() {
+            lines.push(line);
+        }
+        assert_eq!(lines, vec!["a", "b", "", "c"]);
+
+        let mut output = Vec::new();
+        Lines.write(&mut output, &lines[0]).unwrap();
+        assert_eq!(output, b"a\n");
+    }
+
+    #[test]
+    fn test_length_prefixed() {
+        let mut output = Vec::new();
+        LengthPrefixed.write(&mut output, &vec![]).unwrap();
+        LengthPrefixed.write(&mut output, &vec![7, 8]).unwrap();
+        assert_eq!(output, vec![0, 0, 0, 0, 2, 0, 0, 0, 7, 8]);
+
+        let mut input = &output[..];
+        assert_eq!(LengthPrefixed.read(&mut input).unwrap(), Some(vec![]));
+        assert_eq!(LengthPrefixed.read(&mut input).unwrap(), Some(vec![7, 8]));
+        assert_eq!(LengthPrefixed.read(&mut input).unwrap(), None);
+
+        let mut truncated = &output[4..9];
+        let err = LengthPrefixed.read(&mut truncated).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_temp_file_is_removed_on_drop() {
+        let (run, _) = TempFile::create(&env::temp_dir()).unwrap();
+        let path = run.path.clone();
+        assert!(path.exists());
+
+        drop(run);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_merge_runs_is_stable() {
+        let dir = env::temp_dir();
+        let first = spill(&["a1".to_string(), "b1".to_string()], &Lines, &dir).unwrap();
+        let second = spill(&["a2".to_string(), "b2".to_string()], &Lines, &dir).unwrap();
+
+        let mut output = Vec::new();
+        let mut by_letter = |a: &String, b: &String| a[..1].cmp(&b[..1]);
+        merge_runs(&[first, second], &Lines, &mut output, &mut by_letter).unwrap();
+        assert_eq!(output, b"a1\na2\nb1\nb2\n");
+    }
+}
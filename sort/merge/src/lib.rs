@@ -1,19 +1,178 @@
 //! # Merge
 //!
 //! `merge` provides sort capabilities using merge sort
-//! using O(n log(n)) time complexity and O(h) space complexity.
+//! using O(n log(n)) time complexity and O(n) space complexity.
+//!
+//! [`sort`] allocates a buffer for half of the slice up front. Every merge
+//! copies the shorter of the two runs into it and merges it back into the
+//! slice, galloping like [`timsort`].
+//!
+//! The operations of the sort can be observed, see [`sort_observed`].
+//!
+//! [`sort_in_place`] sorts without a buffer, using an in-place block merge,
+//! with O(1) space complexity. Its operations can be observed as well,
+//! see [`sort_in_place_observed`].
+//!
+//! It also provides an adaptive natural merge sort, see [`timsort`],
+//! which sorts nearly sorted slices in close to O(n) time.
+//!
+//! Large slices can be sorted on multiple threads, see [`par_sort`].
+//!
+//! Data which doesn't fit into memory can be sorted through temporary
+//! files, see [`external_sort`].
+//!
+//! Sorted sequences can be checked, merged lazily, combined with set
+//! operations and deduplicated, see [`is_sorted`], [`merge_sorted`],
+//! [`union`] and [`dedup_sorted`].
+//!
+//! Floats are sorted by the IEEE 754 total order, see [`sort_floats`].
+//!
+//! If the comparator panics the slice is left as a permutation of its
+//! elements, none is lost, duplicated or dropped. The buffered merges
+//! copy the elements still in the buffer back into the slice on unwinding,
+//! the parallel merges only copy into the buffer until they complete.
 
-/// Sorts the slice withing the specified interval [b, e)
-/// using recursive merge sort.
-fn internal_mergesort<T>(b: usize, e: usize, slice: &mut [T])
+pub use external::{
+    external_sort, external_sort_by, Encoding, ExternalSortOptions, LengthPrefixed, Lines,
+};
+pub use par::{par_sort, par_sort_by, par_sort_by_grain, DEFAULT_GRAIN_SIZE};
+pub use radix::{NanPlacement, TotalOrd};
+pub use sorted::{
+    dedup_sorted, dedup_sorted_by, dedup_sorted_by_key, difference, intersection, is_sorted,
+    is_sorted_by, is_sorted_by_key, merge_sorted, merge_sorted_by, symmetric_difference, union,
+    Difference, Intersection, MergeSorted, SymmetricDifference, Union,
+};
+pub use tim::{timsort, timsort_by, timsort_by_key};
+
+mod block;
+mod external;
+mod par;
+mod sorted;
+mod tim;
+
+use std::cmp::Ordering;
+use std::mem;
+use std::mem::MaybeUninit;
+
+use observe::{NoopObserver, Shifted, SortObserver};
+
+/// Subslices of at most this many elements are sorted with binary insertion
+/// sort, which unlike the sorting networks of `insertion` is stable.
+const INSERTION_THRESHOLD: usize = 16;
+
+/// Returns the index of the first element within [lo, hi) of the sorted
+/// slice which is not less than the element at index `key`.
+fn lower_bound<T, F, O>(
+    slice: &[T],
+    (mut lo, mut hi): (usize, usize),
+    key: usize,
+    compare: &mut F,
+    observer: &mut O,
+) -> usize
 where
-    T: Ord,
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        observer.on_compare(mid, key);
+        if compare(&slice[mid], &slice[key]) == Ordering::Less {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// Returns the index of the first element within [lo, hi) of the sorted
+/// slice which is greater than the element at index `key`.
+fn upper_bound<T, F, O>(
+    slice: &[T],
+    (mut lo, mut hi): (usize, usize),
+    key: usize,
+    compare: &mut F,
+    observer: &mut O,
+) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
 {
-    if e - b > 1 {
-        let mid = b + (e - b) / 2;
-        internal_mergesort(b, mid, slice);
-        internal_mergesort(mid, e, slice);
-        internal_merge(mid, b, e, slice);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        observer.on_compare(mid, key);
+        if compare(&slice[mid], &slice[key]) != Ordering::Greater {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// Rotates the elements within [b, e) of the slice `k` places to the left,
+/// reporting the rotation to the observer.
+fn rotate_left<T, O>(slice: &mut [T], (b, e): (usize, usize), k: usize, observer: &mut O)
+where
+    O: SortObserver,
+{
+    if k == 0 || k == e - b {
+        return;
+    }
+
+    observer.on_rotate(b..e, b + k);
+    slice[b..e].rotate_left(k);
+}
+
+/// Sorts the slice withing the specified interval [b, e)
+/// using bottom-up merge sort with in-place merges,
+/// reporting the operations to the observer.
+///
+/// There is no recursion, every merge is reported at the depth
+/// it would have in the recursion of a top-down merge sort.
+fn internal_mergesort<T, F, O>(
+    b: usize,
+    e: usize,
+    slice: &mut [T],
+    compare: &mut F,
+    observer: &mut O,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let mut levels = 0;
+    while INSERTION_THRESHOLD << levels < e - b {
+        levels += 1;
+    }
+
+    let mut start = b;
+    while start < e {
+        let end = e.min(start + INSERTION_THRESHOLD);
+        if levels > 0 {
+            observer.on_recurse(levels);
+        }
+        tim::observed_binary_insertion_sort(
+            &mut slice[start..end],
+            1,
+            compare,
+            &mut Shifted::new(&mut *observer, start),
+        );
+        start = end;
+    }
+
+    let mut width = INSERTION_THRESHOLD;
+    while width < e - b {
+        levels -= 1;
+        let mut start = b;
+        while start + width < e {
+            let end = e.min(start + 2 * width);
+            if levels > 0 {
+                observer.on_recurse(levels);
+            }
+            internal_merge(start + width, start, end, slice, compare, observer);
+            start = end;
+        }
+        width *= 2;
     }
 }
 
@@ -25,30 +184,97 @@ where
 /// same time the beginning of the second sublice.
 /// `e` is the end of the second subslice.
 ///
-/// The merge is done in place. Only constant memory is allocated.
-fn internal_merge<T>(mid: usize, b: usize, e: usize, slice: &mut [T])
-where
-    T: Ord,
+/// The merge is done in place using a block merge.
+/// Only constant memory is allocated.
+/// Elements of the first subslice win ties, so the merge is stable.
+/// The operations are reported to the observer.
+fn internal_merge<T, F, O>(
+    mid: usize,
+    b: usize,
+    e: usize,
+    slice: &mut [T],
+    compare: &mut F,
+    observer: &mut O,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
 {
-    let mut left_begin = b;
-    let mut left_end = mid;
-    let mut right_begin = mid;
-    let right_end = e;
-
-    loop {
-        if left_begin == left_end || right_begin == right_end {
-            break;
-        }
+    block::merge(
+        &mut slice[b..e],
+        mid - b,
+        compare,
+        &mut Shifted::new(observer, b),
+    );
+}
 
-        if slice[left_begin] <= slice[right_begin] {
-            left_begin += 1;
-        } else {
-            (&mut slice[left_begin..right_begin + 1]).rotate_right(1);
-            left_begin += 1;
-            left_end += 1;
-            right_begin += 1;
-        }
+/// Sorts the slice within the specified interval [b, e)
+/// using recursive merge sort, merging through the buffer which
+/// must have room for half of the interval.
+///
+/// Every merge copies the shorter run into the buffer and merges it
+/// back into the slice, the runs never ping-pong between the two.
+///
+/// The operations are reported to the observer, the slots of the
+/// buffer as the indices past the end of the slice.
+///
+/// # Safety
+///
+/// `buf` must be valid for writes of `(e - b) / 2` elements
+/// and must not overlap with the slice.
+unsafe fn internal_buffered_mergesort<T, F, O>(
+    slice: &mut [T],
+    (b, e): (usize, usize),
+    buf: *mut T,
+    compare: &mut F,
+    min_gallop: &mut usize,
+    observer: &mut O,
+    depth: usize,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    if e - b <= INSERTION_THRESHOLD {
+        tim::observed_binary_insertion_sort(
+            &mut slice[b..e],
+            1,
+            compare,
+            &mut Shifted::new(&mut *observer, b),
+        );
+        return;
     }
+
+    let mid = b + (e - b) / 2;
+    observer.on_recurse(depth + 1);
+    internal_buffered_mergesort(
+        slice,
+        (b, mid),
+        buf,
+        compare,
+        min_gallop,
+        observer,
+        depth + 1,
+    );
+    observer.on_recurse(depth + 1);
+    internal_buffered_mergesort(
+        slice,
+        (mid, e),
+        buf,
+        compare,
+        min_gallop,
+        observer,
+        depth + 1,
+    );
+
+    let scratch = slice.len() - b;
+    tim::merge(
+        &mut slice[b..e],
+        mid - b,
+        buf,
+        compare,
+        min_gallop,
+        &mut Shifted::new(&mut *observer, b),
+        scratch,
+    );
 }
 
 /// Sorts the slice using recursive merge sort.
@@ -66,7 +292,263 @@ pub fn sort<T>(slice: &mut [T])
 where
     T: Ord,
 {
-    internal_mergesort(0, slice.len(), slice);
+    sort_by(slice, T::cmp);
+}
+
+/// Sorts the slice using recursive merge sort with a comparator function.
+///
+/// A scratch buffer for half of the slice is allocated up front,
+/// every merge copies the shorter run into it and merges it back
+/// into the slice. Unlike the merges of [`sort_in_place_by`] this
+/// takes O(n log(n)) moves.
+///
+/// The sort is stable, equal elements keep their relative order.
+///
+/// # Examples
+///
+/// ```
+/// use merge::sort_by;
+///
+/// let mut nmbrs = vec![1,3,2,5,4];
+/// sort_by(&mut nmbrs, |a, b| b.cmp(a));
+///
+/// assert_eq!(nmbrs, vec![5,4,3,2,1]);
+/// ```
+pub fn sort_by<T, F>(slice: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    sort_by_observed(slice, compare, &mut NoopObserver);
+}
+
+/// Sorts the slice using recursive merge sort with a key extraction function.
+///
+/// # Examples
+///
+/// ```
+/// use merge::sort_by_key;
+///
+/// let mut nmbrs = vec![-4,3,-2,5,1];
+/// sort_by_key(&mut nmbrs, |n: &i32| n.abs());
+///
+/// assert_eq!(nmbrs, vec![1,-2,3,-4,5]);
+/// ```
+pub fn sort_by_key<T, K, F>(slice: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    sort_by(slice, |a, b| f(a).cmp(&f(b)));
+}
+
+/// Sorts the slice using recursive merge sort,
+/// reporting every comparison, move, rotation and recursion to the observer.
+///
+/// The moves into and out of the buffer are reported with the indices
+/// past the end of the slice, see [`SortObserver`].
+///
+/// # Examples
+///
+/// ```
+/// use merge::sort_observed;
+/// use observe::SortStats;
+///
+/// let mut nmbrs: Vec<i32> = (0..40).rev().collect();
+/// let mut stats = SortStats::default();
+/// sort_observed(&mut nmbrs, &mut stats);
+///
+/// assert_eq!(nmbrs, (0..40).collect::<Vec<_>>());
+/// assert_eq!(stats.swaps, 0);
+/// assert!(stats.moves > 0);
+/// ```
+pub fn sort_observed<T, O>(slice: &mut [T], observer: &mut O)
+where
+    T: Ord,
+    O: SortObserver,
+{
+    sort_by_observed(slice, T::cmp, observer);
+}
+
+/// Sorts the slice using recursive merge sort with a comparator function,
+/// reporting every comparison, move, rotation and recursion to the observer.
+///
+/// # Examples
+///
+/// ```
+/// use merge::sort_by_observed;
+/// use observe::Trace;
+///
+/// let input = vec![1,3,2];
+/// let mut nmbrs = input.clone();
+/// let mut trace = Trace::new();
+/// sort_by_observed(&mut nmbrs, |a, b| b.cmp(a), &mut trace);
+///
+/// assert_eq!(nmbrs, vec![3,2,1]);
+/// assert_eq!(observe::replay_by(&input, trace.events(), |a, b| b.cmp(a)), Ok(nmbrs));
+/// ```
+pub fn sort_by_observed<T, F, O>(slice: &mut [T], mut compare: F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    if mem::size_of::<T>() == 0 {
+        return;
+    }
+
+    let len = slice.len();
+    let mut buf: Vec<MaybeUninit<T>> = Vec::with_capacity(len / 2);
+    let mut min_gallop = tim::MIN_GALLOP;
+    unsafe {
+        internal_buffered_mergesort(
+            slice,
+            (0, len),
+            buf.as_mut_ptr() as *mut T,
+            &mut compare,
+            &mut min_gallop,
+            observer,
+            0,
+        );
+    }
+}
+
+/// Sorts the slice of floats by the IEEE 754 total order using merge sort,
+/// with the NaNs grouped at the given end.
+///
+/// -0.0 goes before +0.0. The sort is stable, equal floats
+/// keep their relative order.
+///
+/// # Examples
+///
+/// ```
+/// use merge::{sort_floats, NanPlacement};
+///
+/// let mut floats = vec![2.5f32, f32::NAN, 0.0, -0.0, f32::NEG_INFINITY];
+/// sort_floats(&mut floats, NanPlacement::Last);
+///
+/// assert_eq!(floats[..4], [f32::NEG_INFINITY, -0.0, 0.0, 2.5]);
+/// assert!(floats[1].is_sign_negative());
+/// assert!(floats[4].is_nan());
+/// ```
+pub fn sort_floats<F>(slice: &mut [F], nans: NanPlacement)
+where
+    F: TotalOrd,
+{
+    sort_by(slice, |a, b| a.total_cmp_with(b, nans));
+}
+
+/// Sorts the slice using bottom-up merge sort with in-place block merges.
+///
+/// Unlike [`sort`] it allocates no buffer, it uses O(1) space complexity.
+/// It still takes O(n log(n)) time, also when there are only a few unique
+/// values for the internal buffers of the block merges.
+///
+/// # Examples
+///
+/// ```
+/// use merge::sort_in_place;
+///
+/// let mut nmbrs = vec![4,3,2,5,1];
+/// sort_in_place(&mut nmbrs);
+///
+/// assert_eq!(nmbrs, vec![1,2,3,4,5]);
+/// ```
+pub fn sort_in_place<T>(slice: &mut [T])
+where
+    T: Ord,
+{
+    sort_in_place_by(slice, T::cmp);
+}
+
+/// Sorts the slice using bottom-up merge sort with in-place block merges
+/// with a comparator function.
+///
+/// The sort is stable, equal elements keep their relative order.
+///
+/// # Examples
+///
+/// ```
+/// use merge::sort_in_place_by;
+///
+/// let mut nmbrs = vec![1,3,2,5,4];
+/// sort_in_place_by(&mut nmbrs, |a, b| b.cmp(a));
+///
+/// assert_eq!(nmbrs, vec![5,4,3,2,1]);
+/// ```
+pub fn sort_in_place_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    internal_mergesort(0, slice.len(), slice, &mut compare, &mut NoopObserver);
+}
+
+/// Sorts the slice using bottom-up merge sort with in-place block merges
+/// with a key extraction function.
+///
+/// # Examples
+///
+/// ```
+/// use merge::sort_in_place_by_key;
+///
+/// let mut nmbrs = vec![-4,3,-2,5,1];
+/// sort_in_place_by_key(&mut nmbrs, |n: &i32| n.abs());
+///
+/// assert_eq!(nmbrs, vec![1,-2,3,-4,5]);
+/// ```
+pub fn sort_in_place_by_key<T, K, F>(slice: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    sort_in_place_by(slice, |a, b| f(a).cmp(&f(b)));
+}
+
+/// Sorts the slice using bottom-up merge sort with in-place block merges,
+/// reporting every comparison, swap, rotation and merge to the observer.
+///
+/// # Examples
+///
+/// ```
+/// use merge::sort_in_place_observed;
+/// use observe::SortStats;
+///
+/// let mut nmbrs = vec![4,3,2,5,1];
+/// let mut stats = SortStats::default();
+/// sort_in_place_observed(&mut nmbrs, &mut stats);
+///
+/// assert_eq!(nmbrs, vec![1,2,3,4,5]);
+/// assert_eq!(stats.swaps, 0);
+/// ```
+pub fn sort_in_place_observed<T, O>(slice: &mut [T], observer: &mut O)
+where
+    T: Ord,
+    O: SortObserver,
+{
+    sort_in_place_by_observed(slice, T::cmp, observer);
+}
+
+/// Sorts the slice using bottom-up merge sort with in-place block merges
+/// with a comparator function, reporting every comparison, swap, rotation
+/// and merge to the observer.
+///
+/// # Examples
+///
+/// ```
+/// use merge::sort_in_place_by_observed;
+/// use observe::SortStats;
+///
+/// let mut nmbrs = vec![1,2,3];
+/// let mut stats = SortStats::default();
+/// sort_in_place_by_observed(&mut nmbrs, |a, b| b.cmp(a), &mut stats);
+///
+/// assert_eq!(nmbrs, vec![3,2,1]);
+/// assert!(stats.moves > 0);
+/// ```
+pub fn sort_in_place_by_observed<T, F, O>(slice: &mut [T], mut compare: F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    internal_mergesort(0, slice.len(), slice, &mut compare, observer);
 }
 
 #[cfg(test)]
@@ -76,14 +558,21 @@ mod tests {
     #[test]
     fn test_internal_merge() {
         let mut nmbrs = vec![1, 5, 2, 3, 4];
-        internal_merge(2, 0, nmbrs.len(), &mut nmbrs);
+        internal_merge(
+            2,
+            0,
+            nmbrs.len(),
+            &mut nmbrs,
+            &mut i32::cmp,
+            &mut NoopObserver,
+        );
         assert_eq!(nmbrs, vec![1, 2, 3, 4, 5]);
     }
 
     #[test]
     fn test_internal_mergesort() {
         let mut nmbrs = vec![1, 5, 2, 3, 4];
-        internal_mergesort(0, nmbrs.len(), &mut nmbrs);
+        internal_mergesort(0, nmbrs.len(), &mut nmbrs, &mut i32::cmp, &mut NoopObserver);
         assert_eq!(nmbrs, vec![1, 2, 3, 4, 5]);
     }
 }
@@ -0,0 +1,305 @@
+//! Parallel merge sort.
+//!
+//! The slice is split in halves which are sorted on scoped threads,
+//! the sorted halves are then merged in parallel into a buffer by
+//! splitting the output at a co-rank and copied back.
+
+use std::cmp::Ordering;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::thread;
+
+use observe::NoopObserver;
+
+use super::internal_buffered_mergesort;
+use super::tim::MIN_GALLOP;
+
+/// Default number of elements below which the parallel sort
+/// falls back to the sequential merge sort.
+pub const DEFAULT_GRAIN_SIZE: usize = 1 << 14;
+
+/// Returns the number of threads the sort may split the work between.
+fn available_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Returns how many elements of `left` are among the first `k` elements
+/// of the stable merge of `left` and `right`.
+///
+/// The remaining `k - i` elements are the first elements of `right`.
+fn co_rank<T, F>(k: usize, left: &[T], right: &[T], compare: &F) -> usize
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    let mut lo = k.saturating_sub(right.len());
+    let mut hi = k.min(left.len());
+
+    while lo < hi {
+        let i = lo + (hi - lo) / 2;
+        let j = k - i;
+        // Elements of `left` win ties, so `left[i]` belongs in front
+        // if it is <= `right[j - 1]`.
+        if compare(&left[i], &right[j - 1]) != Ordering::Greater {
+            lo = i + 1;
+        } else {
+            hi = i;
+        }
+    }
+
+    lo
+}
+
+/// Stable merges `left` and `right` into `out` by copying the elements.
+///
+/// # Safety
+///
+/// `out` must be valid for writes of `left.len() + right.len()` elements
+/// and must not overlap with `left` or `right`.
+unsafe fn merge_into<T, F>(left: &[T], right: &[T], out: *mut T, compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    let (mut i, mut j, mut k) = (0, 0, 0);
+    while i < left.len() && j < right.len() {
+        if compare(&right[j], &left[i]) == Ordering::Less {
+            ptr::copy_nonoverlapping(&right[j], out.add(k), 1);
+            j += 1;
+        } else {
+            ptr::copy_nonoverlapping(&left[i], out.add(k), 1);
+            i += 1;
+        }
+        k += 1;
+    }
+
+    let rest = if i < left.len() {
+        &left[i..]
+    } else {
+        &right[j..]
+    };
+    ptr::copy_nonoverlapping(rest.as_ptr(), out.add(k), rest.len());
+}
+
+/// Merges the sorted `left` and `right` into `out`,
+/// splitting the output at its middle while there are threads left.
+///
+/// The elements are only copied, `left` and `right` are left untouched.
+fn par_merge<T, F>(
+    left: &mut [T],
+    right: &mut [T],
+    out: &mut [MaybeUninit<T>],
+    grain_size: usize,
+    threads: usize,
+    compare: &F,
+) where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    let len = left.len() + right.len();
+    if len <= grain_size || threads <= 1 {
+        unsafe { merge_into(left, right, out.as_mut_ptr() as *mut T, compare) };
+        return;
+    }
+
+    let k = len / 2;
+    let i = co_rank(k, left, right, compare);
+    let (left_lo, left_hi) = left.split_at_mut(i);
+    let (right_lo, right_hi) = right.split_at_mut(k - i);
+    let (out_lo, out_hi) = out.split_at_mut(k);
+
+    thread::scope(|s| {
+        s.spawn(|| par_merge(left_lo, right_lo, out_lo, grain_size, threads / 2, compare));
+        par_merge(
+            left_hi,
+            right_hi,
+            out_hi,
+            grain_size,
+            threads - threads / 2,
+            compare,
+        );
+    });
+}
+
+/// Copies the elements of `src` back into `dst` in parallel.
+fn par_copy_back<T>(dst: &mut [T], src: &mut [MaybeUninit<T>], threads: usize)
+where
+    T: Send,
+{
+    let chunk = dst.len().div_ceil(threads);
+    thread::scope(|s| {
+        for (dst, src) in dst.chunks_mut(chunk).zip(src.chunks_mut(chunk)) {
+            s.spawn(move || unsafe {
+                ptr::copy_nonoverlapping(src.as_ptr() as *const T, dst.as_mut_ptr(), dst.len());
+            });
+        }
+    });
+}
+
+/// Sorts the slice using parallel merge sort, `buf` is the scratch
+/// space which must be at least as long as the slice.
+///
+/// A comparator panic leaves the slice as a permutation of its elements,
+/// the merge only ever copies into the buffer and the elements are
+/// copied back once the merge has completed.
+fn par_mergesort<T, F>(
+    slice: &mut [T],
+    buf: &mut [MaybeUninit<T>],
+    grain_size: usize,
+    threads: usize,
+    compare: &F,
+) where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    if slice.len() <= grain_size || threads <= 1 {
+        let mut min_gallop = MIN_GALLOP;
+        let len = slice.len();
+        unsafe {
+            internal_buffered_mergesort(
+                slice,
+                (0, len),
+                buf.as_mut_ptr() as *mut T,
+                &mut |a, b| compare(a, b),
+                &mut min_gallop,
+                &mut NoopObserver,
+                0,
+            );
+        }
+        return;
+    }
+
+    let len = slice.len();
+    let mid = len / 2;
+    let (left, right) = slice.split_at_mut(mid);
+    let (left_buf, right_buf) = buf.split_at_mut(mid);
+    thread::scope(|s| {
+        s.spawn(|| par_mergesort(left, left_buf, grain_size, threads / 2, compare));
+        par_mergesort(right, right_buf, grain_size, threads - threads / 2, compare);
+    });
+
+    if compare(&right[0], &left[mid - 1]) != Ordering::Less {
+        return;
+    }
+
+    let buf = &mut buf[..len];
+    par_merge(left, right, buf, grain_size, threads, compare);
+    par_copy_back(slice, buf, threads);
+}
+
+/// Sorts the slice using parallel merge sort.
+///
+/// # Examples
+///
+/// ```
+/// use merge::par_sort;
+///
+/// let mut nmbrs = vec![4,3,2,5,1];
+/// par_sort(&mut nmbrs);
+///
+/// assert_eq!(nmbrs, vec![1,2,3,4,5]);
+/// ```
+pub fn par_sort<T>(slice: &mut [T])
+where
+    T: Ord + Send,
+{
+    par_sort_by(slice, T::cmp);
+}
+
+/// Sorts the slice using parallel merge sort with a comparator function.
+///
+/// Slices of at most [`DEFAULT_GRAIN_SIZE`] elements are sorted
+/// sequentially, see [`par_sort_by_grain`] for choosing the grain size.
+///
+/// # Examples
+///
+/// ```
+/// use merge::par_sort_by;
+///
+/// let mut nmbrs = vec![1,3,2,5,4];
+/// par_sort_by(&mut nmbrs, |a, b| b.cmp(a));
+///
+/// assert_eq!(nmbrs, vec![5,4,3,2,1]);
+/// ```
+pub fn par_sort_by<T, F>(slice: &mut [T], compare: F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    par_sort_by_grain(slice, DEFAULT_GRAIN_SIZE, compare);
+}
+
+/// Sorts the slice using parallel merge sort with a comparator function.
+///
+/// The slice is split recursively between threads until the parts have
+/// at most `grain_size` elements, these are sorted with the sequential
+/// merge sort. A buffer of the length of the slice is allocated.
+///
+/// The sort is stable, equal elements keep their relative order.
+///
+/// # Examples
+///
+/// ```
+/// use merge::par_sort_by_grain;
+///
+/// let mut nmbrs: Vec<i32> = (0..1000).rev().collect();
+/// par_sort_by_grain(&mut nmbrs, 64, |a, b| a.cmp(b));
+///
+/// assert_eq!(nmbrs, (0..1000).collect::<Vec<_>>());
+/// ```
+pub fn par_sort_by_grain<T, F>(slice: &mut [T], grain_size: usize, compare: F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    if std::mem::size_of::<T>() == 0 {
+        return;
+    }
+
+    let mut buf: Vec<MaybeUninit<T>> = Vec::with_capacity(slice.len());
+    // Safety: `MaybeUninit` requires no initialization.
+    unsafe { buf.set_len(slice.len()) };
+    par_mergesort(
+        slice,
+        &mut buf,
+        grain_size.max(1),
+        available_threads(),
+        &compare,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_co_rank() {
+        let left = vec![1, 3, 3, 5];
+        let right = vec![2, 3, 4];
+        assert_eq!(co_rank(0, &left, &right, &i32::cmp), 0);
+        assert_eq!(co_rank(2, &left, &right, &i32::cmp), 1);
+        assert_eq!(co_rank(4, &left, &right, &i32::cmp), 3);
+        assert_eq!(co_rank(7, &left, &right, &i32::cmp), 4);
+    }
+
+    #[test]
+    fn test_par_merge() {
+        let mut left: Vec<i32> = (0..100).map(|i| i * 2).collect();
+        let mut right: Vec<i32> = (0..100).map(|i| i * 2 + 1).collect();
+        let mut out: Vec<MaybeUninit<i32>> = (0..200).map(|_| MaybeUninit::uninit()).collect();
+        par_merge(&mut left, &mut right, &mut out, 8, 4, &i32::cmp);
+
+        let out: Vec<i32> = out.iter().map(|x| unsafe { x.assume_init() }).collect();
+        assert_eq!(out, (0..200).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_par_mergesort() {
+        let mut pairs: Vec<(usize, usize)> = (0..1000).map(|i| ((i * 7919) % 13, i)).collect();
+        let mut buf: Vec<MaybeUninit<(usize, usize)>> =
+            (0..1000).map(|_| MaybeUninit::uninit()).collect();
+        par_mergesort(&mut pairs, &mut buf, 16, 8, &|a, b| a.0.cmp(&b.0));
+
+        let mut want = pairs.clone();
+        want.sort();
+        assert_eq!(pairs, want);
+    }
+}
@@ -0,0 +1,500 @@
+//! Working with sorted sequences.
+//!
+//! Checking whether a slice is sorted, lazily merging any number of
+//! sorted iterators, set operations on sorted slices and removing
+//! the duplicates of a sorted slice.
+
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::slice;
+
+/// Returns whether the slice is sorted.
+///
+/// # Examples
+///
+/// ```
+/// use merge::is_sorted;
+///
+/// assert!(is_sorted(&[1,2,2,3]));
+/// assert!(!is_sorted(&[1,3,2]));
+/// ```
+pub fn is_sorted<T>(slice: &[T]) -> bool
+where
+    T: Ord,
+{
+    is_sorted_by(slice, T::cmp)
+}
+
+/// Returns whether the slice is sorted with respect to the comparator function.
+///
+/// # Examples
+///
+/// ```
+/// use merge::is_sorted_by;
+///
+/// assert!(is_sorted_by(&[3,2,2,1], |a, b| b.cmp(a)));
+/// ```
+pub fn is_sorted_by<T, F>(slice: &[T], mut compare: F) -> bool
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    slice
+        .windows(2)
+        .all(|w| compare(&w[1], &w[0]) != Ordering::Less)
+}
+
+/// Returns whether the slice is sorted by the keys of the key extraction function.
+///
+/// # Examples
+///
+/// ```
+/// use merge::is_sorted_by_key;
+///
+/// assert!(is_sorted_by_key(&[-1,2,-3], |n: &i32| n.abs()));
+/// ```
+pub fn is_sorted_by_key<T, K, F>(slice: &[T], mut f: F) -> bool
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    is_sorted_by(slice, |a, b| f(a).cmp(&f(b)))
+}
+
+/// The comparator of [`merge_sorted`], comparing with [`Ord::cmp`].
+type OrdCompare<T> = fn(&T, &T) -> Ordering;
+
+/// A lazy k-way merge of sorted iterators, see [`merge_sorted`].
+///
+/// The next element of every iterator is kept in a binary heap,
+/// so every element is yielded in O(log(k)) time.
+pub struct MergeSorted<I, F>
+where
+    I: Iterator,
+{
+    iters: Vec<I>,
+    /// The next element of every unfinished iterator with its index.
+    heap: Vec<(I::Item, usize)>,
+    compare: F,
+}
+
+/// Compares the heap entries of [`MergeSorted`] such that the max-heap
+/// has the smallest element of the earliest iterator at its root.
+fn heap_order<T, F>(compare: &mut F) -> impl FnMut(&(T, usize), &(T, usize)) -> Ordering + '_
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    move |a, b| compare(&b.0, &a.0).then(b.1.cmp(&a.1))
+}
+
+impl<I, F> Iterator for MergeSorted<I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        if self.heap.is_empty() {
+            return None;
+        }
+
+        let index = self.heap[0].1;
+        let elem = match self.iters[index].next() {
+            Some(next) => std::mem::replace(&mut self.heap[0].0, next),
+            None => self.heap.swap_remove(0).0,
+        };
+        heap::sift_down_by(&mut self.heap, 0, heap_order(&mut self.compare));
+
+        Some(elem)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iters.iter().map(Iterator::size_hint).fold(
+            (self.heap.len(), Some(self.heap.len())),
+            |acc, hint| {
+                let lo = acc.0.saturating_add(hint.0);
+                let hi = acc.1.and_then(|hi| hint.1.and_then(|h| hi.checked_add(h)));
+                (lo, hi)
+            },
+        )
+    }
+}
+
+/// Lazily merges the sorted iterators into one sorted iterator.
+///
+/// Equal elements are yielded in the order of the iterators they come
+/// from, elements of the same iterator keep their order.
+///
+/// # Examples
+///
+/// ```
+/// use merge::merge_sorted;
+///
+/// let merged: Vec<i32> = merge_sorted(vec![vec![1,4,7], vec![2,5], vec![3,6,9]]).collect();
+///
+/// assert_eq!(merged, vec![1,2,3,4,5,6,7,9]);
+/// ```
+pub fn merge_sorted<I, J, T>(iters: I) -> MergeSorted<J::IntoIter, OrdCompare<T>>
+where
+    I: IntoIterator<Item = J>,
+    J: IntoIterator<Item = T>,
+    T: Ord,
+{
+    merge_sorted_by(iters, Ord::cmp as OrdCompare<T>)
+}
+
+/// Lazily merges the iterators, sorted with respect to the comparator
+/// function, into one sorted iterator.
+///
+/// See [`merge_sorted`].
+///
+/// # Examples
+///
+/// ```
+/// use merge::merge_sorted_by;
+///
+/// let logs = vec![vec![(1, "a"), (3, "a")], vec![(1, "b"), (2, "b")]];
+/// let merged: Vec<_> = merge_sorted_by(logs, |x, y| x.0.cmp(&y.0)).collect();
+///
+/// assert_eq!(merged, vec![(1, "a"), (1, "b"), (2, "b"), (3, "a")]);
+/// ```
+pub fn merge_sorted_by<I, J, T, F>(iters: I, mut compare: F) -> MergeSorted<J::IntoIter, F>
+where
+    I: IntoIterator<Item = J>,
+    J: IntoIterator<Item = T>,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut iters: Vec<_> = iters.into_iter().map(IntoIterator::into_iter).collect();
+    let mut heap = Vec::with_capacity(iters.len());
+    for (index, iter) in iters.iter_mut().enumerate() {
+        if let Some(elem) = iter.next() {
+            heap.push((elem, index));
+        }
+    }
+    heap::heapify_by(&mut heap, heap_order(&mut compare));
+
+    MergeSorted {
+        iters,
+        heap,
+        compare,
+    }
+}
+
+/// Compares the next elements of the iterators, `None` if either is exhausted.
+fn compare_heads<'a, T>(
+    a: &mut Peekable<slice::Iter<'a, T>>,
+    b: &mut Peekable<slice::Iter<'a, T>>,
+) -> Option<Ordering>
+where
+    T: Ord,
+{
+    Some(a.peek()?.cmp(b.peek()?))
+}
+
+/// The elements of either sorted slice, see [`union`].
+#[derive(Debug, Clone)]
+pub struct Union<'a, T> {
+    a: Peekable<slice::Iter<'a, T>>,
+    b: Peekable<slice::Iter<'a, T>>,
+}
+
+impl<'a, T> Iterator for Union<'a, T>
+where
+    T: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        match compare_heads(&mut self.a, &mut self.b) {
+            Some(Ordering::Less) => self.a.next(),
+            Some(Ordering::Greater) => self.b.next(),
+            Some(Ordering::Equal) => {
+                self.b.next();
+                self.a.next()
+            }
+            None => self.a.next().or_else(|| self.b.next()),
+        }
+    }
+}
+
+/// The elements of both sorted slices, see [`intersection`].
+#[derive(Debug, Clone)]
+pub struct Intersection<'a, T> {
+    a: Peekable<slice::Iter<'a, T>>,
+    b: Peekable<slice::Iter<'a, T>>,
+}
+
+impl<'a, T> Iterator for Intersection<'a, T>
+where
+    T: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            match compare_heads(&mut self.a, &mut self.b)? {
+                Ordering::Less => self.a.next(),
+                Ordering::Greater => self.b.next(),
+                Ordering::Equal => {
+                    self.b.next();
+                    return self.a.next();
+                }
+            };
+        }
+    }
+}
+
+/// The elements of the first sorted slice which are not in the second one,
+/// see [`difference`].
+#[derive(Debug, Clone)]
+pub struct Difference<'a, T> {
+    a: Peekable<slice::Iter<'a, T>>,
+    b: Peekable<slice::Iter<'a, T>>,
+}
+
+impl<'a, T> Iterator for Difference<'a, T>
+where
+    T: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            match compare_heads(&mut self.a, &mut self.b) {
+                Some(Ordering::Less) | None => return self.a.next(),
+                Some(Ordering::Greater) => self.b.next(),
+                Some(Ordering::Equal) => {
+                    self.b.next();
+                    self.a.next()
+                }
+            };
+        }
+    }
+}
+
+/// The elements of exactly one of the sorted slices,
+/// see [`symmetric_difference`].
+#[derive(Debug, Clone)]
+pub struct SymmetricDifference<'a, T> {
+    a: Peekable<slice::Iter<'a, T>>,
+    b: Peekable<slice::Iter<'a, T>>,
+}
+
+impl<'a, T> Iterator for SymmetricDifference<'a, T>
+where
+    T: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            match compare_heads(&mut self.a, &mut self.b) {
+                Some(Ordering::Less) => return self.a.next(),
+                Some(Ordering::Greater) => return self.b.next(),
+                Some(Ordering::Equal) => {
+                    self.a.next();
+                    self.b.next();
+                }
+                None => return self.a.next().or_else(|| self.b.next()),
+            }
+        }
+    }
+}
+
+/// Returns the elements of either sorted slice, in sorted order.
+///
+/// The slices are treated as multisets, an element occurring `m` times
+/// in `a` and `n` times in `b` is yielded `max(m, n)` times.
+/// Elements equal in both slices are yielded from `a`.
+///
+/// # Examples
+///
+/// ```
+/// use merge::union;
+///
+/// let all: Vec<&i32> = union(&[1,2,2,5], &[2,3,5]).collect();
+///
+/// assert_eq!(all, vec![&1,&2,&2,&3,&5]);
+/// ```
+pub fn union<'a, T>(a: &'a [T], b: &'a [T]) -> Union<'a, T>
+where
+    T: Ord,
+{
+    Union {
+        a: a.iter().peekable(),
+        b: b.iter().peekable(),
+    }
+}
+
+/// Returns the elements of both sorted slices, in sorted order.
+///
+/// An element occurring `m` times in `a` and `n` times in `b`
+/// is yielded `min(m, n)` times, from `a`.
+///
+/// # Examples
+///
+/// ```
+/// use merge::intersection;
+///
+/// let common: Vec<&i32> = intersection(&[1,2,2,5], &[2,3,5]).collect();
+///
+/// assert_eq!(common, vec![&2,&5]);
+/// ```
+pub fn intersection<'a, T>(a: &'a [T], b: &'a [T]) -> Intersection<'a, T>
+where
+    T: Ord,
+{
+    Intersection {
+        a: a.iter().peekable(),
+        b: b.iter().peekable(),
+    }
+}
+
+/// Returns the elements of the sorted slice `a` which are not in `b`, in sorted order.
+///
+/// An element occurring `m` times in `a` and `n` times in `b`
+/// is yielded `m - n` times, if `m > n`.
+///
+/// # Examples
+///
+/// ```
+/// use merge::difference;
+///
+/// let only_a: Vec<&i32> = difference(&[1,2,2,5], &[2,3,5]).collect();
+///
+/// assert_eq!(only_a, vec![&1,&2]);
+/// ```
+pub fn difference<'a, T>(a: &'a [T], b: &'a [T]) -> Difference<'a, T>
+where
+    T: Ord,
+{
+    Difference {
+        a: a.iter().peekable(),
+        b: b.iter().peekable(),
+    }
+}
+
+/// Returns the elements of exactly one of the sorted slices, in sorted order.
+///
+/// An element occurring `m` times in `a` and `n` times in `b`
+/// is yielded `|m - n|` times.
+///
+/// # Examples
+///
+/// ```
+/// use merge::symmetric_difference;
+///
+/// let either: Vec<&i32> = symmetric_difference(&[1,2,2,5], &[2,3,5]).collect();
+///
+/// assert_eq!(either, vec![&1,&2,&3]);
+/// ```
+pub fn symmetric_difference<'a, T>(a: &'a [T], b: &'a [T]) -> SymmetricDifference<'a, T>
+where
+    T: Ord,
+{
+    SymmetricDifference {
+        a: a.iter().peekable(),
+        b: b.iter().peekable(),
+    }
+}
+
+/// Moves the first of every run of equal elements of the sorted slice
+/// to the front of it and returns how many there are.
+///
+/// The unique elements keep their order, the order of
+/// the duplicates after them is unspecified.
+///
+/// # Examples
+///
+/// ```
+/// use merge::dedup_sorted;
+///
+/// let mut nmbrs = vec![1,1,2,3,3,3,4];
+/// let len = dedup_sorted(&mut nmbrs);
+///
+/// assert_eq!(nmbrs[..len], [1,2,3,4]);
+/// ```
+pub fn dedup_sorted<T>(slice: &mut [T]) -> usize
+where
+    T: Ord,
+{
+    dedup_sorted_by(slice, T::cmp)
+}
+
+/// Moves the first of every run of equal elements, with respect to the
+/// comparator function, to the front of the sorted slice and returns
+/// how many there are.
+///
+/// See [`dedup_sorted`].
+///
+/// # Examples
+///
+/// ```
+/// use merge::dedup_sorted_by;
+///
+/// let mut words = vec!["a", "A", "b", "B", "c"];
+/// let len = dedup_sorted_by(&mut words, |a, b| a.to_lowercase().cmp(&b.to_lowercase()));
+///
+/// assert_eq!(words[..len], ["a", "b", "c"]);
+/// ```
+pub fn dedup_sorted_by<T, F>(slice: &mut [T], mut compare: F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if slice.is_empty() {
+        return 0;
+    }
+
+    // [0, len) holds the unique elements found so far.
+    let mut len = 1;
+    for curr in 1..slice.len() {
+        if compare(&slice[curr], &slice[len - 1]) != Ordering::Equal {
+            slice.swap(len, curr);
+            len += 1;
+        }
+    }
+
+    len
+}
+
+/// Moves the first of every run of elements with equal keys
+/// to the front of the sorted slice and returns how many there are.
+///
+/// # Examples
+///
+/// ```
+/// use merge::dedup_sorted_by_key;
+///
+/// let mut pairs = vec![(1, 'a'), (1, 'b'), (2, 'c')];
+/// let len = dedup_sorted_by_key(&mut pairs, |p| p.0);
+///
+/// assert_eq!(pairs[..len], [(1, 'a'), (2, 'c')]);
+/// ```
+pub fn dedup_sorted_by_key<T, K, F>(slice: &mut [T], mut f: F) -> usize
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    dedup_sorted_by(slice, |a, b| f(a).cmp(&f(b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_heads() {
+        let (a, b) = ([1, 2], [2]);
+        let (mut a, mut b) = (a.iter().peekable(), b.iter().peekable());
+        assert_eq!(compare_heads(&mut a, &mut b), Some(Ordering::Less));
+        b.next();
+        assert_eq!(compare_heads(&mut a, &mut b), None);
+    }
+
+    #[test]
+    fn test_merge_sorted_size_hint() {
+        let merged = merge_sorted(vec![vec![1, 2], vec![], vec![3]]);
+        assert_eq!(merged.size_hint(), (3, Some(3)));
+        assert_eq!(merged.count(), 3);
+    }
+}
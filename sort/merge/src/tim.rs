@@ -0,0 +1,750 @@
+//! Adaptive natural merge sort in the style of Timsort.
+//!
+//! The slice is split into natural runs, strictly descending runs are
+//! reversed and short runs are extended with binary insertion sort.
+//! Runs are merged using a temporary buffer of the length of the shorter
+//! run, switching to galloping when one run keeps winning.
+
+use std::cmp::Ordering;
+use std::mem;
+use std::mem::MaybeUninit;
+use std::ptr;
+
+use observe::{NoopObserver, Shifted, SortObserver};
+
+use super::{rotate_left, upper_bound};
+
+/// Slices shorter than this are a single run sorted with binary insertion sort.
+const MIN_MERGE: usize = 64;
+
+/// Number of consecutive wins of one run after which the merge starts galloping.
+pub(crate) const MIN_GALLOP: usize = 7;
+
+/// A sorted run of the slice.
+#[derive(Debug, Clone, Copy)]
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+/// Returns the minimum run length for a slice of length `n`.
+///
+/// Chosen such that `n / min_run` is a power of two or slightly less,
+/// which keeps the merges balanced.
+fn min_run(mut n: usize) -> usize {
+    let mut r = 0;
+    while n >= MIN_MERGE {
+        r |= n & 1;
+        n >>= 1;
+    }
+    n + r
+}
+
+/// Returns the length of the run at the beginning of the slice.
+///
+/// A strictly descending run is reversed, so the run is always ascending.
+/// Only strictly descending runs are reversed to keep the sort stable.
+fn count_run<T, F>(slice: &mut [T], compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if slice.len() < 2 {
+        return slice.len();
+    }
+
+    let mut end = 2;
+    if compare(&slice[1], &slice[0]) == Ordering::Less {
+        while end < slice.len() && compare(&slice[end], &slice[end - 1]) == Ordering::Less {
+            end += 1;
+        }
+        slice[..end].reverse();
+    } else {
+        while end < slice.len() && compare(&slice[end], &slice[end - 1]) != Ordering::Less {
+            end += 1;
+        }
+    }
+
+    end
+}
+
+/// Sorts the slice using binary insertion sort,
+/// the first `sorted` elements must already be sorted.
+pub(crate) fn binary_insertion_sort<T, F>(slice: &mut [T], sorted: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    observed_binary_insertion_sort(slice, sorted, compare, &mut NoopObserver);
+}
+
+/// Sorts the slice using binary insertion sort like [`binary_insertion_sort`],
+/// reporting the operations to the observer.
+pub(crate) fn observed_binary_insertion_sort<T, F, O>(
+    slice: &mut [T],
+    sorted: usize,
+    compare: &mut F,
+    observer: &mut O,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    for i in sorted.max(1)..slice.len() {
+        let pos = upper_bound(slice, (0, i), i, compare, observer);
+        rotate_left(slice, (pos, i + 1), i - pos, observer);
+    }
+}
+
+/// Returns the number of leading elements of `run` for which `is_before` holds.
+///
+/// `is_before` must hold for a prefix of the run and for nothing after it.
+/// The prefix end is searched exponentially, either from the start or from
+/// the end of the run, and then pinned down with binary search.
+fn gallop<T, P>(run: &[T], mut is_before: P, from_end: bool) -> usize
+where
+    P: FnMut(&T) -> bool,
+{
+    let (mut lo, mut hi) = (0, run.len());
+
+    if from_end {
+        let mut dist = 1;
+        while dist <= run.len() {
+            let i = run.len() - dist;
+            if is_before(&run[i]) {
+                lo = i + 1;
+                break;
+            }
+            hi = i;
+            dist *= 2;
+        }
+    } else {
+        let mut i = 0;
+        while i < run.len() {
+            if !is_before(&run[i]) {
+                hi = i;
+                break;
+            }
+            lo = i + 1;
+            i = 2 * i + 1;
+        }
+    }
+
+    lo + run[lo..hi].partition_point(is_before)
+}
+
+/// Copies the elements left in the buffer, [src, end), to `dest`
+/// when dropped.
+///
+/// While merging, the elements of the slice which were moved to the buffer
+/// leave a gap in the slice, and `dest` always points at the start of it.
+/// Should the comparator panic, dropping the hole fills the gap so
+/// that the slice ends up holding every element exactly once.
+struct MergeHole<T> {
+    src: *mut T,
+    end: *mut T,
+    dest: *mut T,
+}
+
+impl<T> Drop for MergeHole<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let len = self.end.offset_from(self.src) as usize;
+            ptr::copy_nonoverlapping(self.src, self.dest, len);
+        }
+    }
+}
+
+/// Turns the pointers of a merge into the indices reported to the observer.
+struct Positions<T> {
+    /// The start of the slice being merged.
+    slice: *const T,
+    /// The start of the buffer.
+    buf: *const T,
+    /// The index the first slot of the buffer is reported as.
+    scratch: usize,
+}
+
+impl<T> Positions<T> {
+    /// Returns the index of the element of the slice at `p`.
+    ///
+    /// # Safety
+    ///
+    /// `p` must point into the slice.
+    unsafe fn index(&self, p: *const T) -> usize {
+        p.offset_from(self.slice) as usize
+    }
+
+    /// Returns the index of the buffer slot at `p`.
+    ///
+    /// # Safety
+    ///
+    /// `p` must point into the buffer.
+    unsafe fn slot(&self, p: *const T) -> usize {
+        self.scratch + p.offset_from(self.buf) as usize
+    }
+}
+
+/// Reports the move of the `count` elements from index `from` on
+/// to index `to` on, in the order a copy between them moves them.
+fn report_moves<O>(observer: &mut O, from: usize, to: usize, count: usize)
+where
+    O: SortObserver,
+{
+    if to <= from {
+        for k in 0..count {
+            observer.on_move(from + k, to + k);
+        }
+    } else {
+        for k in (0..count).rev() {
+            observer.on_move(from + k, to + k);
+        }
+    }
+}
+
+/// Merges the sorted runs [0, mid) and [mid, len) of the slice using the buffer,
+/// reporting the operations to the observer.
+///
+/// The shorter run is moved to the buffer, which must have
+/// room for at least `min(mid, len - mid)` elements. Its slots
+/// are reported as the indices from `scratch` on.
+///
+/// # Safety
+///
+/// `buf` must be valid for writes of `min(mid, len - mid)` elements
+/// and must not overlap with the slice.
+pub(crate) unsafe fn merge<T, F, O>(
+    slice: &mut [T],
+    mid: usize,
+    buf: *mut T,
+    compare: &mut F,
+    min_gallop: &mut usize,
+    observer: &mut O,
+    scratch: usize,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    // Elements of the first run which are <= the first element of the
+    // second run and elements of the second run which are >= the last
+    // element of the first run are already in place.
+    let skip = gallop(
+        &slice[..mid],
+        |x| {
+            observer.on_compare(index_of(slice, x), mid);
+            compare(x, &slice[mid]) != Ordering::Greater
+        },
+        false,
+    );
+    let slice = &mut slice[skip..];
+    let mid = mid - skip;
+    if mid == 0 {
+        return;
+    }
+
+    let keep = gallop(
+        &slice[mid..],
+        |x| {
+            observer.on_compare(skip + index_of(slice, x), skip + mid - 1);
+            compare(x, &slice[mid - 1]) == Ordering::Less
+        },
+        true,
+    );
+    let slice = &mut slice[..mid + keep];
+    if keep == 0 {
+        return;
+    }
+
+    let positions = Positions {
+        slice: slice.as_ptr(),
+        buf,
+        scratch: scratch - skip,
+    };
+    let observer = &mut Shifted::new(observer, skip);
+    if mid <= keep {
+        merge_lo(slice, mid, buf, compare, min_gallop, observer, &positions);
+    } else {
+        merge_hi(slice, mid, buf, compare, min_gallop, observer, &positions);
+    }
+}
+
+/// Returns the index of the element within the slice.
+fn index_of<T>(slice: &[T], elem: &T) -> usize {
+    // SAFETY: the element is one of the slice.
+    unsafe { (elem as *const T).offset_from(slice.as_ptr()) as usize }
+}
+
+/// Merges the runs [0, mid) and [mid, len) from the left,
+/// with the first run moved to the buffer.
+///
+/// # Safety
+///
+/// See [`merge`], the buffer must have room for `mid` elements.
+unsafe fn merge_lo<T, F, O>(
+    slice: &mut [T],
+    mid: usize,
+    buf: *mut T,
+    compare: &mut F,
+    min_gallop: &mut usize,
+    observer: &mut O,
+    at: &Positions<T>,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let len = slice.len();
+    let v = slice.as_mut_ptr();
+
+    report_moves(observer, 0, at.scratch, mid);
+    ptr::copy_nonoverlapping(v, buf, mid);
+    let mut hole = MergeHole {
+        src: buf,
+        end: buf.add(mid),
+        dest: v,
+    };
+
+    // The gap [hole.dest, right) is as long as [hole.src, hole.end).
+    let mut right = v.add(mid);
+    let right_end = v.add(len);
+
+    'merge: loop {
+        let mut left_wins = 0;
+        let mut right_wins = 0;
+
+        while left_wins.max(right_wins) < *min_gallop {
+            observer.on_compare(at.index(right), at.slot(hole.src));
+            if compare(&*right, &*hole.src) == Ordering::Less {
+                report_moves(observer, at.index(right), at.index(hole.dest), 1);
+                ptr::copy_nonoverlapping(right, hole.dest, 1);
+                right = right.add(1);
+                right_wins += 1;
+                left_wins = 0;
+            } else {
+                report_moves(observer, at.slot(hole.src), at.index(hole.dest), 1);
+                ptr::copy_nonoverlapping(hole.src, hole.dest, 1);
+                hole.src = hole.src.add(1);
+                left_wins += 1;
+                right_wins = 0;
+            }
+            hole.dest = hole.dest.add(1);
+
+            if hole.src == hole.end || right == right_end {
+                break 'merge;
+            }
+        }
+
+        loop {
+            let run = raw_slice(hole.src, hole.end);
+            let left_count = gallop(
+                run,
+                |x| {
+                    observer.on_compare(at.slot(x), at.index(right));
+                    compare(x, &*right) != Ordering::Greater
+                },
+                false,
+            );
+            report_moves(observer, at.slot(hole.src), at.index(hole.dest), left_count);
+            ptr::copy_nonoverlapping(hole.src, hole.dest, left_count);
+            hole.src = hole.src.add(left_count);
+            hole.dest = hole.dest.add(left_count);
+            if hole.src == hole.end {
+                break 'merge;
+            }
+
+            report_moves(observer, at.index(right), at.index(hole.dest), 1);
+            ptr::copy_nonoverlapping(right, hole.dest, 1);
+            right = right.add(1);
+            hole.dest = hole.dest.add(1);
+            if right == right_end {
+                break 'merge;
+            }
+
+            let run = raw_slice(right, right_end);
+            let right_count = gallop(
+                run,
+                |x| {
+                    observer.on_compare(at.index(x), at.slot(hole.src));
+                    compare(x, &*hole.src) == Ordering::Less
+                },
+                false,
+            );
+            report_moves(observer, at.index(right), at.index(hole.dest), right_count);
+            ptr::copy(right, hole.dest, right_count);
+            right = right.add(right_count);
+            hole.dest = hole.dest.add(right_count);
+            if right == right_end {
+                break 'merge;
+            }
+
+            report_moves(observer, at.slot(hole.src), at.index(hole.dest), 1);
+            ptr::copy_nonoverlapping(hole.src, hole.dest, 1);
+            hole.src = hole.src.add(1);
+            hole.dest = hole.dest.add(1);
+            if hole.src == hole.end {
+                break 'merge;
+            }
+
+            *min_gallop = min_gallop.saturating_sub(1);
+            if left_count < MIN_GALLOP && right_count < MIN_GALLOP {
+                break;
+            }
+        }
+
+        *min_gallop += 2;
+    }
+
+    *min_gallop = (*min_gallop).max(1);
+
+    // Dropping the hole copies what is left of the first run into the gap.
+    let rest = hole.end.offset_from(hole.src) as usize;
+    report_moves(observer, at.slot(hole.src), at.index(hole.dest), rest);
+}
+
+/// Merges the runs [0, mid) and [mid, len) from the right,
+/// with the second run moved to the buffer.
+///
+/// # Safety
+///
+/// See [`merge`], the buffer must have room for `len - mid` elements.
+unsafe fn merge_hi<T, F, O>(
+    slice: &mut [T],
+    mid: usize,
+    buf: *mut T,
+    compare: &mut F,
+    min_gallop: &mut usize,
+    observer: &mut O,
+    at: &Positions<T>,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let len = slice.len();
+    let v = slice.as_mut_ptr();
+
+    report_moves(observer, mid, at.scratch, len - mid);
+    ptr::copy_nonoverlapping(v.add(mid), buf, len - mid);
+    let mut hole = MergeHole {
+        src: buf,
+        end: buf.add(len - mid),
+        dest: v.add(mid),
+    };
+
+    // The gap [hole.dest, out) is as long as [hole.src, hole.end).
+    let left_start = v;
+    let mut out = v.add(len);
+
+    'merge: loop {
+        let mut left_wins = 0;
+        let mut right_wins = 0;
+
+        while left_wins.max(right_wins) < *min_gallop {
+            let left = hole.dest.sub(1);
+            let right = hole.end.sub(1);
+            out = out.sub(1);
+            observer.on_compare(at.slot(right), at.index(left));
+            if compare(&*right, &*left) == Ordering::Less {
+                report_moves(observer, at.index(left), at.index(out), 1);
+                ptr::copy_nonoverlapping(left, out, 1);
+                hole.dest = left;
+                left_wins += 1;
+                right_wins = 0;
+            } else {
+                report_moves(observer, at.slot(right), at.index(out), 1);
+                ptr::copy_nonoverlapping(right, out, 1);
+                hole.end = right;
+                right_wins += 1;
+                left_wins = 0;
+            }
+
+            if hole.dest == left_start || hole.src == hole.end {
+                break 'merge;
+            }
+        }
+
+        loop {
+            let run = raw_slice(left_start, hole.dest);
+            let last = hole.end.sub(1);
+            let left_count = run.len()
+                - gallop(
+                    run,
+                    |x| {
+                        observer.on_compare(at.index(x), at.slot(last));
+                        compare(x, &*last) != Ordering::Greater
+                    },
+                    true,
+                );
+            out = out.sub(left_count);
+            hole.dest = hole.dest.sub(left_count);
+            report_moves(observer, at.index(hole.dest), at.index(out), left_count);
+            ptr::copy(hole.dest, out, left_count);
+            if hole.dest == left_start {
+                break 'merge;
+            }
+
+            out = out.sub(1);
+            hole.end = hole.end.sub(1);
+            report_moves(observer, at.slot(hole.end), at.index(out), 1);
+            ptr::copy_nonoverlapping(hole.end, out, 1);
+            if hole.src == hole.end {
+                break 'merge;
+            }
+
+            let run = raw_slice(hole.src, hole.end);
+            let last = hole.dest.sub(1);
+            let right_count = run.len()
+                - gallop(
+                    run,
+                    |x| {
+                        observer.on_compare(at.slot(x), at.index(last));
+                        compare(x, &*last) == Ordering::Less
+                    },
+                    true,
+                );
+            out = out.sub(right_count);
+            hole.end = hole.end.sub(right_count);
+            report_moves(observer, at.slot(hole.end), at.index(out), right_count);
+            ptr::copy_nonoverlapping(hole.end, out, right_count);
+            if hole.src == hole.end {
+                break 'merge;
+            }
+
+            out = out.sub(1);
+            hole.dest = hole.dest.sub(1);
+            report_moves(observer, at.index(hole.dest), at.index(out), 1);
+            ptr::copy_nonoverlapping(hole.dest, out, 1);
+            if hole.dest == left_start {
+                break 'merge;
+            }
+
+            *min_gallop = min_gallop.saturating_sub(1);
+            if left_count < MIN_GALLOP && right_count < MIN_GALLOP {
+                break;
+            }
+        }
+
+        *min_gallop += 2;
+    }
+
+    *min_gallop = (*min_gallop).max(1);
+
+    // Dropping the hole copies what is left of the second run into the gap.
+    let rest = hole.end.offset_from(hole.src) as usize;
+    report_moves(observer, at.slot(hole.src), at.index(hole.dest), rest);
+}
+
+/// Returns the slice [start, end).
+///
+/// # Safety
+///
+/// The range must be initialized and not be written to
+/// while the returned slice is alive.
+unsafe fn raw_slice<'a, T>(start: *mut T, end: *mut T) -> &'a [T] {
+    std::slice::from_raw_parts(start, end.offset_from(start) as usize)
+}
+
+/// Returns the index of the run to merge with the one after it,
+/// if the run stack breaks the Timsort invariants.
+///
+/// If `force` is set the runs are merged regardless of the invariants.
+fn collapse(runs: &[Run], force: bool) -> Option<usize> {
+    let n = runs.len();
+    if n >= 2
+        && (force
+            || runs[n - 2].len <= runs[n - 1].len
+            || (n >= 3 && runs[n - 3].len <= runs[n - 2].len + runs[n - 1].len)
+            || (n >= 4 && runs[n - 4].len <= runs[n - 3].len + runs[n - 2].len))
+    {
+        if n >= 3 && runs[n - 3].len < runs[n - 1].len {
+            Some(n - 3)
+        } else {
+            Some(n - 2)
+        }
+    } else {
+        None
+    }
+}
+
+/// Sorts the slice using the adaptive natural merge sort.
+fn internal_timsort<T, F>(slice: &mut [T], compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = slice.len();
+    if mem::size_of::<T>() == 0 || len < 2 {
+        return;
+    }
+
+    let min_run = min_run(len);
+    if len <= min_run {
+        let sorted = count_run(slice, compare);
+        binary_insertion_sort(slice, sorted, compare);
+        return;
+    }
+
+    // The buffer never holds more than half of the elements.
+    let mut buf: Vec<MaybeUninit<T>> = Vec::with_capacity(len / 2);
+    let mut runs: Vec<Run> = Vec::new();
+    let mut min_gallop = MIN_GALLOP;
+
+    let mut start = 0;
+    while start < len {
+        let mut run_len = count_run(&mut slice[start..], compare);
+        if run_len < min_run {
+            let end = len.min(start + min_run);
+            binary_insertion_sort(&mut slice[start..end], run_len, compare);
+            run_len = end - start;
+        }
+
+        runs.push(Run {
+            start,
+            len: run_len,
+        });
+        start += run_len;
+
+        while let Some(i) = collapse(&runs, start == len) {
+            let (left, right) = (runs[i], runs[i + 1]);
+            let merged = &mut slice[left.start..right.start + right.len];
+            unsafe {
+                merge(
+                    merged,
+                    left.len,
+                    buf.as_mut_ptr() as *mut T,
+                    compare,
+                    &mut min_gallop,
+                    &mut NoopObserver,
+                    len - left.start,
+                );
+            }
+
+            runs[i].len += right.len;
+            runs.remove(i + 1);
+        }
+    }
+}
+
+/// Sorts the slice using an adaptive natural merge sort (Timsort).
+///
+/// Takes advantage of already sorted or reversed parts of the slice,
+/// nearly sorted slices are sorted in close to O(n) time.
+/// It runs in O(n log(n)) time at worst and uses a buffer of n / 2 elements.
+///
+/// # Examples
+///
+/// ```
+/// use merge::timsort;
+///
+/// let mut nmbrs = vec![1,2,3,4,9,8,7,6,5];
+/// timsort(&mut nmbrs);
+///
+/// assert_eq!(nmbrs, vec![1,2,3,4,5,6,7,8,9]);
+/// ```
+pub fn timsort<T>(slice: &mut [T])
+where
+    T: Ord,
+{
+    timsort_by(slice, T::cmp);
+}
+
+/// Sorts the slice using an adaptive natural merge sort (Timsort)
+/// with a comparator function.
+///
+/// The sort is stable, equal elements keep their relative order.
+///
+/// # Examples
+///
+/// ```
+/// use merge::timsort_by;
+///
+/// let mut nmbrs = vec![1,3,2,5,4];
+/// timsort_by(&mut nmbrs, |a, b| b.cmp(a));
+///
+/// assert_eq!(nmbrs, vec![5,4,3,2,1]);
+/// ```
+pub fn timsort_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    internal_timsort(slice, &mut compare);
+}
+
+/// Sorts the slice using an adaptive natural merge sort (Timsort)
+/// with a key extraction function.
+///
+/// # Examples
+///
+/// ```
+/// use merge::timsort_by_key;
+///
+/// let mut nmbrs = vec![-4,3,-2,5,1];
+/// timsort_by_key(&mut nmbrs, |n: &i32| n.abs());
+///
+/// assert_eq!(nmbrs, vec![1,-2,3,-4,5]);
+/// ```
+pub fn timsort_by_key<T, K, F>(slice: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    timsort_by(slice, |a, b| f(a).cmp(&f(b)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_run() {
+        assert_eq!(min_run(63), 63);
+        assert_eq!(min_run(64), 32);
+        assert_eq!(min_run(65), 33);
+        assert_eq!(min_run(1 << 20), 32);
+    }
+
+    #[test]
+    fn test_count_run() {
+        let mut nmbrs = vec![5, 4, 3, 3, 2];
+        assert_eq!(count_run(&mut nmbrs, &mut i32::cmp), 3);
+        assert_eq!(nmbrs, vec![3, 4, 5, 3, 2]);
+
+        let mut nmbrs = vec![1, 2, 2, 3, 0];
+        assert_eq!(count_run(&mut nmbrs, &mut i32::cmp), 4);
+    }
+
+    #[test]
+    fn test_gallop() {
+        let nmbrs: Vec<i32> = (0..100).collect();
+        for &k in &[0, 1, 2, 50, 99, 100] {
+            assert_eq!(gallop(&nmbrs, |x| *x < k, false), k as usize);
+            assert_eq!(gallop(&nmbrs, |x| *x < k, true), k as usize);
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut nmbrs = vec![1, 3, 5, 7, 9, 2, 4];
+        let mut buf = Vec::with_capacity(2);
+        unsafe {
+            merge(
+                &mut nmbrs,
+                5,
+                buf.as_mut_ptr(),
+                &mut i32::cmp,
+                &mut 1,
+                &mut NoopObserver,
+                7,
+            );
+        }
+        assert_eq!(nmbrs, vec![1, 2, 3, 4, 5, 7, 9]);
+
+        let mut nmbrs = vec![2, 4, 1, 3, 5, 7, 9];
+        unsafe {
+            merge(
+                &mut nmbrs,
+                2,
+                buf.as_mut_ptr(),
+                &mut i32::cmp,
+                &mut 1,
+                &mut NoopObserver,
+                7,
+            );
+        }
+        assert_eq!(nmbrs, vec![1, 2, 3, 4, 5, 7, 9]);
+    }
+}
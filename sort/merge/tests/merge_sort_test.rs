@@ -1,4 +1,19 @@
-use merge::sort;
+use merge::{
+    dedup_sorted, dedup_sorted_by_key, difference, intersection, is_sorted, is_sorted_by,
+    is_sorted_by_key, merge_sorted, merge_sorted_by, symmetric_difference, union,
+};
+use merge::{
+    external_sort, external_sort_by, par_sort, par_sort_by, par_sort_by_grain, sort, sort_by,
+    sort_by_key, sort_by_observed, sort_in_place, sort_in_place_by, sort_in_place_by_key,
+    sort_in_place_by_observed, sort_in_place_observed, sort_observed, timsort, timsort_by,
+    timsort_by_key,
+};
+use merge::{sort_floats, ExternalSortOptions, LengthPrefixed, Lines, NanPlacement};
+use observe::{SortStats, Trace};
+use std::fs;
+use std::io;
+use std::io::{BufRead, Read, Write};
+use std::path::PathBuf;
 
 #[test]
 fn merge_sort_works() {
@@ -19,3 +34,499 @@ fn merge_sort_works() {
         vec![1, 2, 3, 5, 7, 45, 56, 66, 3432, 3543, 4325, 4534, 5234, 65453]
     );
 }
+
+#[test]
+fn merge_sort_by_works() {
+    let mut things = vec![4, 1, 6, 3, 5, 2];
+    sort_by(&mut things, |a, b| b.cmp(a));
+    assert_eq!(things, vec![6, 5, 4, 3, 2, 1]);
+
+    let mut things: Vec<i32> = vec![];
+    sort_by(&mut things, |a, b| b.cmp(a));
+    assert_eq!(things, vec![]);
+}
+
+#[test]
+fn merge_sort_by_key_is_stable() {
+    let mut pairs = vec![(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd'), (1, 'e')];
+    sort_by_key(&mut pairs, |p| p.0);
+    assert_eq!(
+        pairs,
+        vec![(1, 'b'), (1, 'e'), (2, 'd'), (3, 'a'), (3, 'c')]
+    );
+}
+
+#[test]
+fn merge_sort_is_stable() {
+    let mut pairs: Vec<(usize, usize)> = (0..1000).map(|i| ((i * 7919) % 13, i)).collect();
+    sort_by_key(&mut pairs, |p| p.0);
+    assert!(pairs
+        .windows(2)
+        .all(|w| w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1)));
+}
+
+#[test]
+fn merge_sort_in_place_works() {
+    let mut things = vec![
+        1, 5, 3, 2, 66, 7, 5234, 56, 4534, 65453, 45, 3543, 4325, 3432,
+    ];
+    sort_in_place(&mut things);
+    assert_eq!(
+        things,
+        vec![1, 2, 3, 5, 7, 45, 56, 66, 3432, 3543, 4325, 4534, 5234, 65453]
+    );
+
+    let mut things: Vec<i32> = (0..5000).map(|i| (i * 7919) % 5000).collect();
+    sort_in_place_by(&mut things, |a, b| b.cmp(a));
+    assert_eq!(things, (0..5000).rev().collect::<Vec<_>>());
+
+    let mut things: Vec<i32> = vec![];
+    sort_in_place(&mut things);
+    assert_eq!(things, vec![]);
+}
+
+#[test]
+fn merge_sort_in_place_is_stable() {
+    for &modulo in &[2, 13, 100, 1000] {
+        let mut pairs: Vec<(usize, usize)> = (0..3000).map(|i| ((i * 7919) % modulo, i)).collect();
+        sort_in_place_by_key(&mut pairs, |p| p.0);
+        assert!(pairs
+            .windows(2)
+            .all(|w| w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1)));
+    }
+}
+
+#[test]
+fn merge_sort_in_place_with_few_unique_values() {
+    // Too few unique values in A for the internal buffers of the block merge.
+    let n: usize = 1 << 17;
+    for &unique in &[5, 40, 500] {
+        let mut pairs: Vec<(usize, usize)> = (0..n).map(|i| ((i * 7919) % unique, i)).collect();
+        let mut stats = SortStats::default();
+        sort_in_place_by_observed(&mut pairs, |a, b| a.0.cmp(&b.0), &mut stats);
+        assert!(pairs
+            .windows(2)
+            .all(|w| w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1)));
+        assert!(stats.moves + stats.swaps <= 6 * n * 17);
+    }
+}
+
+#[test]
+fn timsort_works() {
+    let mut things = vec![
+        1, 5, 3, 2, 66, 7, 5234, 56, 4534, 65453, 45, 3543, 4325, 3432,
+    ];
+    timsort(&mut things);
+    assert_eq!(
+        things,
+        vec![1, 2, 3, 5, 7, 45, 56, 66, 3432, 3543, 4325, 4534, 5234, 65453]
+    );
+
+    let mut things: Vec<i32> = (0..1000).rev().chain(0..1000).collect();
+    timsort(&mut things);
+    assert_eq!(things, (0..2000).map(|i| i / 2).collect::<Vec<_>>());
+}
+
+#[test]
+fn timsort_is_stable() {
+    let mut pairs: Vec<(usize, usize)> = (0..1000).map(|i| ((i * 7919) % 13, i)).collect();
+    timsort_by_key(&mut pairs, |p| p.0);
+    assert!(pairs
+        .windows(2)
+        .all(|w| w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1)));
+}
+
+#[test]
+fn timsort_is_adaptive() {
+    let mut comparisons = 0;
+    let mut things: Vec<i32> = (0..10_000).collect();
+    timsort_by(&mut things, |a, b| {
+        comparisons += 1;
+        a.cmp(b)
+    });
+    assert_eq!(comparisons, 9_999);
+
+    let mut comparisons = 0;
+    let mut things: Vec<i32> = (0..10_000).rev().collect();
+    timsort_by(&mut things, |a, b| {
+        comparisons += 1;
+        a.cmp(b)
+    });
+    assert_eq!(comparisons, 9_999);
+    assert_eq!(things, (0..10_000).collect::<Vec<_>>());
+}
+
+#[test]
+fn par_sort_works() {
+    let mut things = vec![
+        1, 5, 3, 2, 66, 7, 5234, 56, 4534, 65453, 45, 3543, 4325, 3432,
+    ];
+    par_sort(&mut things);
+    assert_eq!(
+        things,
+        vec![1, 2, 3, 5, 7, 45, 56, 66, 3432, 3543, 4325, 4534, 5234, 65453]
+    );
+
+    let mut things: Vec<i32> = vec![];
+    par_sort_by(&mut things, |a, b| b.cmp(a));
+    assert_eq!(things, vec![]);
+}
+
+#[test]
+fn par_sort_is_stable() {
+    let n = 100_000;
+    let mut pairs: Vec<(usize, usize)> = (0..n).map(|i| ((i * 7919) % 13, i)).collect();
+    par_sort_by_grain(&mut pairs, 64, |a, b| a.0.cmp(&b.0));
+    assert!(pairs
+        .windows(2)
+        .all(|w| w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1)));
+}
+
+#[test]
+fn merge_sort_in_place_observed_works() {
+    let mut things = vec![
+        1, 5, 3, 2, 66, 7, 5234, 56, 4534, 65453, 45, 3543, 4325, 3432,
+    ];
+    let mut stats = SortStats::default();
+    sort_in_place_observed(&mut things, &mut stats);
+    assert_eq!(
+        things,
+        vec![1, 2, 3, 5, 7, 45, 56, 66, 3432, 3543, 4325, 4534, 5234, 65453]
+    );
+    assert!(stats.moves > 0);
+    assert_eq!(stats.recursions, 0);
+
+    let n = 1024;
+    let mut pairs: Vec<(usize, usize)> = (0..n).map(|i| ((i * 7919) % 13, i)).collect();
+    let mut stats = SortStats::default();
+    let mut comparisons = 0;
+    sort_in_place_by_observed(
+        &mut pairs,
+        |a, b| {
+            comparisons += 1;
+            a.0.cmp(&b.0)
+        },
+        &mut stats,
+    );
+    assert!(pairs
+        .windows(2)
+        .all(|w| w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1)));
+    assert_eq!(stats.comparisons, comparisons);
+    assert_eq!(stats.max_depth, 6);
+}
+
+#[test]
+fn merge_sort_observed_works() {
+    let mut things = vec![
+        1, 5, 3, 2, 66, 7, 5234, 56, 4534, 65453, 45, 3543, 4325, 3432, 9, 8, 0,
+    ];
+    let mut stats = SortStats::default();
+    sort_observed(&mut things, &mut stats);
+    assert_eq!(
+        things,
+        vec![0, 1, 2, 3, 5, 7, 8, 9, 45, 56, 66, 3432, 3543, 4325, 4534, 5234, 65453]
+    );
+    assert_eq!(stats.swaps, 0);
+    assert_eq!(stats.max_depth, 1);
+
+    // Long runs of one input make the merges gallop.
+    let n = 1024;
+    let keys: Vec<Vec<usize>> = vec![
+        (0..n).map(|i| (i * 7919) % 13).collect(),
+        (0..n).rev().map(|i| (i / 100) * 1000 + i % 7).collect(),
+        (0..n).map(|i| if i < n / 2 { i + n } else { i }).collect(),
+    ];
+    for keys in keys {
+        let input: Vec<(usize, usize)> = keys.into_iter().zip(0..).collect();
+        let mut pairs = input.clone();
+        let mut trace = Trace::new();
+        let mut stats = SortStats::default();
+        let mut comparisons = 0;
+        sort_by_observed(&mut pairs, |a, b| a.0.cmp(&b.0), &mut trace);
+        sort_by_observed(
+            &mut input.clone(),
+            |a, b| {
+                comparisons += 1;
+                a.0.cmp(&b.0)
+            },
+            &mut stats,
+        );
+
+        assert!(pairs
+            .windows(2)
+            .all(|w| w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1)));
+        assert_eq!(stats.comparisons, comparisons);
+        assert!(stats.moves > 0);
+        assert_eq!(
+            observe::replay_by(&input, trace.events(), |a, b| a.0.cmp(&b.0)),
+            Ok(pairs)
+        );
+    }
+}
+
+/// Returns a new empty directory for the temporary files of a test.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("merge-test-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn external_sort_works() {
+    let dir = temp_dir("works");
+    let nmbrs: Vec<u32> = (0..5000).map(|i| (i * 7919) % 1009).collect();
+    let input: String = nmbrs.iter().map(|n| format!("{:04}\n", n)).collect();
+
+    // Every run holds a few records, so the runs are merged in several passes.
+    let options = ExternalSortOptions::default()
+        .memory_budget(256)
+        .temp_dir(&dir);
+    let mut output = Vec::new();
+    external_sort(input.as_bytes(), &mut output, Lines, &options).unwrap();
+
+    let mut want = nmbrs;
+    want.sort();
+    let want: String = want.iter().map(|n| format!("{:04}\n", n)).collect();
+    assert_eq!(String::from_utf8(output).unwrap(), want);
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+    fs::remove_dir(&dir).unwrap();
+}
+
+#[test]
+fn external_sort_is_stable() {
+    let dir = temp_dir("stable");
+    let input: String = (0..1000).map(|i| format!("{},{}\n", i % 7, i)).collect();
+
+    let options = ExternalSortOptions::default()
+        .memory_budget(1024)
+        .temp_dir(&dir);
+    let mut output = Vec::new();
+    external_sort_by(input.as_bytes(), &mut output, Lines, &options, |a, b| {
+        a[..1].cmp(&b[..1])
+    })
+    .unwrap();
+
+    let mut want: Vec<(u32, u32)> = (0..1000).map(|i| (i % 7, i)).collect();
+    want.sort_by_key(|p| p.0);
+    let want: String = want.iter().map(|p| format!("{},{}\n", p.0, p.1)).collect();
+    assert_eq!(String::from_utf8(output).unwrap(), want);
+
+    fs::remove_dir(&dir).unwrap();
+}
+
+#[test]
+fn external_sort_length_prefixed() {
+    let dir = temp_dir("binary");
+    let records: Vec<Vec<u8>> = (0..500u32)
+        .map(|i| (i * 31 % 97).to_be_bytes()[..(i % 4 + 1) as usize].to_vec())
+        .collect();
+    let mut input = Vec::new();
+    for record in &records {
+        input.extend_from_slice(&(record.len() as u32).to_le_bytes());
+        input.extend_from_slice(record);
+    }
+
+    let options = ExternalSortOptions::default()
+        .memory_budget(512)
+        .temp_dir(&dir);
+    let mut output = Vec::new();
+    external_sort(&input[..], &mut output, LengthPrefixed, &options).unwrap();
+
+    let mut want = records;
+    want.sort();
+    let mut output = &output[..];
+    for record in want {
+        let mut len = [0; 4];
+        output.read_exact(&mut len).unwrap();
+        assert_eq!(u32::from_le_bytes(len) as usize, record.len());
+        let mut got = vec![0; record.len()];
+        output.read_exact(&mut got).unwrap();
+        assert_eq!(got, record);
+    }
+    assert!(output.is_empty());
+
+    fs::remove_dir(&dir).unwrap();
+}
+
+/// A reader which fails once it has read the given number of lines.
+struct FailingReader<'a> {
+    lines: io::Lines<&'a [u8]>,
+    buf: Vec<u8>,
+    pos: usize,
+    remaining: usize,
+}
+
+impl Read for FailingReader<'_> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let n = self.fill_buf()?.read(out)?;
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl BufRead for FailingReader<'_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos == self.buf.len() {
+            if self.remaining == 0 {
+                return Err(io::Error::other("broken reader"));
+            }
+            self.remaining -= 1;
+            self.buf = match self.lines.next() {
+                Some(line) => format!("{}\n", line?).into_bytes(),
+                None => Vec::new(),
+            };
+            self.pos = 0;
+        }
+        Ok(&self.buf[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt;
+    }
+}
+
+/// A writer which always fails.
+struct FailingWriter;
+
+impl Write for FailingWriter {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::other("broken writer"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn external_sort_removes_temp_files_on_error() {
+    let dir = temp_dir("error");
+    let input: String = (0..1000).rev().map(|i| format!("{}\n", i)).collect();
+    let options = ExternalSortOptions::default()
+        .memory_budget(256)
+        .temp_dir(&dir);
+
+    let reader = FailingReader {
+        lines: input.as_bytes().lines(),
+        buf: Vec::new(),
+        pos: 0,
+        remaining: 500,
+    };
+    let err = external_sort(reader, Vec::new(), Lines, &options).unwrap_err();
+    assert_eq!(err.to_string(), "broken reader");
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+    let err = external_sort(input.as_bytes(), FailingWriter, Lines, &options).unwrap_err();
+    assert_eq!(err.to_string(), "broken writer");
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+    fs::remove_dir(&dir).unwrap();
+}
+
+#[test]
+fn merge_sort_floats_across_merges() {
+    let floats: Vec<f64> = (0..100)
+        .map(|i| match i % 7 {
+            0 => f64::NAN,
+            _ => (i * 37 % 23) as f64 - 11.0,
+        })
+        .collect();
+
+    let mut first = floats.clone();
+    sort_floats(&mut first, NanPlacement::First);
+    assert!(first[..15].iter().all(|f| f.is_nan()));
+    assert!(first[15..].windows(2).all(|w| w[0] <= w[1]));
+
+    let mut last = floats;
+    sort_floats(&mut last, NanPlacement::Last);
+    assert_eq!(last[..85], first[15..]);
+    assert!(last[85..].iter().all(|f| f.is_nan()));
+}
+
+#[test]
+fn is_sorted_works() {
+    assert!(is_sorted::<i32>(&[]));
+    assert!(is_sorted(&[1]));
+    assert!(is_sorted(&[1, 1, 2, 5]));
+    assert!(!is_sorted(&[1, 2, 5, 4]));
+    assert!(is_sorted_by(&[5, 2, 2, 1], |a, b| b.cmp(a)));
+    assert!(is_sorted_by_key(&["a", "bb", "cc"], |s| s.len()));
+}
+
+#[test]
+fn merge_sorted_is_stable() {
+    let runs = vec![
+        vec![(1, 0), (3, 0), (3, 1)],
+        vec![],
+        vec![(0, 2), (3, 2), (5, 2)],
+        vec![(1, 3), (3, 3)],
+    ];
+    let mut expected: Vec<_> = runs.iter().flatten().copied().collect();
+    sort_by_key(&mut expected, |p| p.0);
+
+    let merged: Vec<_> = merge_sorted_by(runs, |a, b| a.0.cmp(&b.0)).collect();
+    assert_eq!(merged, expected);
+
+    let merged: Vec<i32> = merge_sorted(Vec::<Vec<i32>>::new()).collect();
+    assert_eq!(merged, vec![]);
+
+    let evens = (0..10).step_by(2);
+    let odds = (1..10).step_by(2);
+    assert_eq!(
+        merge_sorted(vec![evens, odds]).collect::<Vec<_>>(),
+        (0..10).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn set_operations_work() {
+    let a = [1, 2, 2, 2, 4, 6, 6];
+    let b = [2, 3, 4, 4, 6, 7];
+
+    assert_eq!(
+        union(&a, &b).copied().collect::<Vec<_>>(),
+        vec![1, 2, 2, 2, 3, 4, 4, 6, 6, 7]
+    );
+    assert_eq!(
+        intersection(&a, &b).copied().collect::<Vec<_>>(),
+        vec![2, 4, 6]
+    );
+    assert_eq!(
+        difference(&a, &b).copied().collect::<Vec<_>>(),
+        vec![1, 2, 2, 6]
+    );
+    assert_eq!(
+        difference(&b, &a).copied().collect::<Vec<_>>(),
+        vec![3, 4, 7]
+    );
+    assert_eq!(
+        symmetric_difference(&a, &b).copied().collect::<Vec<_>>(),
+        vec![1, 2, 2, 3, 4, 6, 7]
+    );
+
+    assert_eq!(union(&a, &[]).count(), a.len());
+    assert_eq!(intersection(&a, &[]).count(), 0);
+    assert_eq!(difference(&[], &b).count(), 0);
+    assert_eq!(symmetric_difference(&[], &b).count(), b.len());
+}
+
+#[test]
+fn dedup_sorted_works() {
+    let mut empty: Vec<i32> = vec![];
+    assert_eq!(dedup_sorted(&mut empty), 0);
+
+    let mut nmbrs = vec![1, 1, 1, 2, 3, 3, 5, 5, 5, 5, 8];
+    let mut expected = nmbrs.clone();
+    expected.dedup();
+
+    let len = dedup_sorted(&mut nmbrs);
+    assert_eq!(nmbrs[..len], expected[..]);
+    sort(&mut nmbrs[len..]);
+    assert_eq!(nmbrs[len..], [1, 1, 3, 5, 5, 5]);
+
+    let mut pairs = vec![(1, 'a'), (1, 'b'), (2, 'c'), (2, 'd'), (3, 'e')];
+    let len = dedup_sorted_by_key(&mut pairs, |p| p.0);
+    assert_eq!(pairs[..len], [(1, 'a'), (2, 'c'), (3, 'e')]);
+}
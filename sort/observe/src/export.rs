@@ -0,0 +1,201 @@
+//! Exporting traces for visualizations.
+
+use std::fmt::Display;
+use std::io;
+use std::io::Write;
+
+use super::trace::{apply, ReplayError};
+use super::SortEvent;
+
+/// Writes every event of the trace as a JSON object on its own line.
+///
+/// # Examples
+///
+/// ```
+/// use observe::{write_json_lines, SortEvent};
+///
+/// let events = vec![
+///     SortEvent::Compare(1, 0),
+///     SortEvent::Rotate { range: 0..3, mid: 1 },
+/// ];
+/// let mut out = Vec::new();
+/// write_json_lines(&events, &mut out).unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     "{\"event\":\"compare\",\"i\":1,\"j\":0}\n\
+///      {\"event\":\"rotate\",\"start\":0,\"end\":3,\"mid\":1}\n"
+/// );
+/// ```
+pub fn write_json_lines<W>(events: &[SortEvent], mut writer: W) -> io::Result<()>
+where
+    W: Write,
+{
+    for event in events {
+        match event {
+            SortEvent::Compare(i, j) => {
+                writeln!(writer, r#"{{"event":"compare","i":{},"j":{}}}"#, i, j)?
+            }
+            SortEvent::Swap(i, j) => writeln!(writer, r#"{{"event":"swap","i":{},"j":{}}}"#, i, j)?,
+            SortEvent::Move { from, to } => {
+                writeln!(writer, r#"{{"event":"move","from":{},"to":{}}}"#, from, to)?
+            }
+            SortEvent::Rotate { range, mid } => writeln!(
+                writer,
+                r#"{{"event":"rotate","start":{},"end":{},"mid":{}}}"#,
+                range.start, range.end, mid
+            )?,
+            SortEvent::Partition { pivot, range } => writeln!(
+                writer,
+                r#"{{"event":"partition","pivot":{},"start":{},"end":{}}}"#,
+                pivot, range.start, range.end
+            )?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the marker line of the event, with the marker of every
+/// element right aligned in a cell of `width` characters.
+///
+/// Indices of the scratch buffer are not marked.
+fn markers(event: &SortEvent, len: usize, width: usize) -> String {
+    let mut marks = vec![' '; len];
+    match event {
+        SortEvent::Compare(i, j) => {
+            for &k in &[*i, *j] {
+                if k < len {
+                    marks[k] = '^';
+                }
+            }
+        }
+        SortEvent::Swap(i, j) => {
+            marks[*i] = '*';
+            marks[*j] = '*';
+        }
+        SortEvent::Move { from, to } => {
+            if *from < len {
+                marks[*from] = 'o';
+            }
+            if *to < len {
+                marks[*to] = '+';
+            }
+        }
+        SortEvent::Rotate { range, mid } => {
+            for mark in &mut marks[range.clone()] {
+                *mark = '~';
+            }
+            if *mid < range.end {
+                marks[*mid] = '<';
+            }
+        }
+        SortEvent::Partition { pivot, range } => {
+            for mark in &mut marks[range.clone()] {
+                *mark = '-';
+            }
+            marks[*pivot] = '|';
+        }
+    }
+
+    let line: Vec<String> = marks
+        .iter()
+        .map(|mark| format!("{:>width$}", mark, width = width))
+        .collect();
+    line.join(" ").trim_end().to_string()
+}
+
+/// Writes the slice with every element right aligned in a cell of `width` characters.
+fn write_frame<T, W>(slice: &[T], width: usize, writer: &mut W) -> io::Result<()>
+where
+    T: Display,
+    W: Write,
+{
+    let line: Vec<String> = slice
+        .iter()
+        .map(|elem| format!("{:>width$}", elem, width = width))
+        .collect();
+    writeln!(writer, "{}", line.join(" "))
+}
+
+/// Writes an ASCII animation of the trace, one frame per event.
+///
+/// Every frame has a header with the event, the state of the slice after
+/// the event and a line marking the elements the event refers to:
+/// `^` compared, `*` swapped, `o` moved from and `+` moved to, `~` rotated
+/// with `<` marking the element which became the first one, `-` partitioned
+/// with `|` marking the pivot. The scratch buffer of the sort isn't shown.
+///
+/// Returns an error of kind [`InvalidInput`](io::ErrorKind::InvalidInput)
+/// if an event doesn't fit the input.
+///
+/// # Examples
+///
+/// ```
+/// use observe::{write_frames, SortEvent};
+///
+/// let events = vec![SortEvent::Compare(1, 0), SortEvent::Swap(1, 0)];
+/// let mut out = Vec::new();
+/// write_frames(&[20, 1], &events, &mut out).unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     "#0 start\n20  1\n\
+///      #1 compare(1, 0)\n20  1\n ^  ^\n\
+///      #2 swap(1, 0)\n 1 20\n *  *\n"
+/// );
+/// ```
+pub fn write_frames<T, W>(input: &[T], events: &[SortEvent], mut writer: W) -> io::Result<()>
+where
+    T: Clone + Display,
+    W: Write,
+{
+    let mut slice = input.to_vec();
+    let mut scratch = Vec::new();
+    let width = slice
+        .iter()
+        .map(|elem| elem.to_string().chars().count())
+        .max()
+        .unwrap_or(0);
+
+    writeln!(writer, "#0 start")?;
+    write_frame(&slice, width, &mut writer)?;
+
+    for (n, event) in events.iter().enumerate() {
+        apply(&mut slice, &mut scratch, event).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                ReplayError::InvalidEvent { event: n },
+            )
+        })?;
+
+        writeln!(writer, "#{} {}", n + 1, event)?;
+        write_frame(&slice, width, &mut writer)?;
+        writeln!(writer, "{}", markers(event, slice.len(), width))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markers() {
+        let event = SortEvent::Rotate {
+            range: 1..4,
+            mid: 2,
+        };
+        assert_eq!(markers(&event, 5, 2), "    ~  <  ~");
+
+        let event = SortEvent::Partition {
+            pivot: 0,
+            range: 0..2,
+        };
+        assert_eq!(markers(&event, 3, 1), "| -");
+
+        let event = SortEvent::Move { from: 4, to: 1 };
+        assert_eq!(markers(&event, 3, 1), "  +");
+    }
+}
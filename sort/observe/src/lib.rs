@@ -0,0 +1,233 @@
+//! # Observe
+//!
+//! `observe` provides the hooks through which the sort crates
+//! report the comparisons, swaps and moves they perform.
+//!
+//! Every instrumented sort is generic over the [`SortObserver`], with
+//! [`NoopObserver`] all the calls are inlined away. [`SortStats`]
+//! counts the operations.
+//!
+//! [`Trace`] records the operations as [`SortEvent`]s, which can be
+//! replayed, see [`replay`], or exported, see [`write_json_lines`]
+//! and [`write_frames`].
+
+pub use export::{write_frames, write_json_lines};
+pub use trace::{replay, replay_by, ReplayError, SortEvent, Trace};
+
+mod export;
+mod trace;
+
+use std::ops::Range;
+
+/// Receives the operations performed by an instrumented sort.
+///
+/// Indices are positions in the slice being sorted. Sorts which move
+/// elements out of the slice report the slots of their scratch buffer
+/// as the indices past its end, slot `k` as `len + k`, and never use
+/// more slots than the slice has elements.
+/// All methods do nothing by default.
+pub trait SortObserver {
+    /// Called when the elements at indices `i` and `j` are compared.
+    #[inline]
+    fn on_compare(&mut self, i: usize, j: usize) {
+        let _ = (i, j);
+    }
+
+    /// Called when the elements at indices `i` and `j` are swapped.
+    #[inline]
+    fn on_swap(&mut self, i: usize, j: usize) {
+        let _ = (i, j);
+    }
+
+    /// Called when the element at index `from` is moved to index `to`,
+    /// replacing the one there.
+    #[inline]
+    fn on_move(&mut self, from: usize, to: usize) {
+        let _ = (from, to);
+    }
+
+    /// Called when the elements within `range` are rotated to the left,
+    /// such that the element at index `mid` becomes the first one.
+    ///
+    /// By default every moved element is reported to [`on_move`](Self::on_move).
+    #[inline]
+    fn on_rotate(&mut self, range: Range<usize>, mid: usize) {
+        let len = range.len();
+        let k = mid - range.start;
+        for i in 0..len {
+            self.on_move(range.start + i, range.start + (i + len - k) % len);
+        }
+    }
+
+    /// Called when the elements within `range` have been partitioned
+    /// around the pivot, which ended up at index `pivot`.
+    #[inline]
+    fn on_partition(&mut self, pivot: usize, range: Range<usize>) {
+        let _ = (pivot, range);
+    }
+
+    /// Called when the sort recurses, with the depth of the recursion.
+    #[inline]
+    fn on_recurse(&mut self, depth: usize) {
+        let _ = depth;
+    }
+}
+
+impl<O> SortObserver for &mut O
+where
+    O: SortObserver + ?Sized,
+{
+    #[inline]
+    fn on_compare(&mut self, i: usize, j: usize) {
+        (**self).on_compare(i, j);
+    }
+
+    #[inline]
+    fn on_swap(&mut self, i: usize, j: usize) {
+        (**self).on_swap(i, j);
+    }
+
+    #[inline]
+    fn on_move(&mut self, from: usize, to: usize) {
+        (**self).on_move(from, to);
+    }
+
+    #[inline]
+    fn on_rotate(&mut self, range: Range<usize>, mid: usize) {
+        (**self).on_rotate(range, mid);
+    }
+
+    #[inline]
+    fn on_partition(&mut self, pivot: usize, range: Range<usize>) {
+        (**self).on_partition(pivot, range);
+    }
+
+    #[inline]
+    fn on_recurse(&mut self, depth: usize) {
+        (**self).on_recurse(depth);
+    }
+}
+
+/// An observer which ignores all the operations.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopObserver;
+
+impl SortObserver for NoopObserver {}
+
+/// An observer which shifts all the indices by an offset
+/// before passing them on, for sorts which work on a subslice.
+///
+/// # Examples
+///
+/// ```
+/// use observe::{Shifted, SortObserver, SortStats};
+///
+/// let mut stats = SortStats::default();
+/// Shifted::new(&mut stats, 10).on_swap(0, 1);
+///
+/// assert_eq!(stats.swaps, 1);
+/// ```
+#[derive(Debug)]
+pub struct Shifted<O> {
+    observer: O,
+    offset: usize,
+}
+
+impl<O> Shifted<O> {
+    /// Creates an observer which reports index `i` as `i + offset`.
+    pub fn new(observer: O, offset: usize) -> Self {
+        Shifted { observer, offset }
+    }
+}
+
+impl<O> SortObserver for Shifted<O>
+where
+    O: SortObserver,
+{
+    #[inline]
+    fn on_compare(&mut self, i: usize, j: usize) {
+        self.observer.on_compare(i + self.offset, j + self.offset);
+    }
+
+    #[inline]
+    fn on_swap(&mut self, i: usize, j: usize) {
+        self.observer.on_swap(i + self.offset, j + self.offset);
+    }
+
+    #[inline]
+    fn on_move(&mut self, from: usize, to: usize) {
+        self.observer.on_move(from + self.offset, to + self.offset);
+    }
+
+    #[inline]
+    fn on_rotate(&mut self, range: Range<usize>, mid: usize) {
+        let offset = self.offset;
+        self.observer
+            .on_rotate(range.start + offset..range.end + offset, mid + offset);
+    }
+
+    #[inline]
+    fn on_partition(&mut self, pivot: usize, range: Range<usize>) {
+        let offset = self.offset;
+        self.observer
+            .on_partition(pivot + offset, range.start + offset..range.end + offset);
+    }
+
+    #[inline]
+    fn on_recurse(&mut self, depth: usize) {
+        self.observer.on_recurse(depth);
+    }
+}
+
+/// An observer which counts the operations.
+///
+/// # Examples
+///
+/// ```
+/// use observe::{SortObserver, SortStats};
+///
+/// let mut stats = SortStats::default();
+/// stats.on_compare(0, 1);
+/// stats.on_swap(0, 1);
+/// stats.on_recurse(3);
+///
+/// assert_eq!(stats.comparisons, 1);
+/// assert_eq!(stats.swaps, 1);
+/// assert_eq!(stats.max_depth, 3);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SortStats {
+    /// Number of comparisons.
+    pub comparisons: usize,
+    /// Number of swaps.
+    pub swaps: usize,
+    /// Number of element moves.
+    pub moves: usize,
+    /// Number of recursive calls.
+    pub recursions: usize,
+    /// The deepest recursion reached.
+    pub max_depth: usize,
+}
+
+impl SortObserver for SortStats {
+    #[inline]
+    fn on_compare(&mut self, _: usize, _: usize) {
+        self.comparisons += 1;
+    }
+
+    #[inline]
+    fn on_swap(&mut self, _: usize, _: usize) {
+        self.swaps += 1;
+    }
+
+    #[inline]
+    fn on_move(&mut self, _: usize, _: usize) {
+        self.moves += 1;
+    }
+
+    #[inline]
+    fn on_recurse(&mut self, depth: usize) {
+        self.recursions += 1;
+        self.max_depth = self.max_depth.max(depth);
+    }
+}
@@ -0,0 +1,346 @@
+//! Recording and replaying the operations of a sort.
+
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+use super::SortObserver;
+
+/// An operation performed by an instrumented sort.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SortEvent {
+    /// The elements at the two indices were compared.
+    Compare(usize, usize),
+    /// The elements at the two indices were swapped.
+    Swap(usize, usize),
+    /// The element at index `from` was moved to index `to`,
+    /// replacing the one there.
+    Move { from: usize, to: usize },
+    /// The elements within `range` were rotated to the left,
+    /// such that the element at index `mid` became the first one.
+    Rotate { range: Range<usize>, mid: usize },
+    /// The elements within `range` were partitioned around
+    /// the pivot, which ended up at index `pivot`.
+    Partition { pivot: usize, range: Range<usize> },
+}
+
+impl SortEvent {
+    /// Returns the length a slice needs for the event to refer to its elements.
+    fn bound(&self) -> usize {
+        match self {
+            SortEvent::Compare(i, j) | SortEvent::Swap(i, j) => (*i).max(*j) + 1,
+            SortEvent::Move { from, to } => (*from).max(*to) + 1,
+            SortEvent::Rotate { range, .. } | SortEvent::Partition { range, .. } => range.end,
+        }
+    }
+
+    /// Returns whether the indices of the event are consistent.
+    fn is_well_formed(&self) -> bool {
+        match self {
+            SortEvent::Compare(..) | SortEvent::Swap(..) | SortEvent::Move { .. } => true,
+            SortEvent::Rotate { range, mid } => range.start <= *mid && *mid <= range.end,
+            SortEvent::Partition { pivot, range } => range.contains(pivot),
+        }
+    }
+}
+
+impl fmt::Display for SortEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortEvent::Compare(i, j) => write!(f, "compare({}, {})", i, j),
+            SortEvent::Swap(i, j) => write!(f, "swap({}, {})", i, j),
+            SortEvent::Move { from, to } => write!(f, "move({}, {})", from, to),
+            SortEvent::Rotate { range, mid } => write!(f, "rotate({:?}, {})", range, mid),
+            SortEvent::Partition { pivot, range } => {
+                write!(f, "partition({}, {:?})", pivot, range)
+            }
+        }
+    }
+}
+
+/// Applies the swap, move or rotation of the event to the slice.
+///
+/// Moved elements are cloned. Indices past the end of the slice refer
+/// to the slots of the scratch buffer, which has room for as many
+/// elements as the slice and is grown as they are written.
+///
+/// Returns `None`, leaving the slice as is, if the event is invalid
+/// for the slice or refers to an unwritten slot.
+pub(crate) fn apply<T>(
+    slice: &mut [T],
+    scratch: &mut Vec<Option<T>>,
+    event: &SortEvent,
+) -> Option<()>
+where
+    T: Clone,
+{
+    let len = slice.len();
+    let bound = match event {
+        SortEvent::Compare(..) | SortEvent::Move { .. } => 2 * len,
+        _ => len,
+    };
+    if !event.is_well_formed() || event.bound() > bound {
+        return None;
+    }
+
+    let is_written = |i: usize| i < len || matches!(scratch.get(i - len), Some(Some(_)));
+    match event {
+        SortEvent::Compare(i, j) if !is_written(*i) || !is_written(*j) => return None,
+        SortEvent::Swap(i, j) => slice.swap(*i, *j),
+        SortEvent::Move { from, to } => {
+            let elem = match from.checked_sub(len) {
+                None => slice[*from].clone(),
+                Some(slot) => scratch.get(slot)?.clone()?,
+            };
+            match to.checked_sub(len) {
+                None => slice[*to] = elem,
+                Some(slot) => {
+                    if scratch.len() <= slot {
+                        scratch.resize(slot + 1, None);
+                    }
+                    scratch[slot] = Some(elem);
+                }
+            }
+        }
+        SortEvent::Rotate { range, mid } => slice[range.clone()].rotate_left(mid - range.start),
+        SortEvent::Compare(..) | SortEvent::Partition { .. } => {}
+    }
+    Some(())
+}
+
+/// An observer which records the operations as [`SortEvent`]s.
+///
+/// # Examples
+///
+/// ```
+/// use observe::{SortEvent, SortObserver, Trace};
+///
+/// let mut trace = Trace::new();
+/// trace.on_compare(1, 0);
+/// trace.on_swap(1, 0);
+///
+/// assert_eq!(
+///     trace.into_events(),
+///     vec![SortEvent::Compare(1, 0), SortEvent::Swap(1, 0)]
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Trace {
+    events: Vec<SortEvent>,
+}
+
+impl Trace {
+    /// Creates an empty trace.
+    pub fn new() -> Self {
+        Trace::default()
+    }
+
+    /// Returns the recorded events.
+    pub fn events(&self) -> &[SortEvent] {
+        &self.events
+    }
+
+    /// Returns the recorded events, consuming the trace.
+    pub fn into_events(self) -> Vec<SortEvent> {
+        self.events
+    }
+}
+
+impl SortObserver for Trace {
+    fn on_compare(&mut self, i: usize, j: usize) {
+        self.events.push(SortEvent::Compare(i, j));
+    }
+
+    fn on_swap(&mut self, i: usize, j: usize) {
+        self.events.push(SortEvent::Swap(i, j));
+    }
+
+    fn on_move(&mut self, from: usize, to: usize) {
+        self.events.push(SortEvent::Move { from, to });
+    }
+
+    fn on_rotate(&mut self, range: Range<usize>, mid: usize) {
+        self.events.push(SortEvent::Rotate { range, mid });
+    }
+
+    fn on_partition(&mut self, pivot: usize, range: Range<usize>) {
+        self.events.push(SortEvent::Partition { pivot, range });
+    }
+}
+
+/// The error returned when a trace can't be replayed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The event at the index refers to elements outside of the input,
+    /// or its indices are inconsistent.
+    InvalidEvent { event: usize },
+    /// After the replay the element at the index is less than the one before it.
+    NotSorted { index: usize },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::InvalidEvent { event } => write!(f, "invalid trace event #{}", event),
+            ReplayError::NotSorted { index } => {
+                write!(f, "replayed slice is not sorted at index {}", index)
+            }
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+/// Applies the swaps, moves and rotations of the trace to a copy of the
+/// input and checks that the result is sorted.
+///
+/// Moves to and from the indices past the end of the input go through
+/// a scratch buffer, see [`SortObserver`].
+///
+/// Returns the sorted copy.
+///
+/// # Examples
+///
+/// ```
+/// use observe::{replay, SortEvent};
+///
+/// let events = vec![SortEvent::Compare(1, 0), SortEvent::Swap(1, 0)];
+///
+/// assert_eq!(replay(&[2, 1], &events), Ok(vec![1, 2]));
+/// assert!(replay(&[2, 1], &events[..1]).is_err());
+/// ```
+pub fn replay<T>(input: &[T], events: &[SortEvent]) -> Result<Vec<T>, ReplayError>
+where
+    T: Clone + Ord,
+{
+    replay_by(input, events, T::cmp)
+}
+
+/// Applies the swaps, moves and rotations of the trace to a copy of the input
+/// and checks that the result is sorted with respect to the comparator function.
+///
+/// See [`replay`].
+pub fn replay_by<T, F>(
+    input: &[T],
+    events: &[SortEvent],
+    mut compare: F,
+) -> Result<Vec<T>, ReplayError>
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut slice = input.to_vec();
+    let mut scratch = Vec::new();
+
+    for (n, event) in events.iter().enumerate() {
+        apply(&mut slice, &mut scratch, event).ok_or(ReplayError::InvalidEvent { event: n })?;
+    }
+
+    match slice
+        .windows(2)
+        .position(|w| compare(&w[1], &w[0]) == Ordering::Less)
+    {
+        Some(index) => Err(ReplayError::NotSorted { index: index + 1 }),
+        None => Ok(slice),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bound() {
+        assert_eq!(SortEvent::Compare(3, 1).bound(), 4);
+        assert_eq!(SortEvent::Move { from: 6, to: 2 }.bound(), 7);
+        assert_eq!(
+            SortEvent::Rotate {
+                range: 2..5,
+                mid: 5
+            }
+            .bound(),
+            5
+        );
+        assert_eq!(
+            SortEvent::Partition {
+                pivot: 0,
+                range: 0..1
+            }
+            .bound(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_apply_move_through_scratch() {
+        let mut slice = vec![1, 2];
+        let mut scratch = Vec::new();
+
+        assert_eq!(
+            apply(
+                &mut slice,
+                &mut scratch,
+                &SortEvent::Move { from: 3, to: 0 }
+            ),
+            None
+        );
+        assert_eq!(
+            apply(
+                &mut slice,
+                &mut scratch,
+                &SortEvent::Move { from: 0, to: 3 }
+            ),
+            Some(())
+        );
+        assert_eq!(scratch, vec![None, Some(1)]);
+        assert_eq!(
+            apply(
+                &mut slice,
+                &mut scratch,
+                &SortEvent::Move { from: 1, to: 0 }
+            ),
+            Some(())
+        );
+        assert_eq!(
+            apply(
+                &mut slice,
+                &mut scratch,
+                &SortEvent::Move { from: 3, to: 1 }
+            ),
+            Some(())
+        );
+        assert_eq!(slice, vec![2, 1]);
+        assert_eq!(
+            apply(
+                &mut slice,
+                &mut scratch,
+                &SortEvent::Move { from: 0, to: 4 }
+            ),
+            None
+        );
+        assert_eq!(
+            apply(&mut slice, &mut scratch, &SortEvent::Swap(0, 2)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_well_formed() {
+        assert!(SortEvent::Rotate {
+            range: 2..5,
+            mid: 2
+        }
+        .is_well_formed());
+        assert!(!SortEvent::Rotate {
+            range: 2..5,
+            mid: 6
+        }
+        .is_well_formed());
+        assert!(!SortEvent::Partition {
+            pivot: 5,
+            range: 2..5
+        }
+        .is_well_formed());
+    }
+}
@@ -0,0 +1,130 @@
+use observe::{
+    replay, write_frames, write_json_lines, NoopObserver, ReplayError, Shifted, SortEvent,
+    SortObserver, SortStats, Trace,
+};
+
+/// Records the indices of the swaps it observes.
+#[derive(Default)]
+struct Swaps(Vec<(usize, usize)>);
+
+impl SortObserver for Swaps {
+    fn on_swap(&mut self, i: usize, j: usize) {
+        self.0.push((i, j));
+    }
+}
+
+#[test]
+fn sort_stats_counts() {
+    let mut stats = SortStats::default();
+    stats.on_compare(0, 1);
+    stats.on_compare(1, 2);
+    stats.on_swap(0, 1);
+    stats.on_move(2, 0);
+    stats.on_recurse(1);
+    stats.on_recurse(2);
+    stats.on_recurse(1);
+
+    assert_eq!(
+        stats,
+        SortStats {
+            comparisons: 2,
+            swaps: 1,
+            moves: 1,
+            recursions: 3,
+            max_depth: 2,
+        }
+    );
+}
+
+#[test]
+fn shifted_offsets_indices() {
+    let mut swaps = Swaps::default();
+    let mut shifted = Shifted::new(&mut swaps, 10);
+    shifted.on_swap(0, 1);
+    Shifted::new(&mut shifted, 5).on_swap(2, 3);
+    shifted.on_compare(0, 1);
+
+    assert_eq!(swaps.0, vec![(10, 11), (17, 18)]);
+}
+
+#[test]
+fn noop_observer_ignores_everything() {
+    let mut noop = NoopObserver;
+    noop.on_compare(0, 1);
+    noop.on_swap(0, 1);
+    noop.on_move(0, 1);
+    noop.on_recurse(1);
+}
+
+#[test]
+fn trace_replays() {
+    let mut trace = Trace::new();
+    trace.on_compare(0, 2);
+    trace.on_swap(0, 2);
+    trace.on_rotate(0..3, 1);
+    trace.on_partition(1, 0..3);
+    assert_eq!(trace.events().len(), 4);
+
+    // [3, 2, 1] -> [1, 2, 3] -> [2, 3, 1]
+    assert_eq!(replay(&[3, 2, 1], &trace.events()[..2]), Ok(vec![1, 2, 3]));
+    assert_eq!(
+        replay(&[3, 2, 1], trace.events()),
+        Err(ReplayError::NotSorted { index: 2 })
+    );
+    assert_eq!(
+        replay(&[3, 2], trace.events()),
+        Err(ReplayError::InvalidEvent { event: 0 })
+    );
+}
+
+#[test]
+fn trace_replays_moves_through_scratch() {
+    let mut trace = Trace::new();
+    trace.on_move(0, 3);
+    trace.on_move(1, 0);
+    trace.on_move(3, 1);
+    assert_eq!(trace.events()[0], SortEvent::Move { from: 0, to: 3 });
+
+    assert_eq!(replay(&["b", "a"], trace.events()), Ok(vec!["a", "b"]));
+    assert_eq!(
+        replay(&["b", "a"], &trace.events()[1..]),
+        Err(ReplayError::InvalidEvent { event: 1 })
+    );
+}
+
+#[test]
+fn json_lines_export() {
+    let events = vec![
+        SortEvent::Swap(2, 0),
+        SortEvent::Move { from: 3, to: 1 },
+        SortEvent::Partition {
+            pivot: 1,
+            range: 0..3,
+        },
+    ];
+    let mut out = Vec::new();
+    write_json_lines(&events, &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "{\"event\":\"swap\",\"i\":2,\"j\":0}\n\
+         {\"event\":\"move\",\"from\":3,\"to\":1}\n\
+         {\"event\":\"partition\",\"pivot\":1,\"start\":0,\"end\":3}\n"
+    );
+}
+
+#[test]
+fn frames_export() {
+    let events = vec![SortEvent::Rotate {
+        range: 0..3,
+        mid: 2,
+    }];
+    let mut out = Vec::new();
+    write_frames(&["b", "c", "a"], &events, &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "#0 start\nb c a\n#1 rotate(0..3, 2)\na b c\n~ ~ <\n"
+    );
+
+    let err = write_frames(&[1], &[SortEvent::Swap(0, 1)], &mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
@@ -0,0 +1,251 @@
+//! Introspective quick sort.
+//!
+//! Quick sort with median-of-three (ninther for large partitions)
+//! pivot selection, which falls back to heap sort once the recursion
+//! gets deeper than 2*log2(n) and to sorting networks for small partitions.
+
+use std::cmp::Ordering;
+
+use observe::{NoopObserver, Shifted, SortObserver};
+
+use super::observed_partition;
+
+/// Partitions of more than this many elements use the ninther as pivot.
+const NINTHER_THRESHOLD: usize = 128;
+
+/// Sorts the slice within the specified interval [b, e)
+/// using introspective quick sort.
+///
+/// Recursion only ever goes into the smaller partition, the larger
+/// one is handled by the loop, so the stack depth is O(log(n)).
+pub(crate) fn internal_introsort<T, F>(
+    b: usize,
+    e: usize,
+    slice: &mut [T],
+    compare: &mut F,
+    depth_limit: usize,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    observed_introsort(b, e, slice, compare, &mut NoopObserver, depth_limit, 0);
+}
+
+/// Sorts the slice within the specified interval [b, e) like
+/// [`internal_introsort`], reporting the operations to the observer.
+///
+/// `depth` is the depth of the recursion reported to the observer.
+pub(crate) fn observed_introsort<T, F, O>(
+    mut b: usize,
+    mut e: usize,
+    slice: &mut [T],
+    compare: &mut F,
+    observer: &mut O,
+    mut depth_limit: usize,
+    depth: usize,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    loop {
+        if e - b <= insertion::MAX_NETWORK_LEN {
+            let mut shifted = Shifted::new(&mut *observer, b);
+            insertion::sort_small_by_observed(&mut slice[b..e], &mut *compare, &mut shifted);
+            return;
+        }
+
+        if depth_limit == 0 {
+            let mut shifted = Shifted::new(&mut *observer, b);
+            heap::sort_by_observed(&mut slice[b..e], &mut *compare, &mut shifted);
+            return;
+        }
+        depth_limit -= 1;
+
+        let p = observed_choose_pivot(b, e, slice, compare, observer);
+        let partition_index = observed_partition(p, b, e, slice, compare, observer);
+
+        observer.on_recurse(depth + 1);
+        if partition_index - b < e - (partition_index + 1) {
+            observed_introsort(
+                b,
+                partition_index,
+                slice,
+                compare,
+                observer,
+                depth_limit,
+                depth + 1,
+            );
+            b = partition_index + 1;
+        } else {
+            observed_introsort(
+                partition_index + 1,
+                e,
+                slice,
+                compare,
+                observer,
+                depth_limit,
+                depth + 1,
+            );
+            e = partition_index;
+        }
+    }
+}
+
+/// Returns the index of the pivot for the subslice [b, e).
+///
+/// Uses the median of the first, middle and last element,
+/// or the ninther for subslices longer than `NINTHER_THRESHOLD`.
+pub(crate) fn choose_pivot<T, F>(b: usize, e: usize, slice: &[T], compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    observed_choose_pivot(b, e, slice, compare, &mut NoopObserver)
+}
+
+/// Returns the index of the pivot for the subslice [b, e) like
+/// [`choose_pivot`], reporting the comparisons to the observer.
+fn observed_choose_pivot<T, F, O>(
+    b: usize,
+    e: usize,
+    slice: &[T],
+    compare: &mut F,
+    observer: &mut O,
+) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let len = e - b;
+    let mid = b + len / 2;
+    let last = e - 1;
+    let mut median = |a, b, c| median_of_three(a, b, c, slice, compare, observer);
+
+    if len > NINTHER_THRESHOLD {
+        let step = len / 8;
+        let lo = median(b, b + step, b + 2 * step);
+        let mi = median(mid - step, mid, mid + step);
+        let hi = median(last - 2 * step, last - step, last);
+        median(lo, mi, hi)
+    } else {
+        median(b, mid, last)
+    }
+}
+
+/// Returns the index of the median of the elements at indices `a`, `b` and `c`.
+fn median_of_three<T, F, O>(
+    mut a: usize,
+    mut b: usize,
+    mut c: usize,
+    slice: &[T],
+    compare: &mut F,
+    observer: &mut O,
+) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    observer.on_compare(b, a);
+    if compare(&slice[b], &slice[a]) == Ordering::Less {
+        std::mem::swap(&mut a, &mut b);
+    }
+    observer.on_compare(c, b);
+    if compare(&slice[c], &slice[b]) == Ordering::Less {
+        std::mem::swap(&mut b, &mut c);
+    }
+    observer.on_compare(b, a);
+    if compare(&slice[b], &slice[a]) == Ordering::Less {
+        std::mem::swap(&mut a, &mut b);
+    }
+    b
+}
+
+/// Returns the recursion depth budget of about 2*log2(len).
+pub(crate) fn depth_limit(len: usize) -> usize {
+    2 * (usize::BITS - len.leading_zeros()) as usize
+}
+
+/// Sorts the slice using introspective quick sort.
+///
+/// Runs in O(n log(n)) time at worst and uses O(log(n)) stack space.
+/// [`sort`](crate::sort) runs this sort as well.
+///
+/// # Examples
+///
+/// ```
+/// use quick::introsort;
+///
+/// let mut nmbrs = vec![4,3,2,5,1];
+/// introsort(&mut nmbrs);
+///
+/// assert_eq!(nmbrs, vec![1,2,3,4,5]);
+/// ```
+pub fn introsort<T>(slice: &mut [T])
+where
+    T: Ord,
+{
+    introsort_by(slice, T::cmp);
+}
+
+/// Sorts the slice using introspective quick sort with a comparator function.
+///
+/// # Examples
+///
+/// ```
+/// use quick::introsort_by;
+///
+/// let mut nmbrs = vec![1,3,2,5,4];
+/// introsort_by(&mut nmbrs, |a, b| b.cmp(a));
+///
+/// assert_eq!(nmbrs, vec![5,4,3,2,1]);
+/// ```
+pub fn introsort_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let limit = depth_limit(slice.len());
+    internal_introsort(0, slice.len(), slice, &mut compare, limit);
+}
+
+/// Sorts the slice using introspective quick sort with a key extraction function.
+///
+/// # Examples
+///
+/// ```
+/// use quick::introsort_by_key;
+///
+/// let mut nmbrs = vec![-4,3,-2,5,1];
+/// introsort_by_key(&mut nmbrs, |n: &i32| n.abs());
+///
+/// assert_eq!(nmbrs, vec![1,-2,3,-4,5]);
+/// ```
+pub fn introsort_by_key<T, K, F>(slice: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    introsort_by(slice, |a, b| f(a).cmp(&f(b)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_median_of_three() {
+        let nmbrs = vec![3, 1, 2];
+        assert_eq!(
+            median_of_three(0, 1, 2, &nmbrs, &mut i32::cmp, &mut NoopObserver),
+            2
+        );
+
+        let nmbrs = vec![1, 1, 1];
+        let median = median_of_three(0, 1, 2, &nmbrs, &mut i32::cmp, &mut NoopObserver);
+        assert_eq!(nmbrs[median], 1);
+    }
+
+    #[test]
+    fn test_internal_introsort_with_exhausted_depth() {
+        let mut nmbrs: Vec<i32> = (0..100).rev().collect();
+        internal_introsort(0, nmbrs.len(), &mut nmbrs, &mut i32::cmp, 0);
+        assert_eq!(nmbrs, (0..100).collect::<Vec<_>>());
+    }
+}
@@ -1,21 +1,60 @@
 //! # Quick
 //!
-//! `quick` provides sort capabilities using quick sort
-//! using O(n log(n)) on average and O(n^2) at worst time complexity
-//! and O(h) space complexity.
+//! `quick` provides sort capabilities using introspective quick sort
+//! using O(n log(n)) time complexity at worst and O(log(n)) space
+//! complexity, see [`sort`] and [`introsort`].
+//!
+//! The fastest unstable sort of the crate is a pattern-defeating quick
+//! sort with block partitioning, see [`pdqsort`]. It runs in O(n log(n))
+//! time at worst and in O(n) time for sorted and reversed slices,
+//! and is the default unstable sort of the workspace.
+//!
+//! Large slices can be sorted on multiple threads, see [`par_sort`].
+//!
+//! Slices with many duplicate elements are better sorted using three-way
+//! partitioning, see [`sort_with_options`] and [`Partition`].
+//!
+//! When only some of the elements are needed in order, see
+//! [`select_nth_unstable`], [`partial_sort`] and [`top_k`].
+//!
+//! The operations of the sorts can be observed, see [`sort_observed`]
+//! and [`pdqsort_observed`].
+//!
+//! Floats are sorted by the IEEE 754 total order, see [`sort_floats`].
+//!
+//! No comparison is made while an element is held out of the slice,
+//! so if the comparator panics the slice is left as a permutation of
+//! its elements.
+
+pub use intro::{introsort, introsort_by, introsort_by_key};
+pub use par::{par_sort, par_sort_by, par_sort_by_grain, DEFAULT_GRAIN_SIZE};
+pub use partition::{
+    sort_by_key_with_options, sort_by_with_options, sort_with_options, Partition, SortOptions,
+};
+pub use pdq::{pdqsort, pdqsort_by, pdqsort_by_key, pdqsort_by_observed, pdqsort_observed};
+pub use radix::{NanPlacement, TotalOrd};
+pub use select::{
+    partial_sort, partial_sort_by, partial_sort_by_key, select_nth_unstable,
+    select_nth_unstable_by, select_nth_unstable_by_key, top_k, top_k_by, top_k_by_key,
+};
+
+mod intro;
+mod par;
+mod partition;
+mod pdq;
+mod select;
+
+use std::cmp::Ordering;
+
+use observe::{NoopObserver, SortObserver};
 
 /// Sorts the slice within the specified intervar [b, e)
-/// using recursive quick sort.
-fn internal_quicksort<T>(b: usize, e: usize, slice: &mut [T])
+/// using introspective quick sort.
+fn internal_quicksort<T, F>(b: usize, e: usize, slice: &mut [T], compare: &mut F)
 where
-    T: Ord,
+    F: FnMut(&T, &T) -> Ordering,
 {
-    if e - b > 1 {
-        let p = b + (e - b) / 2;
-        let partition_index = internal_partition(p, b, e, slice);
-        internal_quicksort(b, partition_index, slice);
-        internal_quicksort(partition_index + 1, e, slice);
-    }
+    intro::internal_introsort(b, e, slice, compare, intro::depth_limit(e - b));
 }
 
 /// Partition the slice based on the pivot at index `p`,
@@ -28,10 +67,29 @@ where
 /// Indices `b` and `e` denotes the subslice which should be partitioned.
 ///
 /// The partition is done in place. Only constant memory is allocated.
-fn internal_partition<T>(mut p: usize, mut b: usize, mut e: usize, slice: &mut [T]) -> usize
+fn internal_partition<T, F>(p: usize, b: usize, e: usize, slice: &mut [T], compare: &mut F) -> usize
 where
-    T: Ord,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    observed_partition(p, b, e, slice, compare, &mut NoopObserver)
+}
+
+/// Partition the slice based on the pivot at index `p` like
+/// [`internal_partition`], reporting the operations to the observer.
+fn observed_partition<T, F, O>(
+    mut p: usize,
+    mut b: usize,
+    mut e: usize,
+    slice: &mut [T],
+    compare: &mut F,
+    observer: &mut O,
+) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
 {
+    let end = e;
+    observer.on_swap(p, b);
     slice.swap(p, b);
 
     p = b;
@@ -43,19 +101,23 @@ where
             break b;
         }
 
-        if &slice[b] <= &slice[p] {
+        observer.on_compare(b, p);
+        if compare(&slice[b], &slice[p]) != Ordering::Greater {
             b += 1;
         } else {
+            observer.on_swap(e, b);
             slice.swap(e, b);
             e -= 1;
         }
     };
 
+    observer.on_swap(p, partition_index - 1);
     slice.swap(p, partition_index - 1);
+    observer.on_partition(partition_index - 1, p..end);
     partition_index - 1
 }
 
-/// Sorts the slice using recursive quick sort.
+/// Sorts the slice using introspective quick sort.
 ///
 /// # Examples
 ///
@@ -70,7 +132,128 @@ pub fn sort<T>(slice: &mut [T])
 where
     T: Ord,
 {
-    internal_quicksort(0, slice.len(), slice);
+    sort_by(slice, T::cmp);
+}
+
+/// Sorts the slice using introspective quick sort with a comparator function.
+///
+/// The pivot is the median of three elements, or the ninther for long
+/// slices. Small partitions are sorted with sorting networks and once the
+/// recursion gets deeper than 2*log2(n) the partition is sorted with heap
+/// sort, so the sort runs in O(n log(n)) time at worst and uses O(log(n))
+/// stack space, see [`introsort`].
+///
+/// The sort is not stable, equal elements may be reordered.
+///
+/// # Examples
+///
+/// ```
+/// use quick::sort_by;
+///
+/// let mut nmbrs = vec![1,3,2,5,4];
+/// sort_by(&mut nmbrs, |a, b| b.cmp(a));
+///
+/// assert_eq!(nmbrs, vec![5,4,3,2,1]);
+/// ```
+pub fn sort_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    internal_quicksort(0, slice.len(), slice, &mut compare);
+}
+
+/// Sorts the slice using introspective quick sort with a key extraction function.
+///
+/// # Examples
+///
+/// ```
+/// use quick::sort_by_key;
+///
+/// let mut nmbrs = vec![-4,3,-2,5,1];
+/// sort_by_key(&mut nmbrs, |n: &i32| n.abs());
+///
+/// assert_eq!(nmbrs, vec![1,-2,3,-4,5]);
+/// ```
+pub fn sort_by_key<T, K, F>(slice: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    sort_by(slice, |a, b| f(a).cmp(&f(b)));
+}
+
+/// Sorts the slice of floats by the IEEE 754 total order using quick sort,
+/// with the NaNs grouped at the given end.
+///
+/// -0.0 goes before +0.0. The sort is not stable.
+///
+/// # Examples
+///
+/// ```
+/// use quick::{sort_floats, NanPlacement};
+///
+/// let mut floats = vec![2.5, f64::NAN, -0.0, 0.0, -1.0];
+/// sort_floats(&mut floats, NanPlacement::First);
+///
+/// assert!(floats[0].is_nan());
+/// assert_eq!(floats[1..], [-1.0, -0.0, 0.0, 2.5]);
+/// assert!(floats[2].is_sign_negative());
+/// ```
+pub fn sort_floats<F>(slice: &mut [F], nans: NanPlacement)
+where
+    F: TotalOrd,
+{
+    sort_by(slice, |a, b| a.total_cmp_with(b, nans));
+}
+
+/// Sorts the slice using introspective quick sort,
+/// reporting every comparison, swap, partition and recursion to the observer.
+///
+/// # Examples
+///
+/// ```
+/// use observe::SortStats;
+/// use quick::sort_observed;
+///
+/// let mut nmbrs = vec![4,3,2,5,1];
+/// let mut stats = SortStats::default();
+/// sort_observed(&mut nmbrs, &mut stats);
+///
+/// assert_eq!(nmbrs, vec![1,2,3,4,5]);
+/// assert!(stats.max_depth <= 4);
+/// ```
+pub fn sort_observed<T, O>(slice: &mut [T], observer: &mut O)
+where
+    T: Ord,
+    O: SortObserver,
+{
+    sort_by_observed(slice, T::cmp, observer);
+}
+
+/// Sorts the slice using introspective quick sort with a comparator function,
+/// reporting every comparison, swap, partition and recursion to the observer.
+///
+/// # Examples
+///
+/// ```
+/// use observe::SortStats;
+/// use quick::sort_by_observed;
+///
+/// let mut nmbrs = vec![1,3,2];
+/// let mut stats = SortStats::default();
+/// sort_by_observed(&mut nmbrs, |a, b| b.cmp(a), &mut stats);
+///
+/// assert_eq!(nmbrs, vec![3,2,1]);
+/// assert_eq!(stats.comparisons, 3);
+/// ```
+pub fn sort_by_observed<T, F, O>(slice: &mut [T], mut compare: F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let len = slice.len();
+    let limit = intro::depth_limit(len);
+    intro::observed_introsort(0, len, slice, &mut compare, observer, limit, 0);
 }
 
 #[cfg(test)]
@@ -80,14 +263,14 @@ mod tests {
     #[test]
     fn test_internal_quicksort() {
         let mut nmbrs = vec![1, 5, 2, 3, 4];
-        internal_quicksort(0, nmbrs.len(), &mut nmbrs);
+        internal_quicksort(0, nmbrs.len(), &mut nmbrs, &mut i32::cmp);
         assert_eq!(nmbrs, vec![1, 2, 3, 4, 5]);
     }
 
     #[test]
     fn test_internal_partition() {
         let mut nmbrs = vec![1, 5, 2, 3, 4];
-        internal_partition(3, 0, nmbrs.len(), &mut nmbrs);
+        internal_partition(3, 0, nmbrs.len(), &mut nmbrs, &mut i32::cmp);
         assert_eq!(nmbrs, vec![2, 1, 3, 4, 5]);
     }
 }
@@ -0,0 +1,118 @@
+//! Parallel quick sort.
+//!
+//! The slice is partitioned and the two partitions are sorted on scoped
+//! threads, splitting recursively while there are threads left and the
+//! partitions are larger than the grain size.
+
+use std::cmp::Ordering;
+use std::thread;
+
+use super::internal_partition;
+use super::intro::{choose_pivot, introsort_by};
+
+/// Default number of elements below which the parallel sort
+/// falls back to the sequential introsort.
+pub const DEFAULT_GRAIN_SIZE: usize = 1 << 14;
+
+/// Returns the number of threads the sort may split the work between.
+///
+/// Partitions are rarely of equal size, so the work is split
+/// into more parts than there are cores to keep all of them busy.
+fn available_threads() -> usize {
+    4 * thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Sorts the slice using parallel quick sort.
+///
+/// Partitions of at most `grain_size` elements, or once there are no
+/// threads left, are sorted with the sequential introsort.
+fn par_quicksort<T, F>(slice: &mut [T], grain_size: usize, threads: usize, compare: &F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    let mut compare_mut = |a: &T, b: &T| compare(a, b);
+    if slice.len() <= grain_size || threads <= 1 {
+        introsort_by(slice, compare_mut);
+        return;
+    }
+
+    let len = slice.len();
+    let p = choose_pivot(0, len, slice, &mut compare_mut);
+    let partition_index = internal_partition(p, 0, len, slice, &mut compare_mut);
+
+    let (left, right) = slice.split_at_mut(partition_index);
+    let right = &mut right[1..];
+    thread::scope(|s| {
+        s.spawn(|| par_quicksort(left, grain_size, threads / 2, compare));
+        par_quicksort(right, grain_size, threads - threads / 2, compare);
+    });
+}
+
+/// Sorts the slice using parallel quick sort.
+///
+/// # Examples
+///
+/// ```
+/// use quick::par_sort;
+///
+/// let mut nmbrs = vec![4,3,2,5,1];
+/// par_sort(&mut nmbrs);
+///
+/// assert_eq!(nmbrs, vec![1,2,3,4,5]);
+/// ```
+pub fn par_sort<T>(slice: &mut [T])
+where
+    T: Ord + Send,
+{
+    par_sort_by(slice, T::cmp);
+}
+
+/// Sorts the slice using parallel quick sort with a comparator function.
+///
+/// Slices of at most [`DEFAULT_GRAIN_SIZE`] elements are sorted
+/// sequentially, see [`par_sort_by_grain`] for choosing the grain size.
+///
+/// # Examples
+///
+/// ```
+/// use quick::par_sort_by;
+///
+/// let mut nmbrs = vec![1,3,2,5,4];
+/// par_sort_by(&mut nmbrs, |a, b| b.cmp(a));
+///
+/// assert_eq!(nmbrs, vec![5,4,3,2,1]);
+/// ```
+pub fn par_sort_by<T, F>(slice: &mut [T], compare: F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    par_sort_by_grain(slice, DEFAULT_GRAIN_SIZE, compare);
+}
+
+/// Sorts the slice using parallel quick sort with a comparator function.
+///
+/// The slice is partitioned and the partitions are sorted on separate
+/// threads until they have at most `grain_size` elements, these are
+/// sorted with the sequential introsort. No memory is allocated.
+///
+/// The sort is not stable, equal elements may be reordered.
+///
+/// # Examples
+///
+/// ```
+/// use quick::par_sort_by_grain;
+///
+/// let mut nmbrs: Vec<i32> = (0..1000).rev().collect();
+/// par_sort_by_grain(&mut nmbrs, 64, |a, b| a.cmp(b));
+///
+/// assert_eq!(nmbrs, (0..1000).collect::<Vec<_>>());
+/// ```
+pub fn par_sort_by_grain<T, F>(slice: &mut [T], grain_size: usize, compare: F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    par_quicksort(slice, grain_size.max(1), available_threads(), &compare);
+}
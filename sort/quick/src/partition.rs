@@ -0,0 +1,393 @@
+//! Quick sort with selectable partitioning schemes.
+//!
+//! Besides the partitioning used by [`sort`](crate::sort) the slice can be
+//! partitioned three ways (Dutch national flag), grouping the elements
+//! equal to the pivot in the middle, or around two pivots (Yaroslavskiy).
+//!
+//! Every scheme picks its pivots from a sample of the partition and sorts
+//! the partition with heap sort once the recursion gets deeper than
+//! 2*log2(n), like [`introsort`](crate::introsort), so none of them is
+//! quadratic at worst.
+
+use std::cmp::Ordering;
+
+use super::internal_quicksort;
+use super::intro::{choose_pivot, depth_limit};
+
+/// Partitions of at most this many elements are sorted with binary insertion sort.
+const INSERTION_THRESHOLD: usize = 16;
+
+/// The partitioning scheme used by quick sort.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Partition {
+    /// Elements <= the pivot go to the left, elements > go to the right.
+    #[default]
+    TwoWay,
+    /// Elements < the pivot go to the left, elements > go to the right
+    /// and elements equal to the pivot stay in the middle, they are not
+    /// recursed into. Suited for slices with many duplicate elements.
+    ThreeWay,
+    /// The slice is split into three parts by two pivots (Yaroslavskiy).
+    DualPivot,
+}
+
+/// Options of [`sort_with_options`].
+///
+/// # Examples
+///
+/// ```
+/// use quick::{Partition, SortOptions};
+///
+/// let options = SortOptions::default().partition(Partition::ThreeWay);
+///
+/// assert_eq!(options.partition, Partition::ThreeWay);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SortOptions {
+    /// The partitioning scheme, defaults to [`Partition::TwoWay`].
+    pub partition: Partition,
+}
+
+impl SortOptions {
+    /// Sets the partitioning scheme.
+    pub fn partition(mut self, partition: Partition) -> Self {
+        self.partition = partition;
+        self
+    }
+}
+
+/// Partitions the subslice [b, e) around the pivot at index `p`
+/// into elements < the pivot, elements equal to the pivot and
+/// elements > the pivot.
+///
+/// Returns the interval [lt, gt) of the elements equal to the pivot.
+///
+/// The partition is done in place. Only constant memory is allocated.
+pub(crate) fn partition_three_way<T, F>(
+    p: usize,
+    b: usize,
+    e: usize,
+    slice: &mut [T],
+    compare: &mut F,
+) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    slice.swap(p, b);
+
+    // [b, lt) < pivot, [lt, i) == pivot, [i, gt) unknown, [gt, e) > pivot.
+    let mut lt = b;
+    let mut i = b + 1;
+    let mut gt = e;
+
+    while i < gt {
+        match compare(&slice[i], &slice[lt]) {
+            Ordering::Less => {
+                slice.swap(lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                gt -= 1;
+                slice.swap(i, gt);
+            }
+            Ordering::Equal => i += 1,
+        }
+    }
+
+    (lt, gt)
+}
+
+/// Partitions the subslice [b, e), which must have at least 2 elements,
+/// around the two pivots at indices `p1` and `p2`.
+///
+/// Returns the final indices `(lt, gt)` of the pivots, such that the
+/// elements in [b, lt) are < the smaller pivot, the elements in (lt, gt)
+/// are between the pivots and the elements in (gt, e) are > the larger one.
+///
+/// The partition is done in place. Only constant memory is allocated.
+fn partition_dual_pivot<T, F>(
+    p1: usize,
+    p2: usize,
+    b: usize,
+    e: usize,
+    slice: &mut [T],
+    compare: &mut F,
+) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let last = e - 1;
+    slice.swap(p1, b);
+    slice.swap(if p2 == b { p1 } else { p2 }, last);
+    if compare(&slice[last], &slice[b]) == Ordering::Less {
+        slice.swap(b, last);
+    }
+
+    // [b + 1, lt) < p1, [lt, k) between, [k, gt] unknown, (gt, last) > p2.
+    let mut lt = b + 1;
+    let mut gt = last - 1;
+    let mut k = lt;
+
+    while k <= gt {
+        if compare(&slice[k], &slice[b]) == Ordering::Less {
+            slice.swap(k, lt);
+            lt += 1;
+        } else if compare(&slice[k], &slice[last]) == Ordering::Greater {
+            while k < gt && compare(&slice[gt], &slice[last]) == Ordering::Greater {
+                gt -= 1;
+            }
+            slice.swap(k, gt);
+            gt -= 1;
+            if compare(&slice[k], &slice[b]) == Ordering::Less {
+                slice.swap(k, lt);
+                lt += 1;
+            }
+        }
+        k += 1;
+    }
+
+    lt -= 1;
+    gt += 1;
+    slice.swap(b, lt);
+    slice.swap(last, gt);
+
+    (lt, gt)
+}
+
+/// Returns the indices of the two pivots for the subslice [b, e),
+/// which must have more than `INSERTION_THRESHOLD` elements.
+///
+/// Five elements spread around the middle are sorted in place, the
+/// second and the fourth of them are the pivots (Yaroslavskiy).
+fn choose_dual_pivots<T, F>(b: usize, e: usize, slice: &mut [T], compare: &mut F) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = e - b;
+    let seventh = len / 8 + len / 64 + 1;
+    let mid = b + len / 2;
+    let samples = [
+        mid - 2 * seventh,
+        mid - seventh,
+        mid,
+        mid + seventh,
+        mid + 2 * seventh,
+    ];
+
+    for i in 1..samples.len() {
+        let mut j = i;
+        while j > 0 && compare(&slice[samples[j]], &slice[samples[j - 1]]) == Ordering::Less {
+            slice.swap(samples[j], samples[j - 1]);
+            j -= 1;
+        }
+    }
+
+    (samples[1], samples[3])
+}
+
+/// Sorts the slice within the specified interval [b, e)
+/// using quick sort with three-way partitioning.
+///
+/// Once the recursion gets deeper than `depth_limit` the partition
+/// is sorted with heap sort instead.
+///
+/// Recursion only ever goes into the smaller partition, the larger
+/// one is handled by the loop, so the stack depth is O(log(n)).
+fn internal_quicksort_three_way<T, F>(
+    mut b: usize,
+    mut e: usize,
+    slice: &mut [T],
+    compare: &mut F,
+    mut depth_limit: usize,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    while e - b > INSERTION_THRESHOLD {
+        if depth_limit == 0 {
+            heap::sort_by(&mut slice[b..e], &mut *compare);
+            return;
+        }
+        depth_limit -= 1;
+
+        let p = choose_pivot(b, e, slice, compare);
+        let (lt, gt) = partition_three_way(p, b, e, slice, compare);
+
+        if lt - b < e - gt {
+            internal_quicksort_three_way(b, lt, slice, compare, depth_limit);
+            b = gt;
+        } else {
+            internal_quicksort_three_way(gt, e, slice, compare, depth_limit);
+            e = lt;
+        }
+    }
+
+    insertion::binary_sort_by(&mut slice[b..e], &mut *compare);
+}
+
+/// Sorts the slice within the specified interval [b, e)
+/// using dual-pivot quick sort.
+///
+/// Once the recursion gets deeper than `depth_limit` the partition
+/// is sorted with heap sort instead.
+///
+/// The two smaller partitions are recursed into, the largest one
+/// is handled by the loop, so the stack depth is O(log(n)).
+fn internal_quicksort_dual_pivot<T, F>(
+    mut b: usize,
+    mut e: usize,
+    slice: &mut [T],
+    compare: &mut F,
+    mut depth_limit: usize,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    while e - b > INSERTION_THRESHOLD {
+        if depth_limit == 0 {
+            heap::sort_by(&mut slice[b..e], &mut *compare);
+            return;
+        }
+        depth_limit -= 1;
+
+        let (p1, p2) = choose_dual_pivots(b, e, slice, compare);
+        let (lt, gt) = partition_dual_pivot(p1, p2, b, e, slice, compare);
+
+        // With equal pivots the middle partition only holds elements
+        // equal to them, it is already sorted.
+        let equal_pivots = compare(&slice[lt], &slice[gt]) == Ordering::Equal;
+        let mut parts = [(b, lt), (lt + 1, gt), (gt + 1, e)];
+        if equal_pivots {
+            parts[1] = (gt, gt);
+        }
+        parts.sort_by_key(|&(b, e)| e - b);
+
+        internal_quicksort_dual_pivot(parts[0].0, parts[0].1, slice, compare, depth_limit);
+        internal_quicksort_dual_pivot(parts[1].0, parts[1].1, slice, compare, depth_limit);
+        b = parts[2].0;
+        e = parts[2].1;
+    }
+
+    insertion::binary_sort_by(&mut slice[b..e], &mut *compare);
+}
+
+/// Sorts the slice using quick sort with the given options.
+///
+/// # Examples
+///
+/// ```
+/// use quick::{sort_with_options, Partition, SortOptions};
+///
+/// let mut nmbrs = vec![4,3,2,5,1,3];
+/// sort_with_options(&mut nmbrs, SortOptions::default().partition(Partition::ThreeWay));
+///
+/// assert_eq!(nmbrs, vec![1,2,3,3,4,5]);
+/// ```
+pub fn sort_with_options<T>(slice: &mut [T], options: SortOptions)
+where
+    T: Ord,
+{
+    sort_by_with_options(slice, options, T::cmp);
+}
+
+/// Sorts the slice using quick sort with the given options
+/// and a comparator function.
+///
+/// The sort is not stable, equal elements may be reordered.
+///
+/// # Examples
+///
+/// ```
+/// use quick::{sort_by_with_options, Partition, SortOptions};
+///
+/// let mut nmbrs = vec![1,3,2,5,4];
+/// let options = SortOptions::default().partition(Partition::DualPivot);
+/// sort_by_with_options(&mut nmbrs, options, |a, b| b.cmp(a));
+///
+/// assert_eq!(nmbrs, vec![5,4,3,2,1]);
+/// ```
+pub fn sort_by_with_options<T, F>(slice: &mut [T], options: SortOptions, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = slice.len();
+    let limit = depth_limit(len);
+    match options.partition {
+        Partition::TwoWay => internal_quicksort(0, len, slice, &mut compare),
+        Partition::ThreeWay => internal_quicksort_three_way(0, len, slice, &mut compare, limit),
+        Partition::DualPivot => internal_quicksort_dual_pivot(0, len, slice, &mut compare, limit),
+    }
+}
+
+/// Sorts the slice using quick sort with the given options
+/// and a key extraction function.
+///
+/// # Examples
+///
+/// ```
+/// use quick::{sort_by_key_with_options, Partition, SortOptions};
+///
+/// let mut nmbrs = vec![-4,3,-2,5,1];
+/// let options = SortOptions::default().partition(Partition::ThreeWay);
+/// sort_by_key_with_options(&mut nmbrs, options, |n: &i32| n.abs());
+///
+/// assert_eq!(nmbrs, vec![1,-2,3,-4,5]);
+/// ```
+pub fn sort_by_key_with_options<T, K, F>(slice: &mut [T], options: SortOptions, mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    sort_by_with_options(slice, options, |a, b| f(a).cmp(&f(b)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_three_way() {
+        let mut nmbrs = vec![3, 1, 3, 5, 2, 3, 4];
+        let (lt, gt) = partition_three_way(2, 0, nmbrs.len(), &mut nmbrs, &mut i32::cmp);
+        assert_eq!((lt, gt), (2, 5));
+        assert!(nmbrs[..lt].iter().all(|&n| n < 3));
+        assert!(nmbrs[lt..gt].iter().all(|&n| n == 3));
+        assert!(nmbrs[gt..].iter().all(|&n| n > 3));
+    }
+
+    #[test]
+    fn test_partition_dual_pivot() {
+        let mut nmbrs = vec![6, 1, 8, 2, 7, 4, 3, 5];
+        let (lt, gt) = partition_dual_pivot(1, 5, 0, nmbrs.len(), &mut nmbrs, &mut i32::cmp);
+        assert_eq!((nmbrs[lt], nmbrs[gt]), (1, 4));
+        assert!(nmbrs[..lt].iter().all(|&n| n < 1));
+        assert!(nmbrs[lt + 1..gt].iter().all(|&n| (1..=4).contains(&n)));
+        assert!(nmbrs[gt + 1..].iter().all(|&n| n > 4));
+    }
+
+    #[test]
+    fn test_internal_quicksort_dual_pivot_with_equal_pivots() {
+        let mut nmbrs: Vec<i32> = (0..100).map(|i| i % 2).collect();
+        internal_quicksort_dual_pivot(0, nmbrs.len(), &mut nmbrs, &mut i32::cmp, 100);
+        assert_eq!(nmbrs, (0..100).map(|i| i / 50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_choose_dual_pivots() {
+        let mut nmbrs: Vec<i32> = (0..100).rev().collect();
+        let (p1, p2) = choose_dual_pivots(0, nmbrs.len(), &mut nmbrs, &mut i32::cmp);
+        assert!(p1 < p2);
+        assert!(nmbrs[p1] < nmbrs[p2]);
+        assert!(nmbrs[p1] > 0 && nmbrs[p2] < 99);
+    }
+
+    #[test]
+    fn test_internal_quicksort_with_exhausted_depth() {
+        let mut nmbrs: Vec<i32> = (0..100).rev().collect();
+        internal_quicksort_three_way(0, nmbrs.len(), &mut nmbrs, &mut i32::cmp, 0);
+        assert_eq!(nmbrs, (0..100).collect::<Vec<_>>());
+
+        let mut nmbrs: Vec<i32> = (0..100).rev().collect();
+        internal_quicksort_dual_pivot(0, nmbrs.len(), &mut nmbrs, &mut i32::cmp, 0);
+        assert_eq!(nmbrs, (0..100).collect::<Vec<_>>());
+    }
+}
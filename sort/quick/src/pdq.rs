@@ -0,0 +1,757 @@
+//! Pattern-defeating quick sort.
+//!
+//! Quick sort with block partitioning, which compares a block of elements
+//! against the pivot without branching on the results, before moving the
+//! misplaced ones with a cyclic permutation. Partitions which need no swaps
+//! are finished with a partial insertion sort, so sorted and reversed
+//! inputs take O(n) time. Unbalanced partitions shuffle a few elements
+//! to break up patterns, and once there were too many of them the
+//! partition is sorted with heap sort instead.
+//!
+//! Based on "Pattern-defeating Quicksort" by Orson Peters and
+//! "BlockQuicksort" by Stefan Edelkamp and Armin Weiß.
+
+use std::cmp::Ordering;
+use std::mem;
+use std::ptr;
+
+use observe::{NoopObserver, Shifted, SortObserver};
+
+/// Partitions of at least this many elements use the ninther as pivot.
+const NINTHER_THRESHOLD: usize = 50;
+
+/// Above this many swaps while choosing the pivot,
+/// the partition is assumed to be descending and is reversed.
+const MAX_PIVOT_SWAPS: usize = 4 * 3;
+
+/// The number of adjacent out of order pairs the partial
+/// insertion sort fixes before it gives up.
+const MAX_INSERTION_STEPS: usize = 5;
+
+/// Partitions shorter than this are not partially insertion sorted.
+const SHORTEST_SHIFTING: usize = 50;
+
+/// The number of elements compared against the pivot at once.
+/// Offsets within a block must fit into a `u8`.
+const BLOCK: usize = 128;
+
+/// Returns whether `a` is less than `b`.
+fn is_less<T, F>(a: &T, b: &T, compare: &mut F) -> bool
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    compare(a, b) == Ordering::Less
+}
+
+/// Partitions the slice into the elements less than the pivot followed
+/// by the elements not less than it, returns the number of the former.
+///
+/// Both ends are scanned a block at a time. The offsets of the misplaced
+/// elements of a block are recorded without branching, then as many of them
+/// as possible are moved from one end to the other by a cyclic permutation.
+///
+/// The observer sees the pivot at index 0 and the slice from index
+/// `offset`, the element held out of the slice is moved to index `scratch`.
+fn partition_in_blocks<T, F, O>(
+    v: &mut [T],
+    pivot: &T,
+    compare: &mut F,
+    observer: &mut O,
+    (offset, scratch): (usize, usize),
+) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    // [l, r) holds the elements yet to be partitioned.
+    let mut l = 0;
+    let mut r = v.len();
+
+    // The offsets from `l` of the elements not less than the pivot,
+    // those within [start_l, end_l) are still to be moved.
+    let mut offsets_l = [0u8; BLOCK];
+    let mut block_l = BLOCK;
+    let (mut start_l, mut end_l) = (0, 0);
+
+    // The offsets from `r - 1`, towards the start, of the elements less
+    // than the pivot, those within [start_r, end_r) are still to be moved.
+    let mut offsets_r = [0u8; BLOCK];
+    let mut block_r = BLOCK;
+    let (mut start_r, mut end_r) = (0, 0);
+
+    loop {
+        let is_done = r - l <= 2 * BLOCK;
+
+        if is_done {
+            // Size the last blocks to cover what's left between them.
+            // A block with offsets still to be moved spans a full `BLOCK`.
+            let mut rem = r - l;
+            if start_l < end_l || start_r < end_r {
+                rem -= BLOCK;
+            }
+
+            if start_l < end_l {
+                block_r = rem;
+            } else if start_r < end_r {
+                block_l = rem;
+            } else {
+                block_l = rem / 2;
+                block_r = rem - block_l;
+            }
+        }
+
+        if start_l == end_l {
+            start_l = 0;
+            end_l = 0;
+            for i in 0..block_l {
+                offsets_l[end_l] = i as u8;
+                observer.on_compare(offset + l + i, 0);
+                end_l += !is_less(&v[l + i], pivot, compare) as usize;
+            }
+        }
+
+        if start_r == end_r {
+            start_r = 0;
+            end_r = 0;
+            for i in 0..block_r {
+                offsets_r[end_r] = i as u8;
+                observer.on_compare(offset + r - 1 - i, 0);
+                end_r += is_less(&v[r - 1 - i], pivot, compare) as usize;
+            }
+        }
+
+        let count = (end_l - start_l).min(end_r - start_r);
+        if count > 0 {
+            let left = |k: usize| l + offsets_l[k] as usize;
+            let right = |k: usize| r - 1 - offsets_r[k] as usize;
+            let (first_l, first_r) = (start_l, start_r);
+
+            // Instead of swapping the pairs, one element is held out and the
+            // others are moved along the cycle left, right, left, ...
+            // No comparison is made meanwhile, so nothing can panic with
+            // the element held out.
+            //
+            // SAFETY: the left indices are within [l, l + block_l) and the
+            // right ones within [r - block_r, r), which don't overlap. Every
+            // position is read once before it's written, the held out
+            // element is written back to the last one.
+            unsafe {
+                let base = v.as_mut_ptr();
+                let tmp = ptr::read(base.add(left(start_l)));
+                ptr::copy_nonoverlapping(base.add(right(start_r)), base.add(left(start_l)), 1);
+
+                for _ in 1..count {
+                    start_l += 1;
+                    ptr::copy_nonoverlapping(base.add(left(start_l)), base.add(right(start_r)), 1);
+                    start_r += 1;
+                    ptr::copy_nonoverlapping(base.add(right(start_r)), base.add(left(start_l)), 1);
+                }
+
+                ptr::write(base.add(right(start_r)), tmp);
+            }
+
+            // Reported once every element is back in the slice,
+            // so a panicking observer can't duplicate one.
+            observer.on_move(offset + left(first_l), scratch);
+            observer.on_move(offset + right(first_r), offset + left(first_l));
+            for k in 1..count {
+                observer.on_move(offset + left(first_l + k), offset + right(first_r + k - 1));
+                observer.on_move(offset + right(first_r + k), offset + left(first_l + k));
+            }
+            observer.on_move(scratch, offset + right(start_r));
+
+            start_l += 1;
+            start_r += 1;
+        }
+
+        if start_l == end_l {
+            l += block_l;
+        }
+        if start_r == end_r {
+            r -= block_r;
+        }
+
+        if is_done {
+            break;
+        }
+    }
+
+    // At most one block has offsets left, its misplaced elements
+    // are swapped to the other end of what's left between them.
+    if start_l < end_l {
+        while start_l < end_l {
+            end_l -= 1;
+            v.swap(l + offsets_l[end_l] as usize, r - 1);
+            observer.on_swap(offset + l + offsets_l[end_l] as usize, offset + r - 1);
+            r -= 1;
+        }
+        r
+    } else {
+        while start_r < end_r {
+            end_r -= 1;
+            v.swap(l, r - 1 - offsets_r[end_r] as usize);
+            observer.on_swap(offset + l, offset + r - 1 - offsets_r[end_r] as usize);
+            l += 1;
+        }
+        l
+    }
+}
+
+/// Partitions the slice around the pivot at index `p`, such that the
+/// elements less than it are to the left of it and the others to the right.
+///
+/// Returns the final index of the pivot and whether
+/// the slice was already partitioned.
+fn partition<T, F, O>(
+    v: &mut [T],
+    p: usize,
+    compare: &mut F,
+    observer: &mut O,
+    scratch: usize,
+) -> (usize, bool)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    v.swap(0, p);
+    observer.on_swap(0, p);
+    let (mid, was_partitioned) = {
+        let (pivot, rest) = v.split_at_mut(1);
+        let pivot = &pivot[0];
+
+        // Skip the elements which are already in place on either end.
+        let mut l = 0;
+        let mut r = rest.len();
+        while l < r && {
+            observer.on_compare(l + 1, 0);
+            is_less(&rest[l], pivot, compare)
+        } {
+            l += 1;
+        }
+        while l < r && {
+            observer.on_compare(r, 0);
+            !is_less(&rest[r - 1], pivot, compare)
+        } {
+            r -= 1;
+        }
+
+        let was_partitioned = l >= r;
+        let mid = partition_in_blocks(&mut rest[l..r], pivot, compare, observer, (1 + l, scratch));
+        (l + mid, was_partitioned)
+    };
+
+    v.swap(0, mid);
+    observer.on_swap(0, mid);
+    (mid, was_partitioned)
+}
+
+/// Partitions the slice into the elements equal to the pivot at index `p`
+/// followed by the elements greater than it, returns the number of the former.
+///
+/// No element of the slice may be less than the pivot.
+fn partition_equal<T, F, O>(v: &mut [T], p: usize, compare: &mut F, observer: &mut O) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    v.swap(0, p);
+    observer.on_swap(0, p);
+    let (pivot, rest) = v.split_at_mut(1);
+    let pivot = &pivot[0];
+
+    let mut l = 0;
+    let mut r = rest.len();
+    loop {
+        while l < r && {
+            observer.on_compare(0, l + 1);
+            !is_less(pivot, &rest[l], compare)
+        } {
+            l += 1;
+        }
+        while l < r && {
+            observer.on_compare(0, r);
+            is_less(pivot, &rest[r - 1], compare)
+        } {
+            r -= 1;
+        }
+        if l >= r {
+            break;
+        }
+
+        r -= 1;
+        rest.swap(l, r);
+        observer.on_swap(l + 1, r + 1);
+        l += 1;
+    }
+
+    l + 1
+}
+
+/// Sorts the slice if at most `MAX_INSERTION_STEPS` adjacent pairs
+/// are out of order, returns whether it's sorted.
+fn partial_insertion_sort<T, F, O>(v: &mut [T], compare: &mut F, observer: &mut O) -> bool
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let len = v.len();
+    let mut i = 1;
+
+    for _ in 0..MAX_INSERTION_STEPS {
+        while i < len && {
+            observer.on_compare(i, i - 1);
+            !is_less(&v[i], &v[i - 1], compare)
+        } {
+            i += 1;
+        }
+        if i == len {
+            return true;
+        }
+        if len < SHORTEST_SHIFTING {
+            return false;
+        }
+
+        // Swap the pair and shift both elements into place.
+        v.swap(i - 1, i);
+        observer.on_swap(i - 1, i);
+        let mut j = i - 1;
+        while j > 0 && {
+            observer.on_compare(j, j - 1);
+            is_less(&v[j], &v[j - 1], compare)
+        } {
+            v.swap(j, j - 1);
+            observer.on_swap(j, j - 1);
+            j -= 1;
+        }
+        let mut j = i;
+        while j + 1 < len && {
+            observer.on_compare(j + 1, j);
+            is_less(&v[j + 1], &v[j], compare)
+        } {
+            v.swap(j, j + 1);
+            observer.on_swap(j, j + 1);
+            j += 1;
+        }
+    }
+
+    false
+}
+
+/// Swaps three elements around the middle of the slice with elements
+/// at pseudo-random positions, seeded by the length of the slice,
+/// to break up patterns which make the pivots unbalanced.
+fn break_patterns<T, O>(v: &mut [T], observer: &mut O)
+where
+    O: SortObserver,
+{
+    let len = v.len();
+    if len < 8 {
+        return;
+    }
+
+    // Xorshift, deterministic so the sorts are reproducible.
+    let mut random = len as u64;
+    let mut next = || {
+        random ^= random << 13;
+        random ^= random >> 7;
+        random ^= random << 17;
+        random as usize
+    };
+
+    let modulus = len.next_power_of_two();
+    let pos = len / 4 * 2;
+    for i in 0..3 {
+        let mut other = next() & (modulus - 1);
+        if other >= len {
+            other -= len;
+        }
+        v.swap(pos - 1 + i, other);
+        observer.on_swap(pos - 1 + i, other);
+    }
+}
+
+/// Returns the index of the pivot and whether the slice is likely sorted.
+///
+/// Uses the median of three elements at the quarters of the slice, or the
+/// ninther of them and their neighbours for long slices. If the elements
+/// looked descending while choosing, the slice is reversed.
+fn choose_pivot<T, F, O>(v: &mut [T], compare: &mut F, observer: &mut O) -> (usize, bool)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let len = v.len();
+    let mut a = len / 4;
+    let mut b = len / 4 * 2;
+    let mut c = len / 4 * 3;
+    let mut swaps = 0;
+
+    if len >= 8 {
+        let mut sort2 = |a: &mut usize, b: &mut usize| {
+            observer.on_compare(*b, *a);
+            if is_less(&v[*b], &v[*a], compare) {
+                mem::swap(a, b);
+                swaps += 1;
+            }
+        };
+        let mut sort3 = |a: &mut usize, b: &mut usize, c: &mut usize| {
+            sort2(a, b);
+            sort2(b, c);
+            sort2(a, b);
+        };
+
+        if len >= NINTHER_THRESHOLD {
+            let mut sort_adjacent = |a: &mut usize| {
+                let (mut prev, mut next) = (*a - 1, *a + 1);
+                sort3(&mut prev, a, &mut next);
+            };
+            sort_adjacent(&mut a);
+            sort_adjacent(&mut b);
+            sort_adjacent(&mut c);
+        }
+
+        sort3(&mut a, &mut b, &mut c);
+    }
+
+    if swaps < MAX_PIVOT_SWAPS {
+        (b, swaps == 0)
+    } else {
+        v.reverse();
+        for i in 0..len / 2 {
+            observer.on_swap(i, len - 1 - i);
+        }
+        (len - 1 - b, true)
+    }
+}
+
+/// Sorts the slice using pattern-defeating quick sort,
+/// reporting the operations to the observer.
+///
+/// `pred` is an element before the slice which is not greater than any of
+/// it, if there is one. `limit` is the number of unbalanced partitions
+/// allowed before switching to heap sort. `at` is the index of the slice
+/// within the whole slice of length `len`, which the observer sees.
+///
+/// Recursion only ever goes into the smaller partition, the larger
+/// one is handled by the loop, so the stack depth is O(log(n)).
+fn internal_pdqsort<'a, T, F, O>(
+    mut v: &'a mut [T],
+    compare: &mut F,
+    mut pred: Option<&'a T>,
+    mut limit: usize,
+    observer: &mut O,
+    (mut at, len): (usize, usize),
+    depth: usize,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let mut was_balanced = true;
+    let mut was_partitioned = true;
+
+    loop {
+        let n = v.len();
+        if n <= insertion::MAX_NETWORK_LEN {
+            insertion::sort_small_by_observed(
+                v,
+                &mut *compare,
+                &mut Shifted::new(&mut *observer, at),
+            );
+            return;
+        }
+
+        if limit == 0 {
+            heap::sort_by_observed(v, &mut *compare, &mut Shifted::new(&mut *observer, at));
+            return;
+        }
+
+        if !was_balanced {
+            break_patterns(v, &mut Shifted::new(&mut *observer, at));
+            limit -= 1;
+        }
+
+        let (p, likely_sorted) = choose_pivot(v, compare, &mut Shifted::new(&mut *observer, at));
+
+        // The last partition needed no swaps and neither did choosing
+        // the pivot, the slice may well be sorted already.
+        if was_balanced
+            && was_partitioned
+            && likely_sorted
+            && partial_insertion_sort(v, compare, &mut Shifted::new(&mut *observer, at))
+        {
+            return;
+        }
+
+        // The pivot equals the predecessor, which is the smallest element,
+        // so the elements equal to it are in place and can be skipped.
+        if let Some(predecessor) = pred {
+            observer.on_compare(at - 1, at + p);
+            if !is_less(predecessor, &v[p], compare) {
+                let mid = partition_equal(v, p, compare, &mut Shifted::new(&mut *observer, at));
+                // The last of the equal elements stays the predecessor,
+                // so the predecessor is always just before the slice.
+                let (equal, rest) = v.split_at_mut(mid);
+                pred = Some(&equal[mid - 1]);
+                v = rest;
+                at += mid;
+                continue;
+            }
+        }
+
+        let (mid, partitioned) = partition(
+            v,
+            p,
+            compare,
+            &mut Shifted::new(&mut *observer, at),
+            len - at,
+        );
+        observer.on_partition(at + mid, at..at + n);
+        was_balanced = mid.min(n - mid) >= n / 8;
+        was_partitioned = partitioned;
+
+        let (left, right) = v.split_at_mut(mid);
+        let (pivot, right) = right.split_at_mut(1);
+        let pivot = &pivot[0];
+
+        observer.on_recurse(depth + 1);
+        if left.len() < right.len() {
+            internal_pdqsort(left, compare, pred, limit, observer, (at, len), depth + 1);
+            v = right;
+            pred = Some(pivot);
+            at += mid + 1;
+        } else {
+            let right_at = at + mid + 1;
+            internal_pdqsort(
+                right,
+                compare,
+                Some(pivot),
+                limit,
+                observer,
+                (right_at, len),
+                depth + 1,
+            );
+            v = left;
+        }
+    }
+}
+
+/// Sorts the slice using pattern-defeating quick sort.
+///
+/// See [`pdqsort_by`].
+///
+/// # Examples
+///
+/// ```
+/// use quick::pdqsort;
+///
+/// let mut nmbrs = vec![4,3,2,5,1];
+/// pdqsort(&mut nmbrs);
+///
+/// assert_eq!(nmbrs, vec![1,2,3,4,5]);
+/// ```
+pub fn pdqsort<T>(slice: &mut [T])
+where
+    T: Ord,
+{
+    pdqsort_by(slice, T::cmp);
+}
+
+/// Sorts the slice using pattern-defeating quick sort with a comparator function.
+///
+/// Runs in O(n log(n)) time at worst, O(n) for sorted, reversed and
+/// all equal slices, and uses O(log(n)) stack space. Unlike
+/// [`sort`](crate::sort) it doesn't degrade on many duplicates or
+/// on patterns and is the fastest unstable sort of the crate.
+///
+/// The sort is not stable, equal elements may be reordered.
+///
+/// # Examples
+///
+/// ```
+/// use quick::pdqsort_by;
+///
+/// let mut nmbrs = vec![1,3,2,5,4];
+/// pdqsort_by(&mut nmbrs, |a, b| b.cmp(a));
+///
+/// assert_eq!(nmbrs, vec![5,4,3,2,1]);
+/// ```
+pub fn pdqsort_by<T, F>(slice: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    pdqsort_by_observed(slice, compare, &mut NoopObserver);
+}
+
+/// Sorts the slice using pattern-defeating quick sort with a key extraction function.
+///
+/// # Examples
+///
+/// ```
+/// use quick::pdqsort_by_key;
+///
+/// let mut nmbrs = vec![-4,3,-2,5,1];
+/// pdqsort_by_key(&mut nmbrs, |n: &i32| n.abs());
+///
+/// assert_eq!(nmbrs, vec![1,-2,3,-4,5]);
+/// ```
+pub fn pdqsort_by_key<T, K, F>(slice: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    pdqsort_by(slice, |a, b| f(a).cmp(&f(b)));
+}
+
+/// Sorts the slice using pattern-defeating quick sort, reporting
+/// every comparison, swap, move, partition and recursion to the observer.
+///
+/// The element held out of the slice while partitioning is reported
+/// as moved to the index just past the end of the slice.
+///
+/// # Examples
+///
+/// ```
+/// use observe::SortStats;
+/// use quick::pdqsort_observed;
+///
+/// let mut nmbrs: Vec<u32> = (0..100).rev().collect();
+/// let mut stats = SortStats::default();
+/// pdqsort_observed(&mut nmbrs, &mut stats);
+///
+/// assert_eq!(nmbrs, (0..100).collect::<Vec<_>>());
+/// assert_eq!(stats.max_depth, 0);
+/// ```
+pub fn pdqsort_observed<T, O>(slice: &mut [T], observer: &mut O)
+where
+    T: Ord,
+    O: SortObserver,
+{
+    pdqsort_by_observed(slice, T::cmp, observer);
+}
+
+/// Sorts the slice using pattern-defeating quick sort with a comparator
+/// function, reporting every comparison, swap, move, partition and
+/// recursion to the observer.
+///
+/// See [`pdqsort_observed`].
+///
+/// # Examples
+///
+/// ```
+/// use observe::SortStats;
+/// use quick::pdqsort_by_observed;
+///
+/// let mut nmbrs = vec![1,3,2];
+/// let mut stats = SortStats::default();
+/// pdqsort_by_observed(&mut nmbrs, |a, b| b.cmp(a), &mut stats);
+///
+/// assert_eq!(nmbrs, vec![3,2,1]);
+/// assert_eq!(stats.comparisons, 3);
+/// ```
+pub fn pdqsort_by_observed<T, F, O>(slice: &mut [T], mut compare: F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: SortObserver,
+{
+    let len = slice.len();
+    let limit = (usize::BITS - len.leading_zeros()) as usize;
+    internal_pdqsort(slice, &mut compare, None, limit, observer, (0, len), 0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_partitioned(v: &[u32], mid: usize, pivot: u32) -> bool {
+        v[..mid].iter().all(|&x| x < pivot) && v[mid..].iter().all(|&x| x >= pivot)
+    }
+
+    #[test]
+    fn test_partition_in_blocks() {
+        for &len in &[0, 1, 2, 100, 2 * BLOCK, 2 * BLOCK + 1, 1000, 5000] {
+            for &modulus in &[2, 10, 1000] {
+                let mut v: Vec<u32> = (0..len as u32)
+                    .map(|i| i.wrapping_mul(2_654_435_761) % modulus)
+                    .collect();
+                let pivot = modulus / 2;
+                let mut sorted = v.clone();
+
+                let mid =
+                    partition_in_blocks(&mut v, &pivot, &mut u32::cmp, &mut NoopObserver, (0, len));
+                assert!(is_partitioned(&v, mid, pivot), "{} {}", len, modulus);
+
+                v.sort_unstable();
+                sorted.sort_unstable();
+                assert_eq!(v, sorted);
+            }
+        }
+    }
+
+    #[test]
+    fn test_partition() {
+        let mut v: Vec<u32> = (0..1000).rev().collect();
+        let (mid, was_partitioned) = partition(&mut v, 500, &mut u32::cmp, &mut NoopObserver, 1000);
+        assert_eq!((mid, v[mid]), (499, 499));
+        assert!(is_partitioned(&v, mid, 499));
+        assert!(!was_partitioned);
+
+        let mut v: Vec<u32> = (0..1000).collect();
+        assert_eq!(
+            partition(&mut v, 500, &mut u32::cmp, &mut NoopObserver, 1000),
+            (500, true)
+        );
+    }
+
+    #[test]
+    fn test_partition_equal() {
+        let mut v = vec![3, 5, 3, 4, 3, 9, 3];
+        let mid = partition_equal(&mut v, 2, &mut i32::cmp, &mut NoopObserver);
+        assert_eq!(mid, 4);
+        assert_eq!(v[..mid], [3, 3, 3, 3]);
+    }
+
+    #[test]
+    fn test_partial_insertion_sort() {
+        let mut v: Vec<u32> = (0..100).collect();
+        v.swap(10, 11);
+        v.swap(50, 80);
+        assert!(partial_insertion_sort(
+            &mut v,
+            &mut u32::cmp,
+            &mut NoopObserver
+        ));
+        assert_eq!(v, (0..100).collect::<Vec<_>>());
+
+        let mut v: Vec<u32> = (0..100).rev().collect();
+        assert!(!partial_insertion_sort(
+            &mut v,
+            &mut u32::cmp,
+            &mut NoopObserver
+        ));
+    }
+
+    #[test]
+    fn test_break_patterns_is_deterministic() {
+        let mut a: Vec<u32> = (0..100).collect();
+        let mut b = a.clone();
+        break_patterns(&mut a, &mut NoopObserver);
+        break_patterns(&mut b, &mut NoopObserver);
+        assert_eq!(a, b);
+        assert_ne!(a, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_internal_pdqsort_with_exhausted_limit() {
+        let mut nmbrs: Vec<i32> = (0..100).rev().collect();
+        internal_pdqsort(
+            &mut nmbrs,
+            &mut i32::cmp,
+            None,
+            0,
+            &mut NoopObserver,
+            (0, 100),
+            0,
+        );
+        assert_eq!(nmbrs, (0..100).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,376 @@
+//! Selection of the n-th smallest elements.
+//!
+//! Quick select partitions the slice and only continues into the
+//! partition holding the wanted index. If the partitions keep being
+//! unbalanced it switches to median-of-medians pivots, which guarantees
+//! linear time at worst.
+
+use std::cmp::Ordering;
+
+use super::internal_partition;
+use super::intro::{choose_pivot, introsort_by};
+use super::partition::partition_three_way;
+
+/// Subslices of at most this many elements are sorted with insertion sort.
+const INSERTION_THRESHOLD: usize = 16;
+
+/// Number of unbalanced partitions after which
+/// median-of-medians pivots are used instead.
+const UNBALANCED_LIMIT: usize = 4;
+
+/// Places the element at index `k` of the sorted slice at index `k`,
+/// using median-of-medians pivots.
+///
+/// Runs in O(n) time at worst.
+fn median_of_medians_select<T, F>(slice: &mut [T], k: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut b = 0;
+    let mut e = slice.len();
+
+    while e - b > INSERTION_THRESHOLD {
+        let p = median_of_medians(b, e, slice, compare);
+        let (lt, gt) = partition_three_way(p, b, e, slice, compare);
+
+        if k < lt {
+            e = lt;
+        } else if k >= gt {
+            b = gt;
+        } else {
+            return;
+        }
+    }
+
+    insertion::sort_by(&mut slice[b..e], &mut *compare);
+}
+
+/// Returns the index of the median of the medians of groups of five
+/// elements of the subslice [b, e).
+///
+/// The medians are moved to the front of the subslice.
+fn median_of_medians<T, F>(b: usize, e: usize, slice: &mut [T], compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let groups = (e - b) / 5;
+    for group in 0..groups {
+        let start = b + 5 * group;
+        insertion::sort_by(&mut slice[start..start + 5], &mut *compare);
+        slice.swap(b + group, start + 2);
+    }
+
+    median_of_medians_select(&mut slice[b..b + groups], groups / 2, compare);
+    b + groups / 2
+}
+
+/// Places the element at index `k` of the sorted slice at index `k`,
+/// the elements before it are <= to it and the elements after are >= to it.
+fn internal_select<T, F>(slice: &mut [T], k: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut b = 0;
+    let mut e = slice.len();
+    let mut unbalanced = 0;
+
+    while e - b > INSERTION_THRESHOLD {
+        if unbalanced == UNBALANCED_LIMIT {
+            median_of_medians_select(&mut slice[b..e], k - b, compare);
+            return;
+        }
+
+        let len = e - b;
+        let p = choose_pivot(b, e, slice, compare);
+        let partition_index = internal_partition(p, b, e, slice, compare);
+
+        if k < partition_index {
+            e = partition_index;
+        } else if k > partition_index {
+            b = partition_index + 1;
+        } else {
+            return;
+        }
+
+        if e - b > len / 8 * 7 {
+            unbalanced += 1;
+        }
+    }
+
+    insertion::sort_by(&mut slice[b..e], &mut *compare);
+}
+
+/// Reorders the slice such that the element at index `k` is at its
+/// sorted position, the elements before it are <= to it and the
+/// elements after it are >= to it.
+///
+/// Returns the elements before `k`, the element at `k` and the elements after it.
+///
+/// Runs in O(n) time at worst.
+///
+/// # Panics
+///
+/// Panics when `k >= slice.len()`.
+///
+/// # Examples
+///
+/// ```
+/// use quick::select_nth_unstable;
+///
+/// let mut nmbrs = vec![4,3,2,5,1];
+/// let (_, median, _) = select_nth_unstable(&mut nmbrs, 2);
+///
+/// assert_eq!(*median, 3);
+/// ```
+pub fn select_nth_unstable<T>(slice: &mut [T], k: usize) -> (&mut [T], &mut T, &mut [T])
+where
+    T: Ord,
+{
+    select_nth_unstable_by(slice, k, T::cmp)
+}
+
+/// Reorders the slice such that the element at index `k` is at its
+/// sorted position with a comparator function.
+///
+/// # Panics
+///
+/// Panics when `k >= slice.len()`.
+///
+/// # Examples
+///
+/// ```
+/// use quick::select_nth_unstable_by;
+///
+/// let mut nmbrs = vec![1,3,2,5,4];
+/// let (greater, second, _) = select_nth_unstable_by(&mut nmbrs, 1, |a, b| b.cmp(a));
+///
+/// assert_eq!(greater, [5]);
+/// assert_eq!(*second, 4);
+/// ```
+pub fn select_nth_unstable_by<T, F>(
+    slice: &mut [T],
+    k: usize,
+    mut compare: F,
+) -> (&mut [T], &mut T, &mut [T])
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    assert!(
+        k < slice.len(),
+        "select index {} out of range for slice of length {}",
+        k,
+        slice.len()
+    );
+
+    internal_select(slice, k, &mut compare);
+
+    let (before, rest) = slice.split_at_mut(k);
+    let (nth, after) = rest.split_first_mut().unwrap();
+    (before, nth, after)
+}
+
+/// Reorders the slice such that the element at index `k` is at its
+/// sorted position with a key extraction function.
+///
+/// # Panics
+///
+/// Panics when `k >= slice.len()`.
+///
+/// # Examples
+///
+/// ```
+/// use quick::select_nth_unstable_by_key;
+///
+/// let mut nmbrs = vec![-4,3,-2,5,1];
+/// let (_, nth, _) = select_nth_unstable_by_key(&mut nmbrs, 3, |n: &i32| n.abs());
+///
+/// assert_eq!(*nth, -4);
+/// ```
+pub fn select_nth_unstable_by_key<T, K, F>(
+    slice: &mut [T],
+    k: usize,
+    mut f: F,
+) -> (&mut [T], &mut T, &mut [T])
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    select_nth_unstable_by(slice, k, |a, b| f(a).cmp(&f(b)))
+}
+
+/// Sorts the `k` smallest elements of the slice into its first `k`
+/// positions, the order of the remaining elements is unspecified.
+///
+/// Runs in O(n + k log(k)) time.
+///
+/// # Examples
+///
+/// ```
+/// use quick::partial_sort;
+///
+/// let mut nmbrs = vec![4,3,2,5,1];
+/// partial_sort(&mut nmbrs, 3);
+///
+/// assert_eq!(nmbrs[..3], [1,2,3]);
+/// ```
+pub fn partial_sort<T>(slice: &mut [T], k: usize)
+where
+    T: Ord,
+{
+    partial_sort_by(slice, k, T::cmp);
+}
+
+/// Sorts the `k` smallest elements of the slice into its first `k`
+/// positions with a comparator function.
+///
+/// With `k >= slice.len()` the whole slice is sorted.
+///
+/// # Examples
+///
+/// ```
+/// use quick::partial_sort_by;
+///
+/// let mut nmbrs = vec![1,3,2,5,4];
+/// partial_sort_by(&mut nmbrs, 2, |a, b| b.cmp(a));
+///
+/// assert_eq!(nmbrs[..2], [5,4]);
+/// ```
+pub fn partial_sort_by<T, F>(slice: &mut [T], k: usize, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let k = k.min(slice.len());
+    if k == 0 {
+        return;
+    }
+
+    if k < slice.len() {
+        internal_select(slice, k - 1, &mut compare);
+    }
+    introsort_by(&mut slice[..k], compare);
+}
+
+/// Sorts the `k` smallest elements of the slice into its first `k`
+/// positions with a key extraction function.
+///
+/// # Examples
+///
+/// ```
+/// use quick::partial_sort_by_key;
+///
+/// let mut nmbrs = vec![-4,3,-2,5,1];
+/// partial_sort_by_key(&mut nmbrs, 2, |n: &i32| n.abs());
+///
+/// assert_eq!(nmbrs[..2], [1,-2]);
+/// ```
+pub fn partial_sort_by_key<T, K, F>(slice: &mut [T], k: usize, mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    partial_sort_by(slice, k, |a, b| f(a).cmp(&f(b)));
+}
+
+/// Returns the `k` largest elements of the iterator in descending order.
+///
+/// At most `2 * k` elements are kept in memory. Whenever the buffer fills
+/// up the `k` largest elements are selected and the rest is dropped,
+/// so the iterator is consumed in O(n) amortized time.
+///
+/// # Examples
+///
+/// ```
+/// use quick::top_k;
+///
+/// let top = top_k(vec![4,3,2,5,1], 3);
+///
+/// assert_eq!(top, vec![5,4,3]);
+/// ```
+pub fn top_k<I, T>(iter: I, k: usize) -> Vec<T>
+where
+    I: IntoIterator<Item = T>,
+    T: Ord,
+{
+    top_k_by(iter, k, T::cmp)
+}
+
+/// Returns the `k` largest elements of the iterator in descending order
+/// with a comparator function.
+///
+/// # Examples
+///
+/// ```
+/// use quick::top_k_by;
+///
+/// let smallest = top_k_by(1..=100, 3, |a, b| b.cmp(a));
+///
+/// assert_eq!(smallest, vec![1,2,3]);
+/// ```
+pub fn top_k_by<I, T, F>(iter: I, k: usize, mut compare: F) -> Vec<T>
+where
+    I: IntoIterator<Item = T>,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if k == 0 {
+        return Vec::new();
+    }
+
+    let mut descending = |a: &T, b: &T| compare(b, a);
+    let mut top = Vec::with_capacity(2 * k);
+    for elem in iter {
+        top.push(elem);
+        if top.len() == 2 * k {
+            internal_select(&mut top, k - 1, &mut descending);
+            top.truncate(k);
+        }
+    }
+
+    partial_sort_by(&mut top, k, &mut descending);
+    top.truncate(k);
+    top
+}
+
+/// Returns the `k` elements of the iterator with the largest keys
+/// in descending order.
+///
+/// # Examples
+///
+/// ```
+/// use quick::top_k_by_key;
+///
+/// let top = top_k_by_key(vec!["ccc", "a", "dddd", "bb"], 2, |w| w.len());
+///
+/// assert_eq!(top, vec!["dddd", "ccc"]);
+/// ```
+pub fn top_k_by_key<I, T, K, F>(iter: I, k: usize, mut f: F) -> Vec<T>
+where
+    I: IntoIterator<Item = T>,
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    top_k_by(iter, k, |a, b| f(a).cmp(&f(b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_median_of_medians() {
+        let mut nmbrs: Vec<i32> = (0..25).rev().collect();
+        let p = median_of_medians(0, nmbrs.len(), &mut nmbrs, &mut i32::cmp);
+        assert!((7..=17).contains(&nmbrs[p]));
+    }
+
+    #[test]
+    fn test_median_of_medians_select() {
+        let mut nmbrs: Vec<i32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+        median_of_medians_select(&mut nmbrs, 123, &mut i32::cmp);
+        assert_eq!(nmbrs[123], 123);
+        assert!(nmbrs[..123].iter().all(|&n| n < 123));
+
+        let mut nmbrs = vec![3; 1000];
+        median_of_medians_select(&mut nmbrs, 500, &mut i32::cmp);
+        assert_eq!(nmbrs, vec![3; 1000]);
+    }
+}
@@ -1,4 +1,11 @@
-use quick::sort;
+use observe::{SortStats, Trace};
+use quick::{
+    introsort, introsort_by_key, par_sort, par_sort_by, par_sort_by_grain, partial_sort,
+    partial_sort_by_key, pdqsort, pdqsort_by, pdqsort_by_key, pdqsort_by_observed,
+    select_nth_unstable, select_nth_unstable_by, sort, sort_by, sort_by_key,
+    sort_by_key_with_options, sort_by_observed, sort_by_with_options, sort_floats, sort_observed,
+    sort_with_options, top_k, top_k_by_key, NanPlacement, Partition, SortOptions,
+};
 
 #[test]
 fn quick_sort_works() {
@@ -19,3 +26,393 @@ fn quick_sort_works() {
         vec![1, 2, 3, 5, 7, 45, 56, 66, 3432, 3543, 4325, 4534, 5234, 65453]
     );
 }
+
+#[test]
+fn quick_sort_by_works() {
+    let mut things = vec![4, 1, 6, 3, 5, 2];
+    sort_by(&mut things, |a, b| b.cmp(a));
+    assert_eq!(things, vec![6, 5, 4, 3, 2, 1]);
+
+    let mut things: Vec<i32> = vec![];
+    sort_by(&mut things, |a, b| b.cmp(a));
+    assert_eq!(things, vec![]);
+}
+
+#[test]
+fn quick_sort_by_key_works() {
+    let mut words = vec!["ccc", "a", "bb", "dddd"];
+    sort_by_key(&mut words, |w| w.len());
+    assert_eq!(words, vec!["a", "bb", "ccc", "dddd"]);
+}
+
+#[test]
+fn introsort_works() {
+    let mut things = vec![
+        1, 5, 3, 2, 66, 7, 5234, 56, 4534, 65453, 45, 3543, 4325, 3432,
+    ];
+    introsort(&mut things);
+    assert_eq!(
+        things,
+        vec![1, 2, 3, 5, 7, 45, 56, 66, 3432, 3543, 4325, 4534, 5234, 65453]
+    );
+
+    let mut things: Vec<i32> = vec![];
+    introsort(&mut things);
+    assert_eq!(things, vec![]);
+}
+
+#[test]
+fn introsort_handles_adversarial_input() {
+    let n = 10_000;
+    let want: Vec<usize> = (0..n).collect();
+
+    let mut sorted = want.clone();
+    introsort(&mut sorted);
+    assert_eq!(sorted, want);
+
+    let mut reversed: Vec<usize> = (0..n).rev().collect();
+    introsort(&mut reversed);
+    assert_eq!(reversed, want);
+
+    let mut organ_pipe: Vec<usize> = (0..n / 2).chain((0..n / 2).rev()).collect();
+    introsort(&mut organ_pipe);
+    assert_eq!(organ_pipe, (0..n).map(|i| i / 2).collect::<Vec<_>>());
+
+    let mut equal = vec![7; n];
+    introsort(&mut equal);
+    assert_eq!(equal, vec![7; n]);
+}
+
+#[test]
+fn introsort_by_key_works() {
+    let mut words = vec!["ccc", "a", "bb", "dddd"];
+    introsort_by_key(&mut words, |w| std::cmp::Reverse(w.len()));
+    assert_eq!(words, vec!["dddd", "ccc", "bb", "a"]);
+}
+
+#[test]
+fn pdqsort_works() {
+    let mut things = vec![
+        1, 5, 3, 2, 66, 7, 5234, 56, 4534, 65453, 45, 3543, 4325, 3432,
+    ];
+    pdqsort(&mut things);
+    assert_eq!(
+        things,
+        vec![1, 2, 3, 5, 7, 45, 56, 66, 3432, 3543, 4325, 4534, 5234, 65453]
+    );
+
+    let mut things: Vec<i32> = vec![];
+    pdqsort(&mut things);
+    assert_eq!(things, vec![]);
+
+    let mut words = vec!["ccc", "a", "bb", "dddd"];
+    pdqsort_by_key(&mut words, |w| std::cmp::Reverse(w.len()));
+    assert_eq!(words, vec!["dddd", "ccc", "bb", "a"]);
+}
+
+#[test]
+fn pdqsort_observed_replays() {
+    let n = 2000;
+    let keys: Vec<Vec<usize>> = vec![
+        (0..n).map(|i| (i * 7919) % 13).collect(),
+        (0..n).map(|i| (i * 2_654_435_761) % 100_000).collect(),
+        (0..n).map(|i| if i % 100 == 0 { 0 } else { i }).collect(),
+    ];
+    for keys in keys {
+        let input: Vec<(usize, usize)> = keys.into_iter().zip(0..).collect();
+        let mut pairs = input.clone();
+        let mut trace = Trace::new();
+        let mut stats = SortStats::default();
+        let mut comparisons = 0;
+        pdqsort_by_observed(&mut pairs, |a, b| a.0.cmp(&b.0), &mut trace);
+        pdqsort_by_observed(
+            &mut input.clone(),
+            |a, b| {
+                comparisons += 1;
+                a.0.cmp(&b.0)
+            },
+            &mut stats,
+        );
+
+        assert!(pairs.windows(2).all(|w| w[0].0 <= w[1].0));
+        assert_eq!(stats.comparisons, comparisons);
+        assert!(stats.moves > 0);
+        assert_eq!(
+            observe::replay_by(&input, trace.events(), |a, b| a.0.cmp(&b.0)),
+            Ok(pairs)
+        );
+    }
+}
+
+#[test]
+fn pdqsort_handles_adversarial_input() {
+    let n = 10_000;
+    let want: Vec<usize> = (0..n).collect();
+    let sorted_with_comparisons = |mut input: Vec<usize>| {
+        let mut comparisons = 0;
+        pdqsort_by(&mut input, |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        (input, comparisons)
+    };
+
+    let (sorted, comparisons) = sorted_with_comparisons(want.clone());
+    assert_eq!(sorted, want);
+    assert!(comparisons < 2 * n, "sorted input takes linear time");
+
+    let (reversed, comparisons) = sorted_with_comparisons((0..n).rev().collect());
+    assert_eq!(reversed, want);
+    assert!(comparisons < 2 * n, "reversed input takes linear time");
+
+    let (equal, comparisons) = sorted_with_comparisons(vec![7; n]);
+    assert_eq!(equal, vec![7; n]);
+    assert!(comparisons < 3 * n, "equal input takes linear time");
+
+    let organ_pipe: Vec<usize> = (0..n / 2).chain((0..n / 2).rev()).collect();
+    let (organ_pipe, _) = sorted_with_comparisons(organ_pipe);
+    assert_eq!(organ_pipe, (0..n).map(|i| i / 2).collect::<Vec<_>>());
+
+    let sawtooth: Vec<usize> = (0..n).map(|i| i % 100).collect();
+    let (sawtooth, comparisons) = sorted_with_comparisons(sawtooth);
+    assert_eq!(sawtooth, (0..n).map(|i| i / 100).collect::<Vec<_>>());
+    assert!(comparisons < 20 * n);
+}
+
+#[test]
+fn par_sort_works() {
+    let mut things = vec![
+        1, 5, 3, 2, 66, 7, 5234, 56, 4534, 65453, 45, 3543, 4325, 3432,
+    ];
+    par_sort(&mut things);
+    assert_eq!(
+        things,
+        vec![1, 2, 3, 5, 7, 45, 56, 66, 3432, 3543, 4325, 4534, 5234, 65453]
+    );
+
+    let mut things: Vec<i32> = vec![];
+    par_sort_by(&mut things, |a, b| b.cmp(a));
+    assert_eq!(things, vec![]);
+}
+
+#[test]
+fn par_sort_splits_down_to_grain_size() {
+    let n = 100_000;
+    let mut things: Vec<usize> = (0..n).map(|i| (i * 7919) % n).collect();
+    par_sort_by_grain(&mut things, 64, |a, b| a.cmp(b));
+    assert_eq!(things, (0..n).collect::<Vec<_>>());
+
+    let mut things: Vec<usize> = (0..n).map(|i| i % 7).collect();
+    par_sort_by_grain(&mut things, 64, |a, b| b.cmp(a));
+    assert!(things.windows(2).all(|w| w[0] >= w[1]));
+}
+
+#[test]
+fn sort_with_options_works() {
+    for &partition in &[Partition::TwoWay, Partition::ThreeWay, Partition::DualPivot] {
+        let options = SortOptions::default().partition(partition);
+
+        let mut things = vec![
+            1, 5, 3, 2, 66, 7, 5234, 56, 4534, 65453, 45, 3543, 4325, 3432,
+        ];
+        sort_with_options(&mut things, options);
+        assert_eq!(
+            things,
+            vec![1, 2, 3, 5, 7, 45, 56, 66, 3432, 3543, 4325, 4534, 5234, 65453]
+        );
+
+        let mut things: Vec<i32> = vec![];
+        sort_with_options(&mut things, options);
+        assert_eq!(things, vec![]);
+
+        let mut words = vec!["ccc", "a", "bb", "dddd"];
+        sort_by_key_with_options(&mut words, options, |w| w.len());
+        assert_eq!(words, vec!["a", "bb", "ccc", "dddd"]);
+    }
+}
+
+#[test]
+fn sort_with_options_handles_duplicates() {
+    let n = 100_000;
+    let want: Vec<usize> = (0..n).map(|i| i * 5 / n).collect();
+
+    for &partition in &[Partition::ThreeWay, Partition::DualPivot] {
+        let options = SortOptions::default().partition(partition);
+
+        let mut things: Vec<usize> = (0..n).map(|i| (i * 7919) % 5).collect();
+        sort_with_options(&mut things, options);
+        assert_eq!(things, want);
+
+        let mut things = vec![7; n];
+        sort_with_options(&mut things, options);
+        assert_eq!(things, vec![7; n]);
+    }
+}
+
+/// Sorts the indices `0..n` with McIlroy's "killer adversary", which
+/// fixes the values of the elements only as they are compared, so as
+/// to make any quick sort pick bad pivots. Returns the number of comparisons.
+fn sort_against_adversary(n: usize, options: SortOptions) -> usize {
+    let gas = n;
+    let mut values = vec![gas; n];
+    let mut solid = 0;
+    let mut candidate = 0;
+    let mut comparisons = 0;
+
+    let mut things: Vec<usize> = (0..n).collect();
+    sort_by_with_options(&mut things, options, |&x, &y| {
+        comparisons += 1;
+        if values[x] == gas && values[y] == gas {
+            let frozen = if x == candidate { x } else { y };
+            values[frozen] = solid;
+            solid += 1;
+        }
+        if values[x] == gas {
+            candidate = x;
+        } else if values[y] == gas {
+            candidate = y;
+        }
+        values[x].cmp(&values[y])
+    });
+    assert!(things.windows(2).all(|w| values[w[0]] <= values[w[1]]));
+    comparisons
+}
+
+#[test]
+fn sort_with_options_survives_killer_adversary() {
+    let n = 4096;
+    for &partition in &[Partition::TwoWay, Partition::ThreeWay, Partition::DualPivot] {
+        let comparisons = sort_against_adversary(n, SortOptions::default().partition(partition));
+        assert!(comparisons < 8 * n * 12, "{:?}: {}", partition, comparisons);
+    }
+}
+
+#[test]
+fn select_nth_unstable_works() {
+    let mut things = vec![
+        1, 5, 3, 2, 66, 7, 5234, 56, 4534, 65453, 45, 3543, 4325, 3432,
+    ];
+    let (before, nth, after) = select_nth_unstable(&mut things, 7);
+    assert_eq!(*nth, 66);
+    assert!(before.iter().all(|&n| n <= 66));
+    assert!(after.iter().all(|&n| n >= 66));
+
+    let n = 100_000;
+    let mut things: Vec<usize> = (0..n).map(|i| (i * 7919) % n).collect();
+    let (_, nth, _) = select_nth_unstable_by(&mut things, 10, |a, b| b.cmp(a));
+    assert_eq!(*nth, n - 11);
+}
+
+#[test]
+fn select_nth_unstable_is_linear() {
+    let n = 100_000;
+    let inputs: Vec<Vec<usize>> = vec![
+        (0..n).collect(),
+        (0..n).rev().collect(),
+        (0..n / 2).chain((0..n / 2).rev()).collect(),
+        vec![7; n],
+        (0..n).map(|i| i % 2).collect(),
+    ];
+
+    for input in inputs {
+        let mut want = input.clone();
+        want.sort();
+
+        for &k in &[0, n / 3, n / 2, n - 1] {
+            let mut things = input.clone();
+            let mut comparisons = 0;
+            let (_, nth, _) = select_nth_unstable_by(&mut things, k, |a, b| {
+                comparisons += 1;
+                a.cmp(b)
+            });
+            assert_eq!(*nth, want[k]);
+            assert!(comparisons < 30 * n);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn select_nth_unstable_panics_out_of_range() {
+    let mut things = vec![1, 2, 3];
+    select_nth_unstable(&mut things, 3);
+}
+
+#[test]
+fn partial_sort_works() {
+    let mut things = vec![
+        1, 5, 3, 2, 66, 7, 5234, 56, 4534, 65453, 45, 3543, 4325, 3432,
+    ];
+    partial_sort(&mut things, 5);
+    assert_eq!(things[..5], [1, 2, 3, 5, 7]);
+
+    let mut things = vec![3, 1, 2];
+    partial_sort(&mut things, 10);
+    assert_eq!(things, vec![1, 2, 3]);
+
+    let mut things: Vec<i32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+    partial_sort_by_key(&mut things, 100, |&n| std::cmp::Reverse(n));
+    assert_eq!(things[..100], (900..1000).rev().collect::<Vec<_>>()[..]);
+}
+
+#[test]
+fn top_k_works() {
+    let n = 100_000;
+    let top = top_k((0..n).map(|i| (i * 7919) % n), 100);
+    assert_eq!(top, (n - 100..n).rev().collect::<Vec<_>>());
+
+    let top = top_k(vec![3, 1, 2], 10);
+    assert_eq!(top, vec![3, 2, 1]);
+
+    let top = top_k(vec![3, 1, 2], 0);
+    assert_eq!(top, vec![]);
+
+    let top = top_k_by_key(vec!["ccc", "a", "dddd", "bb"], 2, |w| w.len());
+    assert_eq!(top, vec!["dddd", "ccc"]);
+}
+
+#[test]
+fn sort_observed_works() {
+    let mut things = vec![
+        1, 5, 3, 2, 66, 7, 5234, 56, 4534, 65453, 45, 3543, 4325, 3432,
+    ];
+    let mut stats = SortStats::default();
+    sort_observed(&mut things, &mut stats);
+    assert_eq!(
+        things,
+        vec![1, 2, 3, 5, 7, 45, 56, 66, 3432, 3543, 4325, 4534, 5234, 65453]
+    );
+    assert!(stats.swaps > 0);
+    assert_eq!(stats.moves, 0);
+
+    let n = 1024;
+    let mut things: Vec<usize> = (0..n).collect();
+    let mut stats = SortStats::default();
+    let mut comparisons = 0;
+    sort_by_observed(
+        &mut things,
+        |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        },
+        &mut stats,
+    );
+    assert_eq!(things, (0..n).collect::<Vec<_>>());
+    assert_eq!(stats.comparisons, comparisons);
+    assert!(stats.max_depth <= 2 * 11);
+
+    let input: Vec<usize> = (0..n).map(|i| (i * 2_654_435_761) % 1000).collect();
+    let mut trace = Trace::new();
+    let mut things = input.clone();
+    sort_observed(&mut things, &mut trace);
+    assert_eq!(observe::replay(&input, trace.events()), Ok(things));
+}
+
+#[test]
+fn quick_sort_floats_groups_zeros_by_sign() {
+    let mut floats = [0.0f32, -0.0].repeat(20);
+    sort_floats(&mut floats, NanPlacement::Last);
+
+    assert!(floats[..20].iter().all(|f| f.is_sign_negative()));
+    assert!(floats[20..].iter().all(|f| f.is_sign_positive()));
+}
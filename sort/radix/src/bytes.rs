@@ -0,0 +1,105 @@
+//! MSD radix sort for byte strings.
+
+/// Buckets of at most this many elements are sorted with insertion sort.
+const INSERTION_THRESHOLD: usize = 32;
+
+/// Number of buckets per pass, one for strings that end at the
+/// current depth and one for every byte value.
+const BUCKETS: usize = 257;
+
+/// Returns the bucket of `bytes` at `depth`.
+#[inline]
+fn bucket(bytes: &[u8], depth: usize) -> usize {
+    bytes.get(depth).map_or(0, |&b| b as usize + 1)
+}
+
+/// Sorts the slice, whose elements all share their first `depth` bytes,
+/// using insertion sort on the remaining bytes.
+fn insertion_sort<T>(slice: &mut [T], depth: usize)
+where
+    T: AsRef<[u8]>,
+{
+    for curr in 1..slice.len() {
+        let mut tmp = curr;
+        while tmp > 0 && slice[tmp].as_ref()[depth..] < slice[tmp - 1].as_ref()[depth..] {
+            slice.swap(tmp, tmp - 1);
+            tmp -= 1;
+        }
+    }
+}
+
+/// Sorts the slice using in-place MSD radix sort (American flag sort).
+///
+/// Instead of recursing into every bucket the pending buckets are kept
+/// on an explicit stack, so long common prefixes can't overflow the stack.
+pub(crate) fn msd_sort<T>(slice: &mut [T])
+where
+    T: AsRef<[u8]>,
+{
+    let mut pending = vec![(0, slice.len(), 0)];
+
+    while let Some((b, e, depth)) = pending.pop() {
+        let part = &mut slice[b..e];
+        if part.len() <= INSERTION_THRESHOLD {
+            insertion_sort(part, depth);
+            continue;
+        }
+
+        let mut counts = [0; BUCKETS];
+        for elem in part.iter() {
+            counts[bucket(elem.as_ref(), depth)] += 1;
+        }
+
+        let mut ends = [0; BUCKETS];
+        let mut next = [0; BUCKETS];
+        let mut sum = 0;
+        for i in 0..BUCKETS {
+            next[i] = sum;
+            sum += counts[i];
+            ends[i] = sum;
+        }
+
+        for i in 0..BUCKETS {
+            while next[i] < ends[i] {
+                let target = bucket(part[next[i]].as_ref(), depth);
+                if target == i {
+                    next[i] += 1;
+                } else {
+                    part.swap(next[i], next[target]);
+                    next[target] += 1;
+                }
+            }
+        }
+
+        // Strings in bucket 0 have ended, they are all equal.
+        for i in 1..BUCKETS {
+            let start = ends[i] - counts[i];
+            if counts[i] > 1 {
+                pending.push((b + start, b + ends[i], depth + 1));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insertion_sort_skips_prefix() {
+        let mut words = vec!["xc", "xa", "xb"];
+        insertion_sort(&mut words, 1);
+        assert_eq!(words, vec!["xa", "xb", "xc"]);
+    }
+
+    #[test]
+    fn test_msd_sort_long_common_prefix() {
+        let prefix = "a".repeat(10_000);
+        let mut words: Vec<String> = (0..100)
+            .rev()
+            .map(|i| format!("{}{:03}", prefix, i))
+            .collect();
+        msd_sort(&mut words);
+        assert!(words.windows(2).all(|w| w[0] <= w[1]));
+    }
+}
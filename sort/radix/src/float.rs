@@ -0,0 +1,165 @@
+//! Total order of floating point numbers.
+
+use std::cmp::Ordering;
+
+use super::RadixKey;
+
+/// Where NaNs are placed when sorting floats.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NanPlacement {
+    /// All NaNs go before every other float.
+    First,
+    /// All NaNs go after every other float.
+    #[default]
+    Last,
+}
+
+/// Floats ordered by the IEEE 754 totalOrder predicate.
+///
+/// -0.0 is less than +0.0 and, unlike with totalOrder where the sign
+/// bit of a NaN decides its end, all NaNs are grouped at one end.
+///
+/// # Examples
+///
+/// ```
+/// use radix::{NanPlacement, TotalOrd};
+/// use std::cmp::Ordering;
+///
+/// assert_eq!((-0.0f64).total_cmp_with(&0.0, NanPlacement::Last), Ordering::Less);
+/// assert_eq!((-f64::NAN).total_cmp_with(&1.0, NanPlacement::Last), Ordering::Greater);
+/// assert_eq!(f32::NAN.total_cmp_with(&1.0, NanPlacement::First), Ordering::Less);
+/// ```
+pub trait TotalOrd: Copy {
+    /// The unsigned integer the float is transformed into.
+    type Bits: Ord + RadixKey;
+
+    /// Returns the bits of the float transformed such that comparing
+    /// them as unsigned integers gives the IEEE 754 total order.
+    ///
+    /// The sign bit is flipped for positive floats and all the bits are
+    /// flipped for negative ones, which reverses their order.
+    fn total_bits(self) -> Self::Bits;
+
+    /// Returns whether the float is NaN.
+    fn is_nan(self) -> bool;
+
+    /// Compares by the total order with the NaNs placed at the given end.
+    ///
+    /// NaNs are ordered among themselves by their bits.
+    fn total_cmp_with(&self, other: &Self, nans: NanPlacement) -> Ordering {
+        let nan_order = match nans {
+            NanPlacement::First => Ordering::Less,
+            NanPlacement::Last => Ordering::Greater,
+        };
+
+        match (self.is_nan(), other.is_nan()) {
+            (true, false) => nan_order,
+            (false, true) => nan_order.reverse(),
+            _ => self.total_bits().cmp(&other.total_bits()),
+        }
+    }
+}
+
+impl TotalOrd for f32 {
+    type Bits = u32;
+
+    #[inline]
+    fn total_bits(self) -> u32 {
+        let bits = self.to_bits();
+        bits ^ ((((bits as i32) >> 31) as u32) | (1 << 31))
+    }
+
+    #[inline]
+    fn is_nan(self) -> bool {
+        f32::is_nan(self)
+    }
+}
+
+impl TotalOrd for f64 {
+    type Bits = u64;
+
+    #[inline]
+    fn total_bits(self) -> u64 {
+        let bits = self.to_bits();
+        bits ^ ((((bits as i64) >> 63) as u64) | (1 << 63))
+    }
+
+    #[inline]
+    fn is_nan(self) -> bool {
+        f64::is_nan(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_total_bits() {
+        assert_eq!(0.0f32.total_bits(), 1 << 31);
+        assert_eq!((-0.0f32).total_bits(), (1 << 31) - 1);
+        assert!(f64::NEG_INFINITY.total_bits() < (-1.0f64).total_bits());
+        assert!(f64::INFINITY.total_bits() < f64::NAN.total_bits());
+    }
+
+    #[test]
+    fn test_total_cmp_with() {
+        let nan = f64::NAN;
+        assert_eq!(
+            nan.total_cmp_with(&nan, NanPlacement::First),
+            Ordering::Equal
+        );
+        assert_eq!(
+            (-nan).total_cmp_with(&nan, NanPlacement::First),
+            Ordering::Less
+        );
+        assert_eq!(
+            1.0.total_cmp_with(&(-nan), NanPlacement::First),
+            Ordering::Greater
+        );
+        assert_eq!(
+            f64::INFINITY.total_cmp_with(&nan, NanPlacement::Last),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_total_cmp_with_sorts_by_total_order() {
+        let nan = f64::NAN;
+        let floats = vec![
+            3.5,
+            -nan,
+            0.0,
+            f64::NEG_INFINITY,
+            nan,
+            -0.0,
+            -2.25,
+            f64::INFINITY,
+            1e-310,
+            0.0,
+            -0.0,
+        ];
+        let numbers = [
+            f64::NEG_INFINITY,
+            -2.25,
+            -0.0,
+            -0.0,
+            0.0,
+            0.0,
+            1e-310,
+            3.5,
+            f64::INFINITY,
+        ];
+        let bits = |floats: &[f64]| floats.iter().map(|f| f.to_bits()).collect::<Vec<_>>();
+
+        let mut last = floats.clone();
+        last.sort_by(|a, b| a.total_cmp_with(b, NanPlacement::Last));
+        assert_eq!(bits(&last[..9]), bits(&numbers));
+        assert!(last[9..].iter().all(|f| f.is_nan()));
+
+        let mut first = floats;
+        first.sort_by(|a, b| a.total_cmp_with(b, NanPlacement::First));
+        assert!(first[..2].iter().all(|f| f.is_nan()));
+        assert_eq!(bits(&first[2..]), bits(&numbers));
+    }
+}
@@ -0,0 +1,181 @@
+//! Keys that can be sorted byte by byte.
+
+use super::TotalOrd;
+
+/// A fixed-width key that can be radix sorted.
+///
+/// The key is seen as a sequence of `BYTES` digits, the byte at index 0
+/// being the least significant. Comparing two keys digit by digit from the
+/// most significant one must give the order the keys should be sorted in.
+///
+/// Implement it for your own types to radix sort them, e.g. by mapping a
+/// struct to one of its fields or to a tuple of fields.
+///
+/// # Examples
+///
+/// ```
+/// use radix::RadixKey;
+///
+/// #[derive(Clone, Copy)]
+/// struct Version {
+///     major: u8,
+///     minor: u8,
+/// }
+///
+/// impl RadixKey for Version {
+///     const BYTES: usize = 2;
+///
+///     fn byte(&self, index: usize) -> u8 {
+///         if index == 0 { self.minor } else { self.major }
+///     }
+/// }
+///
+/// let mut versions = vec![Version { major: 1, minor: 2 }, Version { major: 0, minor: 9 }];
+/// radix::sort(&mut versions);
+///
+/// assert_eq!(versions[0].major, 0);
+/// ```
+pub trait RadixKey: Copy {
+    /// The number of bytes of the key.
+    const BYTES: usize;
+
+    /// Returns the byte at `index`, where 0 is the least significant byte.
+    fn byte(&self, index: usize) -> u8;
+}
+
+macro_rules! impl_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl RadixKey for $t {
+                const BYTES: usize = std::mem::size_of::<$t>();
+
+                #[inline]
+                fn byte(&self, index: usize) -> u8 {
+                    (*self >> (index * 8)) as u8
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned!(u8, u16, u32, u64, u128, usize);
+
+/// Signed integers are sorted as unsigned ones with the sign bit flipped,
+/// which moves the negative numbers below the positive ones.
+macro_rules! impl_signed {
+    ($($t:ty => $u:ty),*) => {
+        $(
+            impl RadixKey for $t {
+                const BYTES: usize = std::mem::size_of::<$t>();
+
+                #[inline]
+                fn byte(&self, index: usize) -> u8 {
+                    let flipped = (*self as $u) ^ (1 << (<$u>::BITS - 1));
+                    flipped.byte(index)
+                }
+            }
+        )*
+    };
+}
+
+impl_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+
+/// Floats are sorted by the IEEE 754 total order, see [`TotalOrd::total_bits`].
+/// -0.0 goes before +0.0 and NaNs go to the end, or to the start if their
+/// sign bit is set.
+impl RadixKey for f32 {
+    const BYTES: usize = 4;
+
+    #[inline]
+    fn byte(&self, index: usize) -> u8 {
+        self.total_bits().byte(index)
+    }
+}
+
+/// See the implementation for `f32`.
+impl RadixKey for f64 {
+    const BYTES: usize = 8;
+
+    #[inline]
+    fn byte(&self, index: usize) -> u8 {
+        self.total_bits().byte(index)
+    }
+}
+
+impl RadixKey for char {
+    const BYTES: usize = 4;
+
+    #[inline]
+    fn byte(&self, index: usize) -> u8 {
+        (*self as u32).byte(index)
+    }
+}
+
+/// Byte arrays are sorted lexicographically.
+impl<const N: usize> RadixKey for [u8; N] {
+    const BYTES: usize = N;
+
+    #[inline]
+    fn byte(&self, index: usize) -> u8 {
+        self[N - 1 - index]
+    }
+}
+
+/// Tuples are sorted lexicographically.
+impl<A, B> RadixKey for (A, B)
+where
+    A: RadixKey,
+    B: RadixKey,
+{
+    const BYTES: usize = A::BYTES + B::BYTES;
+
+    #[inline]
+    fn byte(&self, index: usize) -> u8 {
+        if index < B::BYTES {
+            self.1.byte(index)
+        } else {
+            self.0.byte(index - B::BYTES)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits<K: RadixKey>(key: K) -> Vec<u8> {
+        (0..K::BYTES).rev().map(|i| key.byte(i)).collect()
+    }
+
+    #[test]
+    fn test_signed_digits_keep_order() {
+        assert!(digits(-1i32) < digits(0i32));
+        assert!(digits(i64::MIN) < digits(-1i64));
+        assert!(digits(i8::MAX) > digits(0i8));
+    }
+
+    #[test]
+    fn test_float_digits_keep_total_order() {
+        let floats = [
+            -f64::NAN,
+            f64::NEG_INFINITY,
+            -1.5,
+            -0.0,
+            0.0,
+            f64::MIN_POSITIVE,
+            2.5,
+            f64::INFINITY,
+            f64::NAN,
+        ];
+        for pair in floats.windows(2) {
+            assert!(digits(pair[0]) < digits(pair[1]));
+        }
+        assert!(digits(-1.0f32) < digits(-0.5f32));
+    }
+
+    #[test]
+    fn test_tuple_digits() {
+        assert_eq!(digits((1u8, 2u16)), vec![1, 0, 2]);
+        assert_eq!(digits([3u8, 4]), vec![3, 4]);
+    }
+}
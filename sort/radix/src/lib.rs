@@ -0,0 +1,199 @@
+//! # Radix
+//!
+//! `radix` provides sort capabilities using radix sort.
+//!
+//! Fixed-width keys, see [`RadixKey`], are sorted using LSD radix sort
+//! using O(w * n) time complexity and O(n) space complexity, where `w`
+//! is the number of bytes of the key. Byte strings are sorted using
+//! in-place MSD radix sort, see [`sort_bytes`].
+//!
+//! The transform of floats into integers ordered by the IEEE 754 total
+//! order is exposed for reuse by the comparison sorts, see [`TotalOrd`].
+
+pub use float::{NanPlacement, TotalOrd};
+pub use key::RadixKey;
+
+mod bytes;
+mod float;
+mod key;
+
+/// Computes the byte of every key at `index` into `digits`
+/// and returns how many keys there are per byte value.
+fn count_digits<K, I>(keys: I, index: usize, digits: &mut Vec<u8>) -> [usize; 256]
+where
+    K: RadixKey,
+    I: Iterator<Item = K>,
+{
+    let mut counts = [0; 256];
+    digits.clear();
+    for key in keys {
+        let digit = key.byte(index);
+        counts[digit as usize] += 1;
+        digits.push(digit);
+    }
+
+    counts
+}
+
+/// Turns the per byte value counts into the starting offsets of the buckets.
+///
+/// Returns `false` if all the elements fall into a single bucket,
+/// in which case the pass can be skipped.
+fn bucket_offsets(counts: &mut [usize; 256], len: usize) -> bool {
+    if counts.contains(&len) {
+        return false;
+    }
+
+    let mut sum = 0;
+    for count in counts.iter_mut() {
+        let next = sum + *count;
+        *count = sum;
+        sum = next;
+    }
+
+    true
+}
+
+/// Sorts the slice using LSD radix sort.
+///
+/// The sort is stable, keys with equal bytes keep their relative order.
+///
+/// # Examples
+///
+/// ```
+/// let mut nmbrs = vec![4, -3, 2, 5, -1];
+/// radix::sort(&mut nmbrs);
+///
+/// assert_eq!(nmbrs, vec![-3, -1, 2, 4, 5]);
+///
+/// let mut floats = vec![2.5, -0.0, -1.5, 0.0];
+/// radix::sort(&mut floats);
+///
+/// assert_eq!(floats, vec![-1.5, -0.0, 0.0, 2.5]);
+/// ```
+pub fn sort<T>(slice: &mut [T])
+where
+    T: RadixKey,
+{
+    let mut scratch = slice.to_vec();
+    let mut digits = Vec::with_capacity(slice.len());
+    let mut in_scratch = false;
+
+    for index in 0..T::BYTES {
+        let (src, dst) = if in_scratch {
+            (&scratch[..], &mut slice[..])
+        } else {
+            (&slice[..], &mut scratch[..])
+        };
+
+        let mut offsets = count_digits(src.iter().copied(), index, &mut digits);
+        if !bucket_offsets(&mut offsets, src.len()) {
+            continue;
+        }
+
+        for (elem, &digit) in src.iter().zip(digits.iter()) {
+            dst[offsets[digit as usize]] = *elem;
+            offsets[digit as usize] += 1;
+        }
+
+        in_scratch = !in_scratch;
+    }
+
+    if in_scratch {
+        slice.copy_from_slice(&scratch);
+    }
+}
+
+/// Sorts the slice using LSD radix sort with a key extraction function.
+///
+/// The key is extracted once per element. The elements themselves
+/// are only moved once, after the sorted order has been computed.
+///
+/// The sort is stable, elements with equal keys keep their relative order.
+///
+/// # Examples
+///
+/// ```
+/// let mut people = vec![("bob", 42u8), ("alice", 7), ("eve", 42), ("dan", 19)];
+/// radix::sort_by_key(&mut people, |p| p.1);
+///
+/// assert_eq!(people, vec![("alice", 7), ("dan", 19), ("bob", 42), ("eve", 42)]);
+/// ```
+pub fn sort_by_key<T, K, F>(slice: &mut [T], f: F)
+where
+    K: RadixKey,
+    F: FnMut(&T) -> K,
+{
+    let keys: Vec<K> = slice.iter().map(f).collect();
+    let mut order: Vec<usize> = (0..slice.len()).collect();
+    let mut scratch = vec![0; slice.len()];
+    let mut digits = Vec::with_capacity(slice.len());
+
+    for index in 0..K::BYTES {
+        let mut offsets = count_digits(order.iter().map(|&i| keys[i]), index, &mut digits);
+        if !bucket_offsets(&mut offsets, order.len()) {
+            continue;
+        }
+
+        for (&i, &digit) in order.iter().zip(digits.iter()) {
+            scratch[offsets[digit as usize]] = i;
+            offsets[digit as usize] += 1;
+        }
+
+        std::mem::swap(&mut order, &mut scratch);
+    }
+
+    apply_order(slice, &mut order);
+}
+
+/// Moves the element at index `order[i]` to index `i`
+/// by following the cycles of the permutation.
+///
+/// `order` is left as the identity permutation.
+fn apply_order<T>(slice: &mut [T], order: &mut [usize]) {
+    for start in 0..slice.len() {
+        let mut curr = start;
+        while order[curr] != start {
+            let next = order[curr];
+            slice.swap(curr, next);
+            order[curr] = curr;
+            curr = next;
+        }
+        order[curr] = curr;
+    }
+}
+
+/// Sorts the slice of byte strings using in-place MSD radix sort.
+///
+/// Strings are ordered lexicographically by their bytes, which for `str`
+/// is the same as the order of their chars.
+/// The sort is not stable, equal strings may be reordered.
+///
+/// # Examples
+///
+/// ```
+/// let mut words = vec!["pear", "apple", "peach", "app", ""];
+/// radix::sort_bytes(&mut words);
+///
+/// assert_eq!(words, vec!["", "app", "apple", "peach", "pear"]);
+/// ```
+pub fn sort_bytes<T>(slice: &mut [T])
+where
+    T: AsRef<[u8]>,
+{
+    bytes::msd_sort(slice);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_order() {
+        let mut letters = vec!['c', 'a', 'd', 'b'];
+        let mut order = vec![1, 3, 0, 2];
+        apply_order(&mut letters, &mut order);
+        assert_eq!(letters, vec!['a', 'b', 'c', 'd']);
+        assert_eq!(order, vec![0, 1, 2, 3]);
+    }
+}
@@ -0,0 +1,90 @@
+use radix::{sort, sort_by_key, sort_bytes};
+
+#[test]
+fn radix_sort_integers() {
+    let mut things: Vec<u32> = vec![
+        1, 5, 3, 2, 66, 7, 5234, 56, 4534, 65453, 45, 3543, 4325, 3432,
+    ];
+    sort(&mut things);
+    assert_eq!(
+        things,
+        vec![1, 2, 3, 5, 7, 45, 56, 66, 3432, 3543, 4325, 4534, 5234, 65453]
+    );
+
+    let mut things: Vec<i64> = vec![0, -1, i64::MAX, 1, i64::MIN, -70_000, 70_000];
+    sort(&mut things);
+    assert_eq!(things, vec![i64::MIN, -70_000, -1, 0, 1, 70_000, i64::MAX]);
+
+    let mut things: Vec<u64> = vec![];
+    sort(&mut things);
+    assert_eq!(things, vec![]);
+}
+
+#[test]
+fn radix_sort_floats() {
+    let mut things = vec![3.5f32, -0.0, f32::NAN, -2.25, 0.0, f32::NEG_INFINITY, 1e-40];
+    sort(&mut things);
+
+    let bits: Vec<u32> = things.iter().map(|f| f.to_bits()).collect();
+    let want: Vec<u32> = [f32::NEG_INFINITY, -2.25, -0.0, 0.0, 1e-40, 3.5, f32::NAN]
+        .iter()
+        .map(|f| f.to_bits())
+        .collect();
+    assert_eq!(bits, want);
+}
+
+#[test]
+fn radix_sort_by_key_is_stable() {
+    let mut pairs = vec![
+        (3u16, 'a'),
+        (1, 'b'),
+        (3, 'c'),
+        (2, 'd'),
+        (1, 'e'),
+        (256, 'f'),
+    ];
+    sort_by_key(&mut pairs, |p| p.0);
+    assert_eq!(
+        pairs,
+        vec![(1, 'b'), (1, 'e'), (2, 'd'), (3, 'a'), (3, 'c'), (256, 'f')]
+    );
+
+    let mut words = vec![
+        String::from("bb"),
+        String::from("a"),
+        String::from("ccc"),
+        String::from("dd"),
+    ];
+    sort_by_key(&mut words, |w| (w.len(), w.as_bytes()[0]));
+    assert_eq!(words, vec!["a", "bb", "dd", "ccc"]);
+}
+
+#[test]
+fn radix_sort_bytes() {
+    let mut words = vec![
+        "some",
+        "long",
+        "string",
+        "separated",
+        "by",
+        "single",
+        "white",
+        "spaces",
+        "s",
+        "",
+    ];
+    let mut want = words.clone();
+    want.sort();
+
+    sort_bytes(&mut words);
+    assert_eq!(words, want);
+
+    let mut many: Vec<Vec<u8>> = (0..1000u32)
+        .map(|i| i.wrapping_mul(2_654_435_761).to_string().into_bytes())
+        .collect();
+    let mut want = many.clone();
+    want.sort();
+
+    sort_bytes(&mut many);
+    assert_eq!(many, want);
+}
@@ -0,0 +1,427 @@
+//! # Sort
+//!
+//! `sort` provides a common interface over the sort crates
+//! of the workspace, so that the algorithm can be chosen at runtime.
+//!
+//! [`Merge`] is the default stable sort and [`Quick`], which runs
+//! pattern-defeating quick sort, the default unstable one.
+//!
+//! Every algorithm can be traced, see [`trace`].
+//!
+//! The permutation which sorts a slice can be computed without moving
+//! its elements and applied to other slices, see [`argsort`] and
+//! [`apply_permutation`].
+
+pub use permutation::{
+    apply_permutation, argsort, argsort_by, argsort_by_key, invert_permutation, is_permutation,
+};
+
+mod permutation;
+
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use observe::{SortEvent, SortObserver, Trace};
+
+/// A sorting algorithm.
+///
+/// Every sort crate of the workspace has an implementor.
+/// [`Algorithm`] implements it as well by dispatching to them.
+///
+/// If the comparator panics, the slice is left as a permutation of its
+/// elements, none is lost, duplicated or dropped.
+pub trait Sorter {
+    /// Returns the name of the algorithm.
+    fn name(&self) -> &'static str;
+
+    /// Returns whether equal elements keep their relative order.
+    fn is_stable(&self) -> bool;
+
+    /// Sorts the slice with a comparator function.
+    fn sort_by<T, F>(&self, slice: &mut [T], compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering;
+
+    /// Sorts the slice with a comparator function,
+    /// reporting the operations of the sort to the observer.
+    fn sort_by_observed<T, F, O>(&self, slice: &mut [T], compare: F, observer: &mut O)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        O: SortObserver;
+
+    /// Sorts the slice.
+    fn sort<T>(&self, slice: &mut [T])
+    where
+        T: Ord,
+    {
+        self.sort_by(slice, T::cmp);
+    }
+
+    /// Sorts the slice with a key extraction function.
+    fn sort_by_key<T, K, F>(&self, slice: &mut [T], mut f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.sort_by(slice, |a, b| f(a).cmp(&f(b)));
+    }
+
+    /// Sorts the slice, reporting the operations of the sort to the observer.
+    fn sort_observed<T, O>(&self, slice: &mut [T], observer: &mut O)
+    where
+        T: Ord,
+        O: SortObserver,
+    {
+        self.sort_by_observed(slice, T::cmp, observer);
+    }
+}
+
+/// Sorts using [`bubble`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Bubble;
+
+impl Sorter for Bubble {
+    fn name(&self) -> &'static str {
+        "bubble"
+    }
+
+    fn is_stable(&self) -> bool {
+        true
+    }
+
+    fn sort_by<T, F>(&self, slice: &mut [T], compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        bubble::sort_by(slice, compare);
+    }
+
+    fn sort_by_observed<T, F, O>(&self, slice: &mut [T], compare: F, observer: &mut O)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        O: SortObserver,
+    {
+        bubble::sort_by_observed(slice, compare, observer);
+    }
+}
+
+/// Sorts using [`heap`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Heap;
+
+impl Sorter for Heap {
+    fn name(&self) -> &'static str {
+        "heap"
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn sort_by<T, F>(&self, slice: &mut [T], compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        heap::sort_by(slice, compare);
+    }
+
+    fn sort_by_observed<T, F, O>(&self, slice: &mut [T], compare: F, observer: &mut O)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        O: SortObserver,
+    {
+        heap::sort_by_observed(slice, compare, observer);
+    }
+}
+
+/// Sorts using [`insertion`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Insertion;
+
+impl Sorter for Insertion {
+    fn name(&self) -> &'static str {
+        "insertion"
+    }
+
+    fn is_stable(&self) -> bool {
+        true
+    }
+
+    fn sort_by<T, F>(&self, slice: &mut [T], compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        insertion::sort_by(slice, compare);
+    }
+
+    fn sort_by_observed<T, F, O>(&self, slice: &mut [T], compare: F, observer: &mut O)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        O: SortObserver,
+    {
+        insertion::sort_by_observed(slice, compare, observer);
+    }
+}
+
+/// Sorts using [`merge`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Merge;
+
+impl Sorter for Merge {
+    fn name(&self) -> &'static str {
+        "merge"
+    }
+
+    fn is_stable(&self) -> bool {
+        true
+    }
+
+    fn sort_by<T, F>(&self, slice: &mut [T], compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        merge::sort_by(slice, compare);
+    }
+
+    fn sort_by_observed<T, F, O>(&self, slice: &mut [T], compare: F, observer: &mut O)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        O: SortObserver,
+    {
+        merge::sort_by_observed(slice, compare, observer);
+    }
+}
+
+/// Sorts using [`quick::pdqsort`], the default unstable sort.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Quick;
+
+impl Sorter for Quick {
+    fn name(&self) -> &'static str {
+        "quick"
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn sort_by<T, F>(&self, slice: &mut [T], compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        quick::pdqsort_by(slice, compare);
+    }
+
+    fn sort_by_observed<T, F, O>(&self, slice: &mut [T], compare: F, observer: &mut O)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        O: SortObserver,
+    {
+        quick::pdqsort_by_observed(slice, compare, observer);
+    }
+}
+
+/// The sorting algorithms of the workspace.
+///
+/// An `Algorithm` can be parsed from its name, which makes it
+/// possible to pick the algorithm from configuration.
+///
+/// # Examples
+///
+/// ```
+/// use sort::{Algorithm, Sorter};
+///
+/// let algorithm: Algorithm = "merge".parse().unwrap();
+/// assert_eq!(algorithm, Algorithm::Merge);
+/// assert_eq!(algorithm.name(), "merge");
+/// assert!(algorithm.is_stable());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    Bubble,
+    Heap,
+    Insertion,
+    Merge,
+    Quick,
+}
+
+impl Algorithm {
+    /// All the algorithms, in the order they are declared.
+    pub const ALL: [Algorithm; 5] = [
+        Algorithm::Bubble,
+        Algorithm::Heap,
+        Algorithm::Insertion,
+        Algorithm::Merge,
+        Algorithm::Quick,
+    ];
+}
+
+impl Sorter for Algorithm {
+    fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Bubble => Bubble.name(),
+            Algorithm::Heap => Heap.name(),
+            Algorithm::Insertion => Insertion.name(),
+            Algorithm::Merge => Merge.name(),
+            Algorithm::Quick => Quick.name(),
+        }
+    }
+
+    fn is_stable(&self) -> bool {
+        match self {
+            Algorithm::Bubble => Bubble.is_stable(),
+            Algorithm::Heap => Heap.is_stable(),
+            Algorithm::Insertion => Insertion.is_stable(),
+            Algorithm::Merge => Merge.is_stable(),
+            Algorithm::Quick => Quick.is_stable(),
+        }
+    }
+
+    fn sort_by<T, F>(&self, slice: &mut [T], compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        match self {
+            Algorithm::Bubble => Bubble.sort_by(slice, compare),
+            Algorithm::Heap => Heap.sort_by(slice, compare),
+            Algorithm::Insertion => Insertion.sort_by(slice, compare),
+            Algorithm::Merge => Merge.sort_by(slice, compare),
+            Algorithm::Quick => Quick.sort_by(slice, compare),
+        }
+    }
+
+    fn sort_by_observed<T, F, O>(&self, slice: &mut [T], compare: F, observer: &mut O)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        O: SortObserver,
+    {
+        match self {
+            Algorithm::Bubble => Bubble.sort_by_observed(slice, compare, observer),
+            Algorithm::Heap => Heap.sort_by_observed(slice, compare, observer),
+            Algorithm::Insertion => Insertion.sort_by_observed(slice, compare, observer),
+            Algorithm::Merge => Merge.sort_by_observed(slice, compare, observer),
+            Algorithm::Quick => Quick.sort_by_observed(slice, compare, observer),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = ParseAlgorithmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Algorithm::ALL
+            .iter()
+            .copied()
+            .find(|algorithm| algorithm.name() == s)
+            .ok_or_else(|| ParseAlgorithmError {
+                name: s.to_string(),
+            })
+    }
+}
+
+/// The error returned when parsing an unknown [`Algorithm`] name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseAlgorithmError {
+    name: String,
+}
+
+impl fmt::Display for ParseAlgorithmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown sort algorithm `{}`", self.name)
+    }
+}
+
+impl Error for ParseAlgorithmError {}
+
+/// Sorts the slice using the chosen algorithm.
+///
+/// # Examples
+///
+/// ```
+/// use sort::{sort_with, Algorithm};
+///
+/// for algorithm in Algorithm::ALL.iter() {
+///     let mut nmbrs = vec![4,3,2,5,1];
+///     sort_with(*algorithm, &mut nmbrs);
+///     assert_eq!(nmbrs, vec![1,2,3,4,5]);
+/// }
+/// ```
+pub fn sort_with<T>(algorithm: Algorithm, slice: &mut [T])
+where
+    T: Ord,
+{
+    algorithm.sort(slice);
+}
+
+/// Sorts the slice using the chosen algorithm with a comparator function.
+///
+/// # Examples
+///
+/// ```
+/// use sort::{sort_with_by, Algorithm};
+///
+/// let mut nmbrs = vec![4,3,2,5,1];
+/// sort_with_by(Algorithm::Quick, &mut nmbrs, |a, b| b.cmp(a));
+/// assert_eq!(nmbrs, vec![5,4,3,2,1]);
+/// ```
+pub fn sort_with_by<T, F>(algorithm: Algorithm, slice: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    algorithm.sort_by(slice, compare);
+}
+
+/// Sorts the slice using the chosen algorithm and returns
+/// the operations the sort performed.
+///
+/// Replaying the trace on a copy of the input with [`observe::replay`]
+/// sorts the copy the same way.
+///
+/// # Examples
+///
+/// ```
+/// use sort::{trace, Algorithm};
+///
+/// let input = vec![4,3,2,5,1];
+/// let mut nmbrs = input.clone();
+/// let events = trace(Algorithm::Heap, &mut nmbrs);
+///
+/// assert_eq!(observe::replay(&input, &events), Ok(nmbrs));
+/// ```
+pub fn trace<T>(algorithm: Algorithm, slice: &mut [T]) -> Vec<SortEvent>
+where
+    T: Ord,
+{
+    trace_by(algorithm, slice, T::cmp)
+}
+
+/// Sorts the slice using the chosen algorithm with a comparator
+/// function and returns the operations the sort performed.
+///
+/// # Examples
+///
+/// ```
+/// use sort::{trace_by, Algorithm};
+///
+/// let mut nmbrs = vec![1,2];
+/// let events = trace_by(Algorithm::Bubble, &mut nmbrs, |a, b| b.cmp(a));
+///
+/// assert_eq!(nmbrs, vec![2,1]);
+/// assert!(!events.is_empty());
+/// ```
+pub fn trace_by<T, F>(algorithm: Algorithm, slice: &mut [T], compare: F) -> Vec<SortEvent>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut trace = Trace::new();
+    algorithm.sort_by_observed(slice, compare, &mut trace);
+    trace.into_events()
+}
@@ -0,0 +1,220 @@
+//! Sorting permutations and applying them.
+//!
+//! A permutation of length `n` is a slice holding every index of
+//! `0..n` once. Applying it to a slice moves the element at index
+//! `perm[i]` to index `i`.
+
+use std::cmp::Ordering;
+
+use super::{Algorithm, Sorter};
+
+/// Returns the permutation which sorts the slice, using the chosen algorithm.
+///
+/// See [`argsort_by`].
+///
+/// # Examples
+///
+/// ```
+/// use sort::{argsort, Algorithm};
+///
+/// let nmbrs = vec![30,10,20];
+/// assert_eq!(argsort(Algorithm::Quick, &nmbrs), vec![1,2,0]);
+/// ```
+pub fn argsort<T>(algorithm: Algorithm, slice: &[T]) -> Vec<usize>
+where
+    T: Ord,
+{
+    argsort_by(algorithm, slice, T::cmp)
+}
+
+/// Returns the permutation which sorts the slice, using the chosen
+/// algorithm with a comparator function.
+///
+/// The slice is left untouched, the indices are sorted instead.
+/// Equal elements are ordered by their index, with any algorithm.
+///
+/// # Examples
+///
+/// ```
+/// use sort::{apply_permutation, argsort_by, Algorithm};
+///
+/// let mut names = vec!["carol", "alice", "bob"];
+/// let mut ages = vec![35, 30, 30];
+///
+/// let perm = argsort_by(Algorithm::Merge, &ages, |a, b| b.cmp(a));
+/// assert_eq!(perm, vec![0,1,2]);
+///
+/// let mut perm = argsort_by(Algorithm::Quick, &names, |a, b| a.cmp(b));
+/// apply_permutation(&mut names, &mut perm);
+/// apply_permutation(&mut ages, &mut perm);
+///
+/// assert_eq!(names, vec!["alice", "bob", "carol"]);
+/// assert_eq!(ages, vec![30, 30, 35]);
+/// ```
+pub fn argsort_by<T, F>(algorithm: Algorithm, slice: &[T], mut compare: F) -> Vec<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut perm: Vec<usize> = (0..slice.len()).collect();
+    algorithm.sort_by(&mut perm, |&a, &b| {
+        compare(&slice[a], &slice[b]).then(a.cmp(&b))
+    });
+    perm
+}
+
+/// Returns the permutation which sorts the slice, using the chosen
+/// algorithm with a key extraction function.
+///
+/// See [`argsort_by`].
+///
+/// # Examples
+///
+/// ```
+/// use sort::{argsort_by_key, Algorithm};
+///
+/// let words = vec!["ccc", "a", "bb"];
+/// assert_eq!(argsort_by_key(Algorithm::Insertion, &words, |w| w.len()), vec![1,2,0]);
+/// ```
+pub fn argsort_by_key<T, K, F>(algorithm: Algorithm, slice: &[T], mut f: F) -> Vec<usize>
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    argsort_by(algorithm, slice, |a, b| f(a).cmp(&f(b)))
+}
+
+/// Returns whether the slice holds every index of `0..perm.len()` once.
+///
+/// # Examples
+///
+/// ```
+/// use sort::is_permutation;
+///
+/// assert!(is_permutation(&[2,0,1]));
+/// assert!(!is_permutation(&[2,0,2]));
+/// assert!(!is_permutation(&[3,0,1]));
+/// ```
+pub fn is_permutation(perm: &[usize]) -> bool {
+    let mut seen = vec![false; perm.len()];
+    perm.iter()
+        .all(|&i| i < seen.len() && !std::mem::replace(&mut seen[i], true))
+}
+
+/// Returns the inverse of the permutation, which undoes applying it.
+///
+/// # Panics
+///
+/// Panics if `perm` is not a permutation.
+///
+/// # Examples
+///
+/// ```
+/// use sort::{apply_permutation, invert_permutation};
+///
+/// let mut perm = vec![2,0,1];
+/// let mut inverse = invert_permutation(&perm);
+/// assert_eq!(inverse, vec![1,2,0]);
+///
+/// let mut letters = vec!['a', 'b', 'c'];
+/// apply_permutation(&mut letters, &mut perm);
+/// apply_permutation(&mut letters, &mut inverse);
+/// assert_eq!(letters, vec!['a', 'b', 'c']);
+/// ```
+pub fn invert_permutation(perm: &[usize]) -> Vec<usize> {
+    assert!(is_permutation(perm), "not a permutation");
+
+    let mut inverse = vec![0; perm.len()];
+    for (i, &p) in perm.iter().enumerate() {
+        inverse[p] = i;
+    }
+    inverse
+}
+
+/// Returns whether the index of a permutation is marked as visited.
+///
+/// Indices are marked by flipping their bits. Indices of a slice are
+/// at most `isize::MAX`, so the highest bit is set only on marked ones.
+fn is_marked(index: usize) -> bool {
+    index > isize::MAX as usize
+}
+
+/// Moves the element at index `perm[i]` of the slice to index `i`, in place.
+///
+/// Follows every cycle of the permutation once, swapping its elements
+/// into place. The visited indices are marked within the permutation
+/// itself and restored before returning, so only O(1) extra space is
+/// used. Runs in O(n) time.
+///
+/// # Panics
+///
+/// Panics if the lengths differ or `perm` is not a permutation,
+/// the slice is left as a permutation of its elements and `perm`
+/// is restored.
+///
+/// # Examples
+///
+/// ```
+/// use sort::apply_permutation;
+///
+/// let mut letters = vec!['c', 'a', 'b'];
+/// let mut perm = vec![1,2,0];
+/// apply_permutation(&mut letters, &mut perm);
+///
+/// assert_eq!(letters, vec!['a', 'b', 'c']);
+/// assert_eq!(perm, vec![1,2,0]);
+/// ```
+pub fn apply_permutation<T>(slice: &mut [T], perm: &mut [usize]) {
+    assert_eq!(slice.len(), perm.len(), "the lengths differ");
+    assert!(perm.iter().all(|&p| p < perm.len()), "not a permutation");
+
+    let mut valid = true;
+    'cycles: for start in 0..perm.len() {
+        if is_marked(perm[start]) {
+            continue;
+        }
+
+        // Every step moves the next element of the cycle into place.
+        let mut curr = start;
+        loop {
+            let next = perm[curr];
+            perm[curr] = !next;
+            if next == start {
+                break;
+            }
+            if is_marked(perm[next]) {
+                valid = false;
+                break 'cycles;
+            }
+            slice.swap(curr, next);
+            curr = next;
+        }
+    }
+
+    for p in perm.iter_mut() {
+        if is_marked(*p) {
+            *p = !*p;
+        }
+    }
+    assert!(valid, "not a permutation");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_permutation_restores_invalid_permutation() {
+        for perm in &[vec![1, 1, 0], vec![0, 3, 1], vec![2, 2, 2]] {
+            let mut slice = vec!['a', 'b', 'c'];
+            let mut invalid = perm.clone();
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                apply_permutation(&mut slice, &mut invalid)
+            }));
+
+            assert!(result.is_err(), "{:?}", perm);
+            assert_eq!(&invalid, perm);
+            slice.sort_unstable();
+            assert_eq!(slice, vec!['a', 'b', 'c']);
+        }
+    }
+}
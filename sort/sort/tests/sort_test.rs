@@ -0,0 +1,134 @@
+use sort::{
+    apply_permutation, argsort, argsort_by, argsort_by_key, invert_permutation, is_permutation,
+    sort_with, trace, trace_by, Algorithm, Bubble, Merge, Sorter,
+};
+
+#[test]
+fn sort_with_every_algorithm() {
+    for algorithm in Algorithm::ALL.iter() {
+        let mut things = vec![
+            1, 5, 3, 2, 66, 7, 5234, 56, 4534, 65453, 45, 3543, 4325, 3432,
+        ];
+        sort_with(*algorithm, &mut things);
+        assert_eq!(
+            things,
+            vec![1, 2, 3, 5, 7, 45, 56, 66, 3432, 3543, 4325, 4534, 5234, 65453]
+        );
+    }
+}
+
+#[test]
+fn sorter_is_generic_over_implementors() {
+    fn sort_desc<S: Sorter>(sorter: S, slice: &mut [i32]) {
+        sorter.sort_by(slice, |a, b| b.cmp(a));
+    }
+
+    let mut things = vec![4, 1, 6, 3, 5, 2];
+    sort_desc(Bubble, &mut things);
+    assert_eq!(things, vec![6, 5, 4, 3, 2, 1]);
+
+    let mut pairs = vec![(3, 'a'), (1, 'b'), (3, 'c'), (1, 'd')];
+    Merge.sort_by_key(&mut pairs, |p| p.0);
+    assert_eq!(pairs, vec![(1, 'b'), (1, 'd'), (3, 'a'), (3, 'c')]);
+}
+
+#[test]
+fn algorithm_parses_from_name() {
+    for algorithm in Algorithm::ALL.iter() {
+        assert_eq!(algorithm.to_string().parse(), Ok(*algorithm));
+    }
+
+    let err = "bogo".parse::<Algorithm>().unwrap_err();
+    assert_eq!(err.to_string(), "unknown sort algorithm `bogo`");
+}
+
+#[test]
+fn trace_replays_every_algorithm() {
+    let input: Vec<i32> = (0..200).map(|i| (i * 7919) % 61).collect();
+    let mut want = input.clone();
+    want.sort();
+
+    for algorithm in Algorithm::ALL.iter() {
+        let mut things = input.clone();
+        let events = trace(*algorithm, &mut things);
+        assert_eq!(things, want);
+        assert_eq!(observe::replay(&input, &events), Ok(want.clone()));
+    }
+}
+
+#[test]
+fn trace_by_replays_with_comparator() {
+    let input = vec![(3, 'a'), (1, 'b'), (3, 'c'), (1, 'd'), (2, 'e')];
+
+    for algorithm in Algorithm::ALL.iter() {
+        let mut pairs = input.clone();
+        let events = trace_by(*algorithm, &mut pairs, |a, b| b.0.cmp(&a.0));
+        let replayed = observe::replay_by(&input, &events, |a, b| b.0.cmp(&a.0));
+        assert_eq!(replayed, Ok(pairs));
+    }
+}
+
+#[test]
+fn argsort_orders_ties_by_index_with_every_algorithm() {
+    let keys = vec![3, 1, 2, 1, 3, 0, 2, 1];
+    for &algorithm in Algorithm::ALL.iter() {
+        let perm = argsort(algorithm, &keys);
+        assert_eq!(perm, vec![5, 1, 3, 7, 2, 6, 0, 4], "{}", algorithm);
+        assert!(is_permutation(&perm));
+
+        let perm = argsort_by_key(algorithm, &keys, |&k| std::cmp::Reverse(k));
+        assert_eq!(perm, vec![0, 4, 2, 6, 1, 3, 7, 5], "{}", algorithm);
+    }
+    assert_eq!(argsort::<i32>(Algorithm::Merge, &[]), vec![]);
+}
+
+#[test]
+fn apply_permutation_reorders_parallel_columns() {
+    let mut names = vec!["dan", "ann", "cid", "bob", "eve"];
+    let mut scores = vec![4, 1, 3, 2, 5];
+
+    let mut perm = argsort_by(Algorithm::Quick, &names, |a, b| a.cmp(b));
+    let inverse = invert_permutation(&perm);
+    apply_permutation(&mut names, &mut perm);
+    apply_permutation(&mut scores, &mut perm);
+
+    assert_eq!(names, vec!["ann", "bob", "cid", "dan", "eve"]);
+    assert_eq!(scores, vec![1, 2, 3, 4, 5]);
+    assert_eq!(perm, vec![1, 3, 2, 0, 4]);
+
+    let mut inverse = inverse;
+    apply_permutation(&mut scores, &mut inverse);
+    assert_eq!(scores, vec![4, 1, 3, 2, 5]);
+}
+
+#[test]
+fn permutations_compose_with_their_inverse() {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    for len in 0..64 {
+        let keys: Vec<u64> = (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state % 16
+            })
+            .collect();
+
+        let mut perm = argsort(Algorithm::Heap, &keys);
+        let mut inverse = invert_permutation(&perm);
+        assert_eq!(invert_permutation(&inverse), perm);
+
+        let mut sorted = keys.clone();
+        apply_permutation(&mut sorted, &mut perm);
+        let mut expected = keys.clone();
+        expected.sort();
+        assert_eq!(sorted, expected);
+
+        apply_permutation(&mut sorted, &mut inverse);
+        assert_eq!(sorted, keys);
+    }
+
+    assert!(is_permutation(&[]));
+    assert!(!is_permutation(&[1, 1]));
+    assert!(!is_permutation(&[0, 2]));
+}
@@ -0,0 +1,493 @@
+//! # Sort testing
+//!
+//! `sort-testing` checks sorts against [`slice::sort`] over generated
+//! inputs, see [`Harness`]. The inputs cover every [`Pattern`] at the
+//! edge case lengths: empty, a single element and the lengths around
+//! the powers of two, where sorts usually switch strategies.
+//!
+//! The inputs are generated by a seedable [`Rng`], so a failure
+//! can be reproduced from the seed in its message.
+//!
+//! Panic safety is checked by injecting a panic into the comparator,
+//! see [`Harness::check_panic_safety`].
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+/// A xorshift64* pseudo random number generator.
+///
+/// Good enough for generating inputs, the same seed gives the same inputs.
+///
+/// # Examples
+///
+/// ```
+/// use sort_testing::Rng;
+///
+/// let mut a = Rng::new(7);
+/// let mut b = Rng::new(7);
+///
+/// assert_eq!(a.next_u64(), b.next_u64());
+/// assert!(a.below(10) < 10);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Creates a generator from the seed.
+    pub fn new(seed: u64) -> Self {
+        // The state must not be zero.
+        Rng {
+            state: seed ^ 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+
+    /// Returns the next pseudo random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Returns a pseudo random number within [0, bound).
+    ///
+    /// # Panics
+    ///
+    /// Panics when `bound` is 0.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+/// The shape of a generated input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pattern {
+    /// Random values, with few duplicates.
+    Random,
+    /// Random values out of 4 distinct ones.
+    FewUnique,
+    /// The same value everywhere.
+    AllEqual,
+    /// Ascending values.
+    Sorted,
+    /// Descending values.
+    Reversed,
+}
+
+impl Pattern {
+    /// All the patterns, in the order they are declared.
+    pub const ALL: [Pattern; 5] = [
+        Pattern::Random,
+        Pattern::FewUnique,
+        Pattern::AllEqual,
+        Pattern::Sorted,
+        Pattern::Reversed,
+    ];
+
+    /// Generates `len` values of the pattern.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sort_testing::{Pattern, Rng};
+    ///
+    /// let mut rng = Rng::new(1);
+    ///
+    /// assert_eq!(Pattern::Reversed.generate(3, &mut rng), vec![2, 1, 0]);
+    /// assert!(Pattern::FewUnique.generate(10, &mut rng).iter().all(|&v| v < 4));
+    /// ```
+    pub fn generate(&self, len: usize, rng: &mut Rng) -> Vec<u32> {
+        match self {
+            Pattern::Random => (0..len).map(|_| rng.next_u64() as u32).collect(),
+            Pattern::FewUnique => (0..len).map(|_| rng.below(4) as u32).collect(),
+            Pattern::AllEqual => vec![7; len],
+            Pattern::Sorted => (0..len as u32).collect(),
+            Pattern::Reversed => (0..len as u32).rev().collect(),
+        }
+    }
+}
+
+/// A generated input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Input {
+    pub pattern: Pattern,
+    pub values: Vec<u32>,
+    /// The seed of the harness which generated the input.
+    pub seed: u64,
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} input of length {} (seed {:#x})",
+            self.pattern,
+            self.values.len(),
+            self.seed
+        )
+    }
+}
+
+/// An element with a key to sort by and a tag to tell equal keys apart.
+///
+/// The tag of a generated element is its index in the input, so after a
+/// stable sort the elements with equal keys have increasing tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tagged {
+    pub key: u32,
+    pub tag: usize,
+}
+
+/// Tags the values with their indices.
+///
+/// # Examples
+///
+/// ```
+/// use sort_testing::{tag, Tagged};
+///
+/// assert_eq!(tag(&[5, 5]), vec![Tagged { key: 5, tag: 0 }, Tagged { key: 5, tag: 1 }]);
+/// ```
+pub fn tag(values: &[u32]) -> Vec<Tagged> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(tag, &key)| Tagged { key, tag })
+        .collect()
+}
+
+/// An element which counts how many times it has been dropped.
+///
+/// A sort which duplicates an element drops it twice, one which
+/// loses an element never drops it.
+#[derive(Debug)]
+pub struct Tracked<'a> {
+    pub key: u32,
+    /// The index of the element in the input.
+    pub id: usize,
+    drops: &'a AtomicUsize,
+}
+
+impl<'a> Tracked<'a> {
+    /// Creates an element reporting its drop to `drops`.
+    pub fn new(key: u32, id: usize, drops: &'a AtomicUsize) -> Self {
+        Tracked { key, id, drops }
+    }
+}
+
+impl Drop for Tracked<'_> {
+    fn drop(&mut self) {
+        self.drops.fetch_add(1, AtomicOrdering::SeqCst);
+    }
+}
+
+/// Returns the edge case lengths up to `2^max_power + 1`: 0, 1, 2, 3
+/// and `2^k - 1`, `2^k`, `2^k + 1` for every `k` up to `max_power`.
+///
+/// # Examples
+///
+/// ```
+/// use sort_testing::lengths;
+///
+/// assert_eq!(lengths(3), vec![0, 1, 2, 3, 4, 5, 7, 8, 9]);
+/// ```
+pub fn lengths(max_power: u32) -> Vec<usize> {
+    let mut lengths = vec![0, 1, 2, 3];
+    for k in 2..=max_power {
+        let power = 1 << k;
+        for len in [power - 1, power, power + 1].iter() {
+            if !lengths.contains(len) {
+                lengths.push(*len);
+            }
+        }
+    }
+
+    lengths
+}
+
+/// Returns whether `b` holds the same elements as `a`, each the same number of times.
+///
+/// # Examples
+///
+/// ```
+/// use sort_testing::is_permutation;
+///
+/// assert!(is_permutation(&[1, 2, 2], &[2, 1, 2]));
+/// assert!(!is_permutation(&[1, 2, 2], &[1, 1, 2]));
+/// ```
+pub fn is_permutation<T>(a: &[T], b: &[T]) -> bool
+where
+    T: Clone + Ord,
+{
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    a.sort();
+    b.sort();
+    a == b
+}
+
+/// Checks sorts against [`slice::sort`] over generated inputs.
+///
+/// # Examples
+///
+/// ```
+/// use sort_testing::Harness;
+///
+/// let harness = Harness::default().seed(42).max_power(6);
+///
+/// harness.check_sort("std unstable", |s| s.sort_unstable());
+/// harness.check_stable("std stable", |s| s.sort_by_key(|t| t.key));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Harness {
+    /// The seed of the generated inputs.
+    pub seed: u64,
+    /// The inputs are at most `2^max_power + 1` elements long, defaults to 10.
+    pub max_power: u32,
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Harness {
+            seed: 0x5eed,
+            max_power: 10,
+        }
+    }
+}
+
+impl Harness {
+    /// Sets the seed of the generated inputs.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets the largest power of two around which input lengths are generated.
+    pub fn max_power(mut self, max_power: u32) -> Self {
+        self.max_power = max_power;
+        self
+    }
+
+    /// Returns an input of every pattern for every edge case length.
+    pub fn inputs(&self) -> Vec<Input> {
+        let mut rng = Rng::new(self.seed);
+        let mut inputs = Vec::new();
+
+        for &pattern in Pattern::ALL.iter() {
+            for len in lengths(self.max_power) {
+                inputs.push(Input {
+                    pattern,
+                    values: pattern.generate(len, &mut rng),
+                    seed: self.seed,
+                });
+            }
+        }
+
+        inputs
+    }
+
+    /// Checks that the sort leaves every input as a permutation of itself,
+    /// equal to the input sorted by [`slice::sort`].
+    ///
+    /// # Panics
+    ///
+    /// Panics with the `name` of the sort and the failing input.
+    pub fn check_sort<S>(&self, name: &str, mut sort: S)
+    where
+        S: FnMut(&mut [u32]),
+    {
+        for input in self.inputs() {
+            let mut got = input.values.clone();
+            sort(&mut got);
+
+            assert!(
+                is_permutation(&input.values, &got),
+                "{} lost or duplicated elements of the {}",
+                name,
+                input
+            );
+
+            let mut want = input.values.clone();
+            want.sort();
+            assert!(
+                got == want,
+                "{} differs from slice::sort on the {}",
+                name,
+                input
+            );
+        }
+    }
+
+    /// Checks that the sort, which sorts by [`Tagged::key`], keeps
+    /// the elements with equal keys in their input order.
+    ///
+    /// The keys are reduced to a few distinct values, so that
+    /// every input has many equal keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the `name` of the sort and the failing input.
+    pub fn check_stable<S>(&self, name: &str, mut sort: S)
+    where
+        S: FnMut(&mut [Tagged]),
+    {
+        for mut input in self.inputs() {
+            for value in input.values.iter_mut() {
+                *value %= 5;
+            }
+
+            let mut got = tag(&input.values);
+            sort(&mut got);
+
+            let mut want = tag(&input.values);
+            want.sort_by_key(|t| t.key);
+            assert!(got == want, "{} is not stable on the {}", name, input);
+        }
+    }
+
+    /// Checks that the sort leaves every input as a permutation of itself
+    /// when the comparator panics midway, without dropping any element.
+    ///
+    /// Every input is sorted once to count the comparisons, then again
+    /// for several `n` smaller than the count, with a comparator which
+    /// panics on its `n + 1`-th call. The sort must pass the comparator
+    /// it is given to the sort under test.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the `name` of the sort and the failing input.
+    pub fn check_panic_safety<S>(&self, name: &str, mut sort: S)
+    where
+        S: FnMut(&mut [Tracked<'_>], &(dyn Fn(&Tracked<'_>, &Tracked<'_>) -> Ordering + Sync)),
+    {
+        let inputs = self
+            .inputs()
+            .into_iter()
+            .filter(|input| matches!(input.pattern, Pattern::Random | Pattern::FewUnique));
+
+        for input in inputs {
+            let total = self.count_comparisons(&input, &mut sort);
+            let mut panic_after = vec![0, 1, total / 3, total / 2, total.saturating_sub(1)];
+            panic_after.dedup();
+
+            for n in panic_after.into_iter().filter(|&n| n < total) {
+                let drops = AtomicUsize::new(0);
+                let comparisons = AtomicUsize::new(0);
+                let mut elems: Vec<Tracked<'_>> = input
+                    .values
+                    .iter()
+                    .enumerate()
+                    .map(|(id, &key)| Tracked::new(key, id, &drops))
+                    .collect();
+
+                let compare = |a: &Tracked<'_>, b: &Tracked<'_>| {
+                    if comparisons.fetch_add(1, AtomicOrdering::SeqCst) == n {
+                        panic!("injected panic after {} comparisons", n);
+                    }
+                    a.key.cmp(&b.key)
+                };
+                let _ = panic::catch_unwind(AssertUnwindSafe(|| sort(&mut elems, &compare)));
+
+                assert_eq!(
+                    drops.load(AtomicOrdering::SeqCst),
+                    0,
+                    "{} dropped elements after a panic after {} comparisons on the {}",
+                    name,
+                    n,
+                    input
+                );
+
+                let ids: Vec<usize> = elems.iter().map(|elem| elem.id).collect();
+                assert!(
+                    is_permutation(&ids, &(0..ids.len()).collect::<Vec<_>>()),
+                    "{} lost or duplicated elements after a panic after {} comparisons on the {}",
+                    name,
+                    n,
+                    input
+                );
+
+                drop(elems);
+                assert_eq!(drops.load(AtomicOrdering::SeqCst), input.values.len());
+            }
+        }
+    }
+
+    /// Returns how many comparisons the sort makes on the input.
+    fn count_comparisons<S>(&self, input: &Input, sort: &mut S) -> usize
+    where
+        S: FnMut(&mut [Tracked<'_>], &(dyn Fn(&Tracked<'_>, &Tracked<'_>) -> Ordering + Sync)),
+    {
+        let drops = AtomicUsize::new(0);
+        let comparisons = AtomicUsize::new(0);
+        let mut elems: Vec<Tracked<'_>> = input
+            .values
+            .iter()
+            .enumerate()
+            .map(|(id, &key)| Tracked::new(key, id, &drops))
+            .collect();
+
+        sort(&mut elems, &|a, b| {
+            comparisons.fetch_add(1, AtomicOrdering::SeqCst);
+            a.key.cmp(&b.key)
+        });
+        comparisons.load(AtomicOrdering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lengths() {
+        let lengths = lengths(10);
+        assert_eq!(lengths.len(), 4 + 3 * 9 - 1);
+        assert!(lengths.contains(&1023));
+        assert!(lengths.contains(&1025));
+        assert!(lengths.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_inputs_are_reproducible() {
+        let harness = Harness::default().max_power(4);
+        assert_eq!(harness.inputs(), harness.inputs());
+        assert_ne!(harness.inputs(), harness.seed(1).inputs());
+    }
+
+    #[test]
+    #[should_panic(expected = "lost or duplicated elements")]
+    fn test_check_sort_catches_lost_elements() {
+        Harness::default().check_sort("broken", |s| {
+            if s.len() > 1 {
+                s[0] = s[1];
+            }
+            s.sort();
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "lost or duplicated elements after a panic")]
+    fn test_check_panic_safety_catches_duplicates() {
+        Harness::default()
+            .max_power(4)
+            .check_panic_safety("duplicating", |s, compare| {
+                let sorted = panic::catch_unwind(AssertUnwindSafe(|| s.sort_by(compare)));
+                if let Err(payload) = sorted {
+                    // Overwrites the second element with the first one,
+                    // as a sort without a guard for its moves would.
+                    unsafe { std::ptr::copy_nonoverlapping(&s[0], &mut s[1], 1) };
+                    panic::resume_unwind(payload);
+                }
+            });
+    }
+
+    #[test]
+    #[should_panic(expected = "is not stable")]
+    fn test_check_stable_catches_reordering() {
+        Harness::default().check_stable("unstable", |s| s.sort_by_key(|t| (t.key, !t.tag)));
+    }
+}
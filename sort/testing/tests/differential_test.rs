@@ -0,0 +1,83 @@
+use quick::{Partition, SortOptions};
+use sort::{Algorithm, Sorter};
+use sort_testing::Harness;
+
+#[test]
+fn every_algorithm_matches_slice_sort() {
+    let harness = Harness::default();
+
+    for algorithm in Algorithm::ALL.iter() {
+        harness.check_sort(algorithm.name(), |s| algorithm.sort(s));
+    }
+
+    harness.check_sort("heap::sort", heap::sort);
+    harness.check_sort("insertion::binary_sort", insertion::binary_sort);
+    for gaps in [
+        insertion::Gaps::Ciura,
+        insertion::Gaps::Tokuda,
+        insertion::Gaps::Sedgewick,
+    ]
+    .iter()
+    {
+        harness.check_sort("insertion::shell_sort", |s| insertion::shell_sort(s, *gaps));
+    }
+
+    harness.check_sort("merge::sort_in_place", merge::sort_in_place);
+    harness.check_sort("merge::timsort", merge::timsort);
+    harness.check_sort("merge::par_sort", |s| {
+        merge::par_sort_by_grain(s, 16, |a, b| a.cmp(b))
+    });
+
+    harness.check_sort("quick::introsort", quick::introsort);
+    harness.check_sort("quick::pdqsort", quick::pdqsort);
+    harness.check_sort("quick::par_sort", |s| {
+        quick::par_sort_by_grain(s, 16, |a, b| a.cmp(b))
+    });
+    for partition in [Partition::TwoWay, Partition::ThreeWay, Partition::DualPivot].iter() {
+        harness.check_sort("quick::sort_with_options", |s| {
+            quick::sort_with_options(s, SortOptions::default().partition(*partition))
+        });
+    }
+
+    harness.check_sort("radix::sort", radix::sort);
+    // The values span the whole u32 range, they are sorted
+    // by two stable passes over their 16 bit halves.
+    harness.check_sort("counting::sort_by_key", |s| {
+        counting::sort_by_key(s, 0..1 << 16, |&v| (v & 0xffff) as usize).unwrap();
+        counting::sort_by_key(s, 0..1 << 16, |&v| (v >> 16) as usize).unwrap();
+    });
+}
+
+#[test]
+fn stable_algorithms_keep_equal_elements_in_order() {
+    let harness = Harness::default();
+
+    for algorithm in Algorithm::ALL.iter().filter(|a| a.is_stable()) {
+        harness.check_stable(algorithm.name(), |s| algorithm.sort_by_key(s, |t| t.key));
+    }
+
+    harness.check_stable("insertion::binary_sort", |s| {
+        insertion::binary_sort_by_key(s, |t| t.key)
+    });
+    harness.check_stable("merge::sort_in_place", |s| {
+        merge::sort_in_place_by_key(s, |t| t.key)
+    });
+    harness.check_stable("merge::timsort", |s| merge::timsort_by_key(s, |t| t.key));
+    harness.check_stable("merge::par_sort", |s| {
+        merge::par_sort_by_grain(s, 16, |a, b| a.key.cmp(&b.key))
+    });
+    harness.check_stable("radix::sort_by_key", |s| radix::sort_by_key(s, |t| t.key));
+    harness.check_stable("counting::sort_by_key", |s| {
+        counting::sort_by_key(s, 0..5, |t| t.key as usize).unwrap()
+    });
+}
+
+#[test]
+fn algorithms_agree_across_seeds() {
+    for seed in 1..4 {
+        let harness = Harness::default().seed(seed).max_power(7);
+        for algorithm in Algorithm::ALL.iter() {
+            harness.check_sort(algorithm.name(), |s| algorithm.sort(s));
+        }
+    }
+}
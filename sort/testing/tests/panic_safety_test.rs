@@ -0,0 +1,44 @@
+use sort::{Algorithm, Sorter};
+use sort_testing::Harness;
+
+#[test]
+fn every_algorithm_is_panic_safe() {
+    let harness = Harness::default().max_power(7);
+
+    for algorithm in Algorithm::ALL.iter() {
+        harness.check_panic_safety(algorithm.name(), |s, compare| algorithm.sort_by(s, compare));
+    }
+}
+
+#[test]
+fn buffered_and_copying_sorts_are_panic_safe() {
+    let harness = Harness::default().max_power(9);
+
+    harness.check_panic_safety("merge::sort", |s, compare| merge::sort_by(s, compare));
+    harness.check_panic_safety("merge::timsort", |s, compare| merge::timsort_by(s, compare));
+    harness.check_panic_safety("merge::par_sort", |s, compare| {
+        merge::par_sort_by_grain(s, 16, compare)
+    });
+    harness.check_panic_safety("insertion::binary_sort", |s, compare| {
+        insertion::binary_sort_by(s, compare)
+    });
+    harness.check_panic_safety("quick::pdqsort", |s, compare| quick::pdqsort_by(s, compare));
+}
+
+#[test]
+fn in_place_sorts_are_panic_safe() {
+    let harness = Harness::default().max_power(7);
+
+    harness.check_panic_safety("merge::sort_in_place", |s, compare| {
+        merge::sort_in_place_by(s, compare)
+    });
+    harness.check_panic_safety("quick::introsort", |s, compare| {
+        quick::introsort_by(s, compare)
+    });
+    harness.check_panic_safety("quick::par_sort", |s, compare| {
+        quick::par_sort_by_grain(s, 16, compare)
+    });
+    harness.check_panic_safety("insertion::shell_sort", |s, compare| {
+        insertion::shell_sort_by(s, insertion::Gaps::Ciura, compare)
+    });
+}
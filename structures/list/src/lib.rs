@@ -1,4 +1,6 @@
 //! A singly linked listed where each element is heap allocated.
+use std::cmp::Ordering;
+
 type Link<T> = Option<Box<Node<T>>>;
 
 struct Node<T> {
@@ -94,6 +96,148 @@ impl<T> List<T> {
         })
     }
 
+    /// Sorts the list.
+    ///
+    /// See [`List::sort_by`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use list::List;
+    ///
+    /// let mut list = List::new();
+    /// list.push(2);
+    /// list.push(3);
+    /// list.push(1);
+    ///
+    /// list.sort();
+    ///
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+    /// ```
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp)
+    }
+
+    /// Sorts the list with a comparator function.
+    ///
+    /// Uses a bottom-up merge sort which relinks the nodes of the list,
+    /// nothing is allocated and no element is moved. Runs in O(n log(n))
+    /// time and O(1) space.
+    ///
+    /// The sort is stable, equal elements keep their relative order.
+    ///
+    /// If the comparator panics, the list keeps all its elements,
+    /// in an unspecified order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use list::List;
+    ///
+    /// let mut list = List::new();
+    /// list.push(1);
+    /// list.push(3);
+    /// list.push(2);
+    ///
+    /// list.sort_by(|a, b| b.cmp(a));
+    ///
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![3, 2, 1]);
+    /// ```
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut width = 1;
+        loop {
+            // Every pass merges the neighbouring sorted runs of `width` nodes.
+            let rest = self.head.take();
+            let mut pass = Merge::new(&mut self.head, rest);
+            let mut merges = 0;
+
+            while pass.rest.head.is_some() {
+                pass.left.head = pass.rest.head.take();
+                pass.right.head = split_off(&mut pass.left.head, width);
+                pass.rest.head = split_off(&mut pass.right.head, width);
+
+                pass.merge(&mut compare);
+                merges += 1;
+            }
+
+            if merges <= 1 {
+                return;
+            }
+            width *= 2;
+        }
+    }
+
+    /// Sorts the list with a key extraction function.
+    ///
+    /// See [`List::sort_by`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use list::List;
+    ///
+    /// let mut list = List::new();
+    /// list.push((2, 'a'));
+    /// list.push((1, 'b'));
+    /// list.push((2, 'c'));
+    ///
+    /// list.sort_by_key(|pair| pair.0);
+    ///
+    /// assert_eq!(
+    ///     list.iter().copied().collect::<Vec<_>>(),
+    ///     vec![(1, 'b'), (2, 'c'), (2, 'a')]
+    /// );
+    /// ```
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.sort_by(|a, b| f(a).cmp(&f(b)))
+    }
+
+    /// Merges two sorted lists into one sorted list.
+    ///
+    /// The nodes of both lists are relinked, equal elements
+    /// of `self` come before those of `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use list::List;
+    ///
+    /// let mut odd = List::new();
+    /// odd.push(3);
+    /// odd.push(1);
+    ///
+    /// let mut even = List::new();
+    /// even.push(4);
+    /// even.push(2);
+    ///
+    /// let merged = odd.merge_sorted(even);
+    ///
+    /// assert_eq!(merged.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    /// ```
+    pub fn merge_sorted(mut self, mut other: Self) -> Self
+    where
+        T: Ord,
+    {
+        let mut merged = List::new();
+        let mut merge = Merge::new(&mut merged.head, None);
+        merge.left.head = self.head.take();
+        merge.right.head = other.head.take();
+        merge.merge(&mut T::cmp);
+        drop(merge);
+
+        merged
+    }
+
     /// Returns an immutable iterator
     /// over the contents of the list.
     ///
@@ -160,6 +304,83 @@ impl<T> List<T> {
     }
 }
 
+/// Detaches and returns the nodes after the first `n` nodes of the list.
+fn split_off<T>(head: &mut Link<T>, n: usize) -> Link<T> {
+    let mut curr = head;
+    for _ in 0..n {
+        curr = &mut curr.as_mut()?.next;
+    }
+
+    curr.take()
+}
+
+/// Returns the link after the last node of the list.
+fn last_link<T>(mut link: &mut Link<T>) -> &mut Link<T> {
+    while link.is_some() {
+        link = &mut link.as_mut().unwrap().next;
+    }
+
+    link
+}
+
+/// Merges sorted lists, linking the merged nodes one after another.
+///
+/// When dropped, the nodes of `left`, `right` and `rest`, which aren't
+/// merged yet, are relinked after the merged ones. So if the comparator
+/// panics, the list being merged into keeps all its elements.
+struct Merge<'a, T> {
+    /// The link after the last merged node.
+    tail: Option<&'a mut Link<T>>,
+    left: List<T>,
+    right: List<T>,
+    rest: List<T>,
+}
+
+impl<'a, T> Merge<'a, T> {
+    fn new(tail: &'a mut Link<T>, rest: Link<T>) -> Self {
+        Merge {
+            tail: Some(tail),
+            left: List::new(),
+            right: List::new(),
+            rest: List { head: rest },
+        }
+    }
+
+    /// Merges `left` and `right` after the merged nodes.
+    fn merge<F>(&mut self, compare: &mut F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        loop {
+            // Ties are taken from the left list to keep the merge stable.
+            let from = match (&self.left.head, &self.right.head) {
+                (Some(l), Some(r)) if compare(&r.data, &l.data) == Ordering::Less => {
+                    &mut self.right
+                }
+                (Some(_), _) => &mut self.left,
+                (None, Some(_)) => &mut self.right,
+                (None, None) => return,
+            };
+
+            let mut node = from.head.take().unwrap();
+            from.head = node.next.take();
+            let tail = self.tail.take().unwrap();
+            self.tail = Some(&mut tail.insert(node).next);
+        }
+    }
+}
+
+impl<'a, T> Drop for Merge<'a, T> {
+    fn drop(&mut self) {
+        if let Some(mut tail) = self.tail.take() {
+            for list in [&mut self.left, &mut self.right, &mut self.rest].iter_mut() {
+                *tail = list.head.take();
+                tail = last_link(tail);
+            }
+        }
+    }
+}
+
 pub struct IntoIter<T>(List<T>);
 
 impl<T> Iterator for IntoIter<T> {
@@ -210,3 +431,106 @@ impl<'a, T> Iterator for IterMut<'a, T> {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::{self, AssertUnwindSafe};
+
+    /// An element ordered by its key only, the tag tells equal ones apart.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Tagged {
+        key: u32,
+        tag: usize,
+    }
+
+    impl PartialOrd for Tagged {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Tagged {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.key.cmp(&other.key)
+        }
+    }
+
+    fn from_vec<T>(elems: Vec<T>) -> List<T> {
+        let mut list = List::new();
+        for elem in elems.into_iter().rev() {
+            list.push(elem);
+        }
+        list
+    }
+
+    fn to_vec<T: Clone>(list: &List<T>) -> Vec<T> {
+        list.iter().cloned().collect()
+    }
+
+    fn tagged(len: usize, modulus: u32) -> Vec<Tagged> {
+        (0..len)
+            .map(|tag| Tagged {
+                key: (tag as u32).wrapping_mul(2_654_435_761) % modulus,
+                tag,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sort_is_stable_across_passes() {
+        for &len in &[0, 1, 2, 3, 7, 8, 9, 31, 32, 33, 255, 256, 257, 300] {
+            for &modulus in &[1, 3, 1000] {
+                let elems = tagged(len, modulus);
+                let mut list = from_vec(elems.clone());
+                list.sort();
+
+                let mut expected = elems;
+                expected.sort();
+                assert_eq!(to_vec(&list), expected, "{} {}", len, modulus);
+            }
+        }
+    }
+
+    #[test]
+    fn test_merge_sorted_takes_ties_from_self_first() {
+        let mut left = tagged(65, 4);
+        left.iter_mut().for_each(|t| t.tag += 1000);
+        left.sort();
+        let mut right = tagged(33, 4);
+        right.sort();
+
+        let merged = from_vec(left.clone()).merge_sorted(from_vec(right.clone()));
+
+        let mut expected = left;
+        expected.extend(right);
+        expected.sort();
+        assert_eq!(to_vec(&merged), expected);
+        assert_eq!(to_vec(&List::<Tagged>::new().merge_sorted(List::new())), vec![]);
+    }
+
+    #[test]
+    fn test_sort_by_keeps_all_elements_when_the_comparator_panics() {
+        for &len in &[17, 300] {
+            for &after in &[0, 1, len / 2, len + len / 2] {
+                let elems = tagged(len, 1000);
+                let mut list = from_vec(elems.clone());
+
+                let mut comparisons = 0;
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    list.sort_by(|a, b| {
+                        comparisons += 1;
+                        if comparisons > after {
+                            panic!("comparator panicked");
+                        }
+                        a.cmp(b)
+                    })
+                }));
+                assert!(result.is_err(), "{} {}", len, after);
+
+                let mut tags: Vec<usize> = list.iter().map(|t| t.tag).collect();
+                tags.sort_unstable();
+                assert_eq!(tags, (0..len).collect::<Vec<_>>(), "{} {}", len, after);
+            }
+        }
+    }
+}